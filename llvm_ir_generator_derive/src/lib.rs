@@ -0,0 +1,133 @@
+//! Derive macros for `llvm_ir_generator::types::rust_type`.
+//!
+//! `#[derive(LlvmType)]` maps a `#[repr(C)]` or `#[repr(packed)]` struct to an identified
+//! LLVM struct type named after the struct, or after `#[llvm(name = "...")]`.
+//! It also adds `llvm_named_struct()` to the struct, for registering the type with a module.
+//! Structs may have lifetime parameters, but no type or const parameters.
+//! `#[derive(LlvmConstant)]` writes values of such a struct as struct constants.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, GenericParam, LitStr};
+
+struct Layout {
+    packed: bool
+}
+
+fn layout(input: &DeriveInput) -> Result<Layout, Error> {
+    let mut c = false;
+    let mut packed = false;
+    for attribute in input.attrs.iter().filter(|it| it.path().is_ident("repr")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                c = true;
+            } else if meta.path.is_ident("packed") {
+                if meta.input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let alignment: syn::LitInt = content.parse()?;
+                    if alignment.base10_parse::<usize>()? != 1 {
+                        return Err(meta.error("only `packed(1)` can be expressed as an LLVM struct type"));
+                    }
+                }
+                packed = true;
+            } else if meta.path.is_ident("align") {
+                return Err(meta.error("`align` can't be expressed as an LLVM struct type"));
+            } else {
+                return Err(meta.error("unsupported representation, use `#[repr(C)]` or `#[repr(packed)]`"));
+            }
+            Ok(())
+        })?;
+    }
+    if !c && !packed {
+        return Err(Error::new(input.ident.span(), "LLVM struct types need a fixed layout, add `#[repr(C)]`"));
+    }
+    Ok(Layout { packed })
+}
+
+fn llvm_name(input: &DeriveInput) -> Result<LitStr, Error> {
+    let mut name = LitStr::new(&input.ident.to_string(), input.ident.span());
+    for attribute in input.attrs.iter().filter(|it| it.path().is_ident("llvm")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = meta.value()?.parse()?;
+                return Ok(());
+            }
+            Err(meta.error("unknown `llvm` attribute, expected `name = \"...\"`"))
+        })?;
+    }
+    Ok(name)
+}
+
+fn fields(input: &DeriveInput) -> Result<&Fields, Error> {
+    // Lifetimes don't change the layout, type and const parameters would.
+    if let Some(param) = input.generics.params.iter().find(|it| !matches!(it, GenericParam::Lifetime(_))) {
+        return Err(Error::new(param.span(), "structs with type or const parameters can't be mapped to a single LLVM struct type"));
+    }
+    match &input.data {
+        Data::Struct(data) => Ok(&data.fields),
+        _ => Err(Error::new(input.ident.span(), "only structs can be mapped to LLVM struct types"))
+    }
+}
+
+fn derive_llvm_type(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = fields(input)?;
+    let layout = layout(input)?;
+    let name = llvm_name(input)?;
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let types = fields.iter().map(|field| &field.ty);
+    let constructor = if layout.packed { quote!(new_packed) } else { quote!(new) };
+    Ok(quote! {
+        impl #impl_generics #ident #type_generics #where_clause {
+            /// The identified struct type of this struct. Every call returns the same type.
+            pub fn llvm_named_struct() -> ::llvm_ir_generator::types::first_class::aggregate::NamedStructType {
+                static NAMED_STRUCT: ::std::sync::OnceLock<::llvm_ir_generator::types::first_class::aggregate::NamedStructType> = ::std::sync::OnceLock::new();
+                ::llvm_ir_generator::types::rust_type::derived_named_struct(&NAMED_STRUCT, #name, || {
+                    ::llvm_ir_generator::types::first_class::aggregate::StructType::#constructor(vec![
+                        #(<#types as ::llvm_ir_generator::types::rust_type::LlvmType>::llvm_type().into()),*
+                    ])
+                })
+            }
+        }
+
+        impl #impl_generics ::llvm_ir_generator::types::rust_type::LlvmType for #ident #type_generics #where_clause {
+            fn llvm_type() -> ::llvm_ir_generator::types::first_class::Type {
+                Self::llvm_named_struct().into()
+            }
+        }
+    })
+}
+
+fn derive_llvm_constant(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = fields(input)?;
+    let layout = layout(input)?;
+    let ident = &input.ident;
+    let members = fields.members().map(|member| {
+        // Fields of packed structs may be unaligned and can only be read by copy.
+        if layout.packed { quote!(&{ self.#member }) } else { quote!(&self.#member) }
+    });
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::llvm_ir_generator::types::rust_type::LlvmConstant for #ident #type_generics #where_clause {
+            fn llvm_constant(&self) -> ::llvm_ir_generator::constant::Constant<'static> {
+                ::llvm_ir_generator::constant::complex::StructConstant::new_named(Self::llvm_named_struct(), vec![
+                    #(::llvm_ir_generator::types::rust_type::LlvmConstant::llvm_constant(#members)),*
+                ]).expect("field constants have the types of the struct body").into()
+            }
+        }
+    })
+}
+
+#[proc_macro_derive(LlvmType, attributes(llvm))]
+pub fn llvm_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_llvm_type(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[proc_macro_derive(LlvmConstant, attributes(llvm))]
+pub fn llvm_constant(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_llvm_constant(&input).unwrap_or_else(Error::into_compile_error).into()
+}
@@ -0,0 +1,8 @@
+//! Shapes of structs the derive macros accept and reject.
+
+#[test]
+fn derive() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/pass/*.rs");
+    cases.compile_fail("tests/ui/fail/*.rs");
+}
@@ -0,0 +1,10 @@
+use llvm_ir_generator::types::rust_type::LlvmType;
+
+#[derive(LlvmType)]
+#[repr(C)]
+#[llvm(title = "point")]
+struct Point {
+    x: i32
+}
+
+fn main() {}
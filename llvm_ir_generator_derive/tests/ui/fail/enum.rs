@@ -0,0 +1,10 @@
+use llvm_ir_generator::types::rust_type::LlvmType;
+
+#[derive(LlvmType)]
+#[repr(C)]
+enum Kind {
+    A,
+    B
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use llvm_ir_generator::types::rust_type::LlvmType;
+
+#[derive(LlvmType)]
+#[repr(C)]
+struct Wrapper<'a, T> {
+    value: &'a T
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use llvm_ir_generator::types::rust_type::LlvmType;
+
+#[derive(LlvmType)]
+struct Point {
+    x: i32
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use llvm_ir_generator::types::rust_type::LlvmType;
+
+#[derive(LlvmType)]
+#[repr(C, align(8))]
+struct Aligned {
+    x: i32
+}
+
+#[derive(LlvmType)]
+#[repr(packed(2))]
+struct Packed {
+    x: i32
+}
+
+#[derive(LlvmType)]
+#[repr(transparent)]
+struct Transparent {
+    x: i32
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use llvm_ir_generator::types::rust_type::LlvmType;
+
+#[derive(LlvmType)]
+#[repr(C)]
+struct Slice<'a, 'b: 'a> where 'b: 'a {
+    data: &'a u8,
+    rest: Option<&'b Slice<'b, 'b>>,
+    len: usize
+}
+
+fn main() {
+    assert_eq!(Slice::llvm_type().to_string(), "%Slice");
+    assert_eq!(Slice::llvm_named_struct().body().unwrap().to_string(), format!("{{ i8*, %Slice*, i{} }}", usize::BITS));
+}
@@ -0,0 +1,40 @@
+use llvm_ir_generator::types::rust_type::LlvmType;
+
+#[derive(LlvmType)]
+#[repr(C)]
+struct Node {
+    next: *const Node,
+    value: i32
+}
+
+#[derive(LlvmType)]
+#[repr(C)]
+struct Parent {
+    first_child: *mut Child
+}
+
+#[derive(LlvmType)]
+#[repr(C)]
+struct Child {
+    parent: *mut Parent,
+    next_sibling: Option<&'static Child>
+}
+
+fn main() {
+    // Every thread sees the bodies set, whichever thread declares them.
+    let threads: Vec<_> = (0..8).map(|i| std::thread::spawn(move || {
+        let (node, parent, child) = if i % 2 == 0 {
+            (Node::llvm_named_struct(), Parent::llvm_named_struct(), Child::llvm_named_struct())
+        } else {
+            let child = Child::llvm_named_struct();
+            (Node::llvm_named_struct(), Parent::llvm_named_struct(), child)
+        };
+        assert_eq!(node.body().unwrap().to_string(), "{ %Node*, i32 }");
+        assert_eq!(parent.body().unwrap().to_string(), "{ %Child* }");
+        assert_eq!(child.body().unwrap().to_string(), "{ %Parent*, %Child* }");
+    })).collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(Node::llvm_named_struct(), Node::llvm_named_struct());
+}
@@ -0,0 +1,25 @@
+use llvm_ir_generator::types::rust_type::{LlvmType, LlvmConstant};
+
+#[derive(LlvmType, LlvmConstant)]
+#[repr(C)]
+struct Point {
+    x: i32,
+    y: f64,
+    tags: [u8; 2]
+}
+
+#[derive(LlvmType, LlvmConstant)]
+#[repr(C, packed)]
+#[llvm(name = "struct.header")]
+struct Header {
+    kind: u8,
+    size: u32
+}
+
+fn main() {
+    let point = Point::llvm_named_struct();
+    assert_eq!(point.body().unwrap().to_string(), "{ i32, double, [ 2 x i8 ] }");
+    assert_eq!(Point { x: 1, y: 0.5, tags: [2, 3] }.llvm_constant().to_string(), "{ i32 1, double 0.5, [ 2 x i8 ] [ i8 2, i8 3 ] }");
+    assert_eq!(Header::llvm_type().to_string(), "%struct.header");
+    assert_eq!(Header::llvm_named_struct().body().unwrap().to_string(), "<{ i8, i32 }>");
+}
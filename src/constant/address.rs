@@ -0,0 +1,207 @@
+//! Constants that stand for the address of a global variable, function, alias or block.
+//!
+//! Addresses are pointers in the address space of what they point to.
+
+use core::fmt::Display;
+
+use crate::identifier::{GlobalIdentifier, LocalIdentifier};
+use crate::module::{alias::Alias, function::Function, global_variable::GlobalVariable};
+use crate::types::first_class::single_value::{IntegerType, PointerType};
+use crate::types::interner::TypeId;
+use crate::types::ReturnType;
+
+#[derive(Debug, Clone)]
+pub enum Constant<'s> {
+    Global(GlobalAddressConstant<'s>),
+    Block(BlockAddressConstant<'s>),
+    DSOLocalEquivalent(DSOLocalEquivalentConstant<'s>),
+    NoCFI(NoCFIConstant<'s>)
+}
+
+impl<'s> From<GlobalAddressConstant<'s>> for Constant<'s> {
+    fn from(constant: GlobalAddressConstant<'s>) -> Self {
+        Constant::Global(constant)
+    }
+}
+
+impl<'s> From<BlockAddressConstant<'s>> for Constant<'s> {
+    fn from(constant: BlockAddressConstant<'s>) -> Self {
+        Constant::Block(constant)
+    }
+}
+
+impl<'s> From<DSOLocalEquivalentConstant<'s>> for Constant<'s> {
+    fn from(constant: DSOLocalEquivalentConstant<'s>) -> Self {
+        Constant::DSOLocalEquivalent(constant)
+    }
+}
+
+impl<'s> From<NoCFIConstant<'s>> for Constant<'s> {
+    fn from(constant: NoCFIConstant<'s>) -> Self {
+        Constant::NoCFI(constant)
+    }
+}
+
+impl<'s> Display for Constant<'s> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (match self {
+            Self::Global(it) => it as &dyn Display,
+            Self::Block(it) => it as &dyn Display,
+            Self::DSOLocalEquivalent(it) => it as &dyn Display,
+            Self::NoCFI(it) => it as &dyn Display
+        }).fmt(f)
+    }
+}
+
+impl<'s> ReturnType for Constant<'s> {
+    fn return_type(&self) -> TypeId {
+        (match self {
+            Self::Global(it) => it as &dyn ReturnType,
+            Self::Block(it) => it as &dyn ReturnType,
+            Self::DSOLocalEquivalent(it) => it as &dyn ReturnType,
+            Self::NoCFI(it) => it as &dyn ReturnType
+        }).return_type()
+    }
+}
+
+/// A block that `blockaddress` can't take the address of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockAddressError {
+    /// The entry block of `function`, which can't be jumped to.
+    EntryBlock { function: String },
+    /// An unnamed block that isn't part of `function` yet, so that it has no number.
+    UnnumberedBlock { function: String }
+}
+
+impl Display for BlockAddressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EntryBlock { function } => f.write_fmt(format_args!("`blockaddress` of the entry block of `{}`", function)),
+            Self::UnnumberedBlock { function } => f.write_fmt(format_args!("`blockaddress` of an unnamed block that isn't part of `{}` yet", function))
+        }
+    }
+}
+
+impl std::error::Error for BlockAddressError {}
+
+fn pointer_to(value_type: TypeId, address_space: usize) -> TypeId {
+    TypeId::new(PointerType::new_with_address_space(value_type.get().clone(), address_space))
+}
+
+/// `@name`, the address of a global variable, function or alias.
+#[derive(Debug, Clone)]
+pub struct GlobalAddressConstant<'s> {
+    identifier: GlobalIdentifier<'s>,
+    _type: TypeId
+}
+
+impl<'s> GlobalAddressConstant<'s> {
+    /// The address of a global that isn't at hand, e.g. one of another module.
+    pub fn new(identifier: GlobalIdentifier<'s>, _type: PointerType) -> Self {
+        GlobalAddressConstant { identifier, _type: TypeId::new(_type) }
+    }
+}
+
+impl<'s> From<&GlobalVariable<'s>> for GlobalAddressConstant<'s> {
+    fn from(global_variable: &GlobalVariable<'s>) -> Self {
+        GlobalAddressConstant { identifier: global_variable.identifier().clone(), _type: pointer_to(global_variable.return_type(), global_variable.address_space()) }
+    }
+}
+
+impl<'s> From<&Function<'s>> for GlobalAddressConstant<'s> {
+    fn from(function: &Function<'s>) -> Self {
+        GlobalAddressConstant { identifier: function.identifier().clone(), _type: function.return_type() }
+    }
+}
+
+impl<'s> From<&Alias<'s>> for GlobalAddressConstant<'s> {
+    fn from(alias: &Alias<'s>) -> Self {
+        GlobalAddressConstant { identifier: alias.identifier().clone(), _type: pointer_to(alias.return_type(), alias.address_space()) }
+    }
+}
+
+impl<'s> Display for GlobalAddressConstant<'s> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.identifier.fmt(f)
+    }
+}
+
+impl<'s> ReturnType for GlobalAddressConstant<'s> {
+    fn return_type(&self) -> TypeId { self._type }
+}
+
+/// `blockaddress(@function, %block)`, the address of a block for `indirectbr`.
+#[derive(Debug, Clone)]
+pub struct BlockAddressConstant<'s> {
+    function: GlobalIdentifier<'s>,
+    block: LocalIdentifier<'s>,
+    /// The number of an unnamed block, which is only known inside its function.
+    number: Option<usize>,
+    _type: TypeId
+}
+
+impl<'s> BlockAddressConstant<'s> {
+    /// Named blocks can be added to `function` later, unnamed ones have to be part of it already.
+    pub fn new(function: &Function<'s>, block: LocalIdentifier<'s>) -> Result<Self, BlockAddressError> {
+        let number = function.local_number(&block);
+        if function.is_entry_block(&block) { return Err(BlockAddressError::EntryBlock { function: function.identifier().to_string() }); }
+        if block.is_unnamed() && number.is_none() { return Err(BlockAddressError::UnnumberedBlock { function: function.identifier().to_string() }); }
+        let _type = TypeId::new(PointerType::new_with_address_space(IntegerType(8), function.address_space()));
+        Ok(BlockAddressConstant { function: function.identifier().clone(), block, number, _type })
+    }
+}
+
+impl<'s> Display for BlockAddressConstant<'s> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.number {
+            Some(number) => f.write_fmt(format_args!("blockaddress({}, %{})", self.function, number)),
+            None => f.write_fmt(format_args!("blockaddress({}, {})", self.function, self.block))
+        }
+    }
+}
+
+impl<'s> ReturnType for BlockAddressConstant<'s> {
+    fn return_type(&self) -> TypeId { self._type }
+}
+
+/// `dso_local_equivalent @function`, a stand-in for a function that resolves within the same linkage unit.
+#[derive(Debug, Clone)]
+pub struct DSOLocalEquivalentConstant<'s>(GlobalAddressConstant<'s>);
+
+impl<'s> DSOLocalEquivalentConstant<'s> {
+    pub fn new(function: &Function<'s>) -> Self {
+        DSOLocalEquivalentConstant(function.into())
+    }
+}
+
+impl<'s> Display for DSOLocalEquivalentConstant<'s> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::printer::supports("dso_local_equivalent", Some(12), None);
+        f.write_fmt(format_args!("dso_local_equivalent {}", self.0))
+    }
+}
+
+impl<'s> ReturnType for DSOLocalEquivalentConstant<'s> {
+    fn return_type(&self) -> TypeId { self.0.return_type() }
+}
+
+/// `no_cfi @function`, the address of the function itself rather than its control-flow integrity jump table entry.
+#[derive(Debug, Clone)]
+pub struct NoCFIConstant<'s>(GlobalAddressConstant<'s>);
+
+impl<'s> NoCFIConstant<'s> {
+    pub fn new(function: &Function<'s>) -> Self {
+        NoCFIConstant(function.into())
+    }
+}
+
+impl<'s> Display for NoCFIConstant<'s> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::printer::supports("no_cfi", Some(15), None);
+        f.write_fmt(format_args!("no_cfi {}", self.0))
+    }
+}
+
+impl<'s> ReturnType for NoCFIConstant<'s> {
+    fn return_type(&self) -> TypeId { self.0.return_type() }
+}
@@ -1,15 +1,17 @@
 use core::fmt::Display;
+use std::borrow::Cow;
 
 use crate::types::first_class::Type;
 use crate::types::ReturnType;
+use crate::types::interner::TypeId;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Constant<'s> {
     Struct(StructConstant<'s>),
     Array(ArrayConstant<'s>),
+    ByteString(ByteStringConstant<'s>),
     Vector(VectorConstant<'s>),
-    ZeroInitializion(ZeroInitializionConstant),
-    //MetadataNode(MetadataNodeConstant)
+    ZeroInitializion(ZeroInitializionConstant)
 }
 
 impl<'s> From<StructConstant<'s>> for Constant<'s> {
@@ -24,6 +26,12 @@ impl<'s> From<ArrayConstant<'s>> for Constant<'s> {
     }
 }
 
+impl<'s> From<ByteStringConstant<'s>> for Constant<'s> {
+    fn from(constant: ByteStringConstant<'s>) -> Self {
+        Constant::ByteString(constant)
+    }
+}
+
 impl<'s> From<VectorConstant<'s>> for Constant<'s> {
     fn from(constant: VectorConstant<'s>) -> Self {
         Constant::Vector(constant)
@@ -41,6 +49,7 @@ impl<'s> Display for Constant<'s> {
         (match self {
             Self::Struct(it) => it as &dyn Display,
             Self::Array(it) => it as &dyn Display,
+            Self::ByteString(it) => it as &dyn Display,
             Self::Vector(it) => it as &dyn Display,
             Self::ZeroInitializion(it) => it as &dyn Display
         }).fmt(f)
@@ -48,65 +57,118 @@ impl<'s> Display for Constant<'s> {
 }
 
 impl<'s> ReturnType for Constant<'s> {
-    fn return_type(&self) -> Type {
+    fn return_type(&self) -> TypeId {
         (match self {
             Constant::Struct(it) => it as &dyn ReturnType,
             Constant::Array(it) => it as &dyn ReturnType,
+            Constant::ByteString(it) => it as &dyn ReturnType,
             Constant::Vector(it) => it as &dyn ReturnType,
-            Constant::ZeroInitializion(it) => it as &dyn ReturnType,
-            //Constant::MetadataNode(it) => it as &dyn ReturnType
+            Constant::ZeroInitializion(it) => it as &dyn ReturnType
         }).return_type()
     }
 }
 
+/// Constants that don't fit the aggregate type they are combined into.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ComplexConstantCreationError {
-    IllegalReturnType
+    /// The constant at `index` doesn't have the type of that element or field.
+    ElementType { index: usize, expected: Type, found: Type },
+    ElementCount { expected: usize, found: usize },
+    /// A named struct whose body hasn't been set.
+    OpaqueStruct(String),
+    /// Scalable vectors have no constants other than `zeroinitializer`.
+    ScalableVector,
+    InvalidType(crate::types::first_class::TypeCreationError)
 }
 
-#[derive(Debug)]
-pub struct StructConstant<'s>(Vec<(Type, crate::constant::Constant<'s>)>, bool);
+impl Display for ComplexConstantCreationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ElementType { index, expected, found } => f.write_fmt(format_args!("element {} has type `{}`, expected `{}`", index, found, expected)),
+            Self::ElementCount { expected, found } => f.write_fmt(format_args!("{} elements given, expected {}", found, expected)),
+            Self::OpaqueStruct(name) => f.write_fmt(format_args!("%{} has no body", crate::identifier::Name(name.as_bytes()))),
+            Self::ScalableVector => f.write_str("scalable vectors can't be written as a list of elements"),
+            Self::InvalidType(error) => Display::fmt(error, f)
+        }
+    }
+}
 
-impl<'s> StructConstant<'s> {
-    fn new(fields: Vec<(Type, crate::constant::Constant<'s>)>, packed: bool) -> Result<Self, ComplexConstantCreationError> {
-        for (_type, constant) in &fields {
-            if _type != &constant.return_type() { return Err(ComplexConstantCreationError::IllegalReturnType); }
+impl std::error::Error for ComplexConstantCreationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidType(error) => Some(error),
+            _ => None
         }
-        Ok(StructConstant(fields, packed))
+    }
+}
+
+impl From<crate::types::first_class::TypeCreationError> for ComplexConstantCreationError {
+    fn from(error: crate::types::first_class::TypeCreationError) -> Self {
+        ComplexConstantCreationError::InvalidType(error)
+    }
+}
+
+/// Checks that `constants` have the types of the elements or fields in `expected`, in order.
+fn check_elements<'a, 's: 'a>(expected: impl Iterator<Item = TypeId>, constants: impl Iterator<Item = &'a crate::constant::Constant<'s>>) -> Result<(), ComplexConstantCreationError> {
+    for (index, (expected, constant)) in expected.zip(constants).enumerate() {
+        let found = constant.return_type();
+        if found != expected { return Err(ComplexConstantCreationError::ElementType { index, expected: expected.get().clone(), found: found.get().clone() }); }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct StructConstant<'s>(pub(crate) Vec<crate::constant::Constant<'s>>, pub(crate) bool, pub(crate) TypeId);
+
+impl<'s> StructConstant<'s> {
+    pub fn new(fields: Vec<(Type, crate::constant::Constant<'s>)>, packed: bool) -> Result<Self, ComplexConstantCreationError> {
+        let (elements, constants): (Vec<TypeId>, Vec<_>) = fields.into_iter().map(|(_type, constant)| (TypeId::new(_type), constant)).unzip();
+        check_elements(elements.iter().cloned(), constants.iter())?;
+        let _type = TypeId::new(if packed {
+            crate::types::first_class::aggregate::StructType::new_packed(elements)
+        } else {
+            crate::types::first_class::aggregate::StructType::new(elements)
+        }?);
+        Ok(StructConstant(constants, packed, _type))
+    }
+    /// A constant of an identified struct, whose body has to match the types of `constants`.
+    pub fn new_named(_type: crate::types::first_class::aggregate::NamedStructType, constants: Vec<crate::constant::Constant<'s>>) -> Result<Self, ComplexConstantCreationError> {
+        let Some(body) = _type.body() else { return Err(ComplexConstantCreationError::OpaqueStruct(_type.name().to_string())); };
+        if body.types.len() != constants.len() { return Err(ComplexConstantCreationError::ElementCount { expected: body.types.len(), found: constants.len() }); }
+        check_elements(body.types.iter().cloned(), constants.iter())?;
+        let packed = body.packed;
+        Ok(StructConstant(constants, packed, TypeId::new(_type)))
     }
 }
 
 impl<'s> Display for StructConstant<'s> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.1 { f.write_str("<")?; }
         f.write_str("{ ")?;
-        for (i, (_type, constant)) in self.0.iter().enumerate() {
-            f.write_fmt(format_args!("{} {}", _type, constant))?;
-            if i < (self.0.len()) { f.write_str(", ")?; }
+        for (i, constant) in self.0.iter().enumerate() {
+            f.write_fmt(format_args!("{} {}", constant.return_type(), constant))?;
+            if i < (self.0.len()-1) { f.write_str(", ")?; }
         }
         f.write_str(" }")?;
+        if self.1 { f.write_str(">")?; }
         Ok(())
     }
 }
 
 impl<'s> ReturnType for StructConstant<'s> {
-    fn return_type(&self) -> Type {
-        let elements = self.0.iter().map(|it| it.0.clone()).collect();
-        Type::from(if self.1 {
-            crate::types::first_class::aggregate::StructType::new_packed(elements)
-        } else {
-            crate::types::first_class::aggregate::StructType::new(elements)
-        })
-    }
+    fn return_type(&self) -> TypeId { self.2 }
 }
 
-#[derive(Debug)]
-pub struct ArrayConstant<'s>(Type, Vec<crate::constant::Constant<'s>>);
+#[derive(Debug, Clone)]
+pub struct ArrayConstant<'s>(pub(crate) TypeId, pub(crate) Vec<crate::constant::Constant<'s>>, pub(crate) TypeId);
 
 impl<'s> ArrayConstant<'s> {
-    fn new(_type: Type, constants: Vec<crate::constant::Constant<'s>>) -> Result<Self, ComplexConstantCreationError> {
-        for constant in &constants {
-            if &_type != &constant.return_type() { return Err(ComplexConstantCreationError::IllegalReturnType); }
-        }
-        Ok(ArrayConstant(_type, constants))
+    pub fn new(_type: Type, constants: Vec<crate::constant::Constant<'s>>) -> Result<Self, ComplexConstantCreationError> {
+        let element_type = TypeId::new(_type.clone());
+        check_elements(std::iter::repeat(element_type), constants.iter())?;
+        let array_type = crate::types::first_class::aggregate::ArrayType::new(constants.len(), _type)?;
+        let array_type = TypeId::new(array_type);
+        Ok(ArrayConstant(element_type, constants, array_type))
     }
 }
 
@@ -123,20 +185,56 @@ impl<'s> Display for ArrayConstant<'s> {
 }
 
 impl<'s> ReturnType for ArrayConstant<'s> {
-    fn return_type(&self) -> Type {
-        crate::types::first_class::aggregate::ArrayType::new(self.1.len(), Box::new(self.0.clone())).into()
+    fn return_type(&self) -> TypeId { self.2 }
+}
+
+/// An array of `i8`, written as a string: `c"Hello\0A\00"`.
+#[derive(Debug, Clone)]
+pub struct ByteStringConstant<'s>(Cow<'s, [u8]>, TypeId);
+
+impl<'s> ByteStringConstant<'s> {
+    /// The bytes, followed by a NUL byte if `nul_terminated`.
+    pub fn new(bytes: impl Into<Cow<'s, [u8]>>, nul_terminated: bool) -> Self {
+        let mut bytes = bytes.into();
+        if nul_terminated { bytes.to_mut().push(0); }
+        let _type = crate::types::first_class::aggregate::ArrayType::new(bytes.len(), crate::types::first_class::single_value::IntegerType(8))
+            .expect("i8 is a valid element type");
+        ByteStringConstant(bytes, TypeId::new(_type))
+    }
+    /// The UTF-8 bytes of `text`, followed by a NUL byte if `nul_terminated`.
+    pub fn from_text(text: impl Into<Cow<'s, str>>, nul_terminated: bool) -> Self {
+        let bytes = match text.into() {
+            Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+            Cow::Owned(text) => Cow::Owned(text.into_bytes())
+        };
+        ByteStringConstant::new(bytes, nul_terminated)
+    }
+    /// The bytes, including the NUL terminator if there is one.
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
     }
 }
 
-#[derive(Debug)]
-pub struct VectorConstant<'s>(crate::types::first_class::single_value::VectorType, Vec<crate::constant::Constant<'s>>);
+impl<'s> Display for ByteStringConstant<'s> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("c{}", crate::identifier::QuotedBytes(&self.0)))
+    }
+}
+
+impl<'s> ReturnType for ByteStringConstant<'s> {
+    fn return_type(&self) -> TypeId { self.1 }
+}
+
+#[derive(Debug, Clone)]
+pub struct VectorConstant<'s>(pub(crate) TypeId, pub(crate) Vec<crate::constant::Constant<'s>>, pub(crate) TypeId);
 
 impl<'s> VectorConstant<'s> {
-    fn new(_type: crate::types::first_class::single_value::VectorType, constants: Vec<crate::constant::Constant<'s>>) -> Result<Self, ComplexConstantCreationError> {
-        for constant in &constants {
-            if _type._type.as_ref() != &constant.return_type() { return Err(ComplexConstantCreationError::IllegalReturnType); }
-        }
-        Ok(VectorConstant(_type, constants))
+    pub fn new(_type: crate::types::first_class::single_value::VectorType, constants: Vec<crate::constant::Constant<'s>>) -> Result<Self, ComplexConstantCreationError> {
+        if _type.is_scalable() { return Err(ComplexConstantCreationError::ScalableVector); }
+        if _type.element_count() != constants.len() { return Err(ComplexConstantCreationError::ElementCount { expected: _type.element_count(), found: constants.len() }); }
+        let element_type = _type._type;
+        check_elements(std::iter::repeat(element_type), constants.iter())?;
+        Ok(VectorConstant(element_type, constants, TypeId::new(_type)))
     }
 }
 
@@ -144,7 +242,7 @@ impl<'s> Display for VectorConstant<'s> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("< ")?;
         for (i, constant) in self.1.iter().enumerate() {
-            f.write_fmt(format_args!("{} {}", self.0._type, constant))?;
+            f.write_fmt(format_args!("{} {}", self.0, constant))?;
             if i < (self.1.len()-1) { f.write_str(", ")?; }
         }
         f.write_str(" >")?;
@@ -153,15 +251,15 @@ impl<'s> Display for VectorConstant<'s> {
 }
 
 impl<'s> ReturnType for VectorConstant<'s> {
-    fn return_type(&self) -> Type { self.0.clone().into() }
+    fn return_type(&self) -> TypeId { self.2 }
 }
 
-#[derive(Debug)]
-pub struct ZeroInitializionConstant(Type);
+#[derive(Debug, Clone)]
+pub struct ZeroInitializionConstant(TypeId);
 
 impl ZeroInitializionConstant {
-    fn new(_type: Type) -> Self {
-        ZeroInitializionConstant(_type)
+    pub fn new(_type: impl Into<TypeId>) -> Self {
+        ZeroInitializionConstant(_type.into())
     }
 }
 
@@ -172,8 +270,21 @@ impl Display for ZeroInitializionConstant {
 }
 
 impl ReturnType for ZeroInitializionConstant {
-    fn return_type(&self) -> Type { self.0.clone() }
+    fn return_type(&self) -> TypeId { self.0 }
 }
 
-//pub struct MetadataNodeConstant<'s>()
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    #[test]
+    fn byte_strings_escape_what_isnt_printable() {
+        let string = ByteStringConstant::from_text("say \"hi\"\n", true);
+        assert_eq!(string.to_string(), "c\"say \\22hi\\22\\0A\\00\"");
+        assert_eq!(string.return_type().to_string(), "[ 10 x i8 ]");
+        assert_eq!(ByteStringConstant::from_text("a\\b", false).to_string(), "c\"a\\5Cb\"");
+        assert_eq!(ByteStringConstant::new(&[0xFF, b'~'][..], false).to_string(), "c\"\\FF~\"");
+        let empty = ByteStringConstant::new(Vec::new(), false);
+        assert_eq!((empty.to_string(), empty.return_type().to_string()), ("c\"\"".to_string(), "[ 0 x i8 ]".to_string()));
+    }
+}
@@ -0,0 +1,1015 @@
+//! Constant expressions, operations on constants that are evaluated when the module is compiled.
+//!
+//! Every expression checks its operands on creation and knows its result type. Some operations
+//! have been removed from constant expressions in later LLVM versions; a `ModulePrinter`
+//! targeting such a version reports them as unsupported.
+
+use core::fmt::Display;
+
+use crate::types::first_class::{Type, single_value::{IntegerType, PointerType, VectorType}};
+use crate::types::interner::TypeId;
+use crate::types::ReturnType;
+
+#[derive(Debug, Clone)]
+pub enum Constant<'s> {
+    Cast(CastConstant<'s>),
+    GetElementPointer(GetElementPointerConstant<'s>),
+    ExtractElement(ExtractElementConstant<'s>),
+    InsertElement(InsertElementConstant<'s>),
+    ShuffleVector(ShuffleVectorConstant<'s>),
+    ExtractValue(ExtractValueConstant<'s>),
+    InsertValue(InsertValueConstant<'s>),
+    Binary(BinaryConstant<'s>),
+    Compare(CompareConstant<'s>),
+    Select(SelectConstant<'s>)
+}
+
+impl<'s> From<CastConstant<'s>> for Constant<'s> {
+    fn from(constant: CastConstant<'s>) -> Self {
+        Constant::Cast(constant)
+    }
+}
+
+impl<'s> From<GetElementPointerConstant<'s>> for Constant<'s> {
+    fn from(constant: GetElementPointerConstant<'s>) -> Self {
+        Constant::GetElementPointer(constant)
+    }
+}
+
+impl<'s> From<ExtractElementConstant<'s>> for Constant<'s> {
+    fn from(constant: ExtractElementConstant<'s>) -> Self {
+        Constant::ExtractElement(constant)
+    }
+}
+
+impl<'s> From<InsertElementConstant<'s>> for Constant<'s> {
+    fn from(constant: InsertElementConstant<'s>) -> Self {
+        Constant::InsertElement(constant)
+    }
+}
+
+impl<'s> From<ShuffleVectorConstant<'s>> for Constant<'s> {
+    fn from(constant: ShuffleVectorConstant<'s>) -> Self {
+        Constant::ShuffleVector(constant)
+    }
+}
+
+impl<'s> From<ExtractValueConstant<'s>> for Constant<'s> {
+    fn from(constant: ExtractValueConstant<'s>) -> Self {
+        Constant::ExtractValue(constant)
+    }
+}
+
+impl<'s> From<InsertValueConstant<'s>> for Constant<'s> {
+    fn from(constant: InsertValueConstant<'s>) -> Self {
+        Constant::InsertValue(constant)
+    }
+}
+
+impl<'s> From<BinaryConstant<'s>> for Constant<'s> {
+    fn from(constant: BinaryConstant<'s>) -> Self {
+        Constant::Binary(constant)
+    }
+}
+
+impl<'s> From<CompareConstant<'s>> for Constant<'s> {
+    fn from(constant: CompareConstant<'s>) -> Self {
+        Constant::Compare(constant)
+    }
+}
+
+impl<'s> From<SelectConstant<'s>> for Constant<'s> {
+    fn from(constant: SelectConstant<'s>) -> Self {
+        Constant::Select(constant)
+    }
+}
+
+impl<'s> Display for Constant<'s> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (match self {
+            Self::Cast(it) => it as &dyn Display,
+            Self::GetElementPointer(it) => it as &dyn Display,
+            Self::ExtractElement(it) => it as &dyn Display,
+            Self::InsertElement(it) => it as &dyn Display,
+            Self::ShuffleVector(it) => it as &dyn Display,
+            Self::ExtractValue(it) => it as &dyn Display,
+            Self::InsertValue(it) => it as &dyn Display,
+            Self::Binary(it) => it as &dyn Display,
+            Self::Compare(it) => it as &dyn Display,
+            Self::Select(it) => it as &dyn Display
+        }).fmt(f)
+    }
+}
+
+impl<'s> ReturnType for Constant<'s> {
+    fn return_type(&self) -> TypeId {
+        (match self {
+            Self::Cast(it) => it as &dyn ReturnType,
+            Self::GetElementPointer(it) => it as &dyn ReturnType,
+            Self::ExtractElement(it) => it as &dyn ReturnType,
+            Self::InsertElement(it) => it as &dyn ReturnType,
+            Self::ShuffleVector(it) => it as &dyn ReturnType,
+            Self::ExtractValue(it) => it as &dyn ReturnType,
+            Self::InsertValue(it) => it as &dyn ReturnType,
+            Self::Binary(it) => it as &dyn ReturnType,
+            Self::Compare(it) => it as &dyn ReturnType,
+            Self::Select(it) => it as &dyn ReturnType
+        }).return_type()
+    }
+}
+
+/// Operands that don't fit the operation of a constant expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstantExpressionError {
+    /// `operator` doesn't accept an operand of type `operand`.
+    InvalidOperand { operator: &'static str, operand: Type },
+    InvalidCast { operator: &'static str, from: Type, to: Type },
+    /// An operand of `operator` whose type differs from the one implied by the other operands.
+    TypeMismatch { operator: &'static str, expected: Type, found: Type },
+    /// An index, as written, that selects nothing in `aggregate`.
+    InvalidIndex { aggregate: Type, index: String },
+    MissingIndex
+}
+
+impl Display for ConstantExpressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidOperand { operator, operand } => f.write_fmt(format_args!("`{}` can't take an operand of type `{}`", operator, operand)),
+            Self::InvalidCast { operator, from, to } => f.write_fmt(format_args!("`{}` can't convert `{}` to `{}`", operator, from, to)),
+            Self::TypeMismatch { operator, expected, found } => f.write_fmt(format_args!("operand of `{}` has type `{}`, expected `{}`", operator, found, expected)),
+            Self::InvalidIndex { aggregate, index } => f.write_fmt(format_args!("index {} selects nothing in `{}`", index, aggregate)),
+            Self::MissingIndex => f.write_str("`extractvalue` and `insertvalue` need at least one index")
+        }
+    }
+}
+
+impl std::error::Error for ConstantExpressionError {}
+
+/// A constant preceded by its type, as operands of constant expressions are written.
+struct Typed<'a, 's>(&'a super::Constant<'s>);
+
+impl Display for Typed<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} {}", self.0.return_type(), self.0))
+    }
+}
+
+fn operand_type(constant: &super::Constant<'_>) -> TypeId {
+    constant.return_type()
+}
+
+/// `scalar`, or a vector of `scalar` with as many elements as `shape` if that is a vector.
+fn with_shape(shape: &Type, scalar: Type) -> Type {
+    let Some(count) = shape.element_count().filter(|_| shape.is_vector()) else { return scalar; };
+    let vector = if shape.is_scalable() { VectorType::new_scalable(count, scalar) } else { VectorType::new(count, scalar) };
+    return vector.expect("the shape comes from a valid vector type").into();
+}
+
+fn same_shape(a: &Type, b: &Type) -> bool {
+    a.is_vector() == b.is_vector() && a.is_scalable() == b.is_scalable() && (!a.is_vector() || a.element_count() == b.element_count())
+}
+
+/// The value of an integer constant used as an index, if it is a plain number.
+fn index_value(constant: &super::Constant<'_>) -> Option<usize> {
+    match constant {
+        super::Constant::Simple(super::simple::Constant::Integer(it)) => it.as_index(),
+        _ => None
+    }
+}
+
+/// The type of the element or field `index` of `aggregate`. Vector elements can only be selected by `getelementptr`.
+fn indexed_type(aggregate: &Type, index: usize, vectors: bool) -> Option<&Type> {
+    if let Some(fields) = aggregate.struct_fields() { return fields.get(index).map(TypeId::get); }
+    if aggregate.is_array() || (vectors && aggregate.is_vector()) { return aggregate.element_type(); }
+    return None;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastOperator {
+    Truncate,
+    ZeroExtend,
+    SignExtend,
+    FloatTruncate,
+    FloatExtend,
+    FloatToUnsigned,
+    FloatToSigned,
+    UnsignedToFloat,
+    SignedToFloat,
+    PointerToInteger,
+    IntegerToPointer,
+    BitCast,
+    AddressSpaceCast
+}
+
+impl CastOperator {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Truncate => "trunc",
+            Self::ZeroExtend => "zext",
+            Self::SignExtend => "sext",
+            Self::FloatTruncate => "fptrunc",
+            Self::FloatExtend => "fpext",
+            Self::FloatToUnsigned => "fptoui",
+            Self::FloatToSigned => "fptosi",
+            Self::UnsignedToFloat => "uitofp",
+            Self::SignedToFloat => "sitofp",
+            Self::PointerToInteger => "ptrtoint",
+            Self::IntegerToPointer => "inttoptr",
+            Self::BitCast => "bitcast",
+            Self::AddressSpaceCast => "addrspacecast"
+        }
+    }
+    /// The construct and version for casts that LLVM removed from constant expressions.
+    fn removal(&self) -> Option<(&'static str, u32)> {
+        match self {
+            Self::ZeroExtend => Some(("zext constant expression", 18)),
+            Self::SignExtend => Some(("sext constant expression", 18)),
+            Self::FloatTruncate => Some(("fptrunc constant expression", 18)),
+            Self::FloatExtend => Some(("fpext constant expression", 18)),
+            Self::FloatToUnsigned => Some(("fptoui constant expression", 18)),
+            Self::FloatToSigned => Some(("fptosi constant expression", 18)),
+            Self::UnsignedToFloat => Some(("uitofp constant expression", 18)),
+            Self::SignedToFloat => Some(("sitofp constant expression", 18)),
+            _ => None
+        }
+    }
+}
+
+impl Display for CastOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The size in bits of a type that `bitcast` can reinterpret, i.e. any non-pointer single value type.
+fn bit_size(_type: &Type) -> Option<usize> {
+    let scalar = _type.scalar_type();
+    let bits = scalar.integer_width().or_else(|| scalar.float_type().map(|it| it.bit_width()))?;
+    return Some(bits * if _type.is_vector() { _type.element_count()? } else { 1 });
+}
+
+pub(crate) fn is_valid_cast(operator: CastOperator, from: &Type, to: &Type) -> bool {
+    if operator == CastOperator::BitCast {
+        if from.is_pointer_or_pointer_vector() || to.is_pointer_or_pointer_vector() {
+            return from.is_pointer_or_pointer_vector() && to.is_pointer_or_pointer_vector()
+                && same_shape(from, to) && from.address_space() == to.address_space();
+        }
+        return from.is_scalable() == to.is_scalable() && bit_size(from).is_some() && bit_size(from) == bit_size(to);
+    }
+    if !same_shape(from, to) { return false; }
+    let (from, to) = (from.scalar_type(), to.scalar_type());
+    let float_width = |it: &Type| it.float_type().map(|it| it.bit_width());
+    return match operator {
+        CastOperator::Truncate => from.integer_width().zip(to.integer_width()).is_some_and(|(from, to)| from > to),
+        CastOperator::ZeroExtend | CastOperator::SignExtend => from.integer_width().zip(to.integer_width()).is_some_and(|(from, to)| from < to),
+        CastOperator::FloatTruncate => float_width(from).zip(float_width(to)).is_some_and(|(from, to)| from > to),
+        CastOperator::FloatExtend => float_width(from).zip(float_width(to)).is_some_and(|(from, to)| from < to),
+        CastOperator::FloatToUnsigned | CastOperator::FloatToSigned => from.is_float() && to.is_integer(),
+        CastOperator::UnsignedToFloat | CastOperator::SignedToFloat => from.is_integer() && to.is_float(),
+        CastOperator::PointerToInteger => from.is_pointer() && to.is_integer(),
+        CastOperator::IntegerToPointer => from.is_integer() && to.is_pointer(),
+        CastOperator::AddressSpaceCast => from.is_pointer() && to.is_pointer() && from.address_space() != to.address_space(),
+        CastOperator::BitCast => unreachable!("bitcasts are checked above")
+    };
+}
+
+/// `trunc (i32 257 to i8)`, `ptrtoint (i8* null to i64)`, ...
+#[derive(Debug, Clone)]
+pub struct CastConstant<'s> {
+    pub(crate) operator: CastOperator,
+    pub(crate) value: Box<super::Constant<'s>>,
+    pub(crate) _type: TypeId
+}
+
+impl<'s> CastConstant<'s> {
+    pub fn new(operator: CastOperator, value: super::Constant<'s>, _type: impl Into<TypeId>) -> Result<Self, ConstantExpressionError> {
+        let _type = _type.into();
+        let from = &*operand_type(&value);
+        if !is_valid_cast(operator, from, _type.get()) {
+            return Err(ConstantExpressionError::InvalidCast { operator: operator.name(), from: from.clone(), to: _type.get().clone() });
+        }
+        Ok(CastConstant { operator, value: Box::new(value), _type })
+    }
+}
+
+impl Display for CastConstant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((construct, removed)) = self.operator.removal() { crate::printer::supports(construct, None, Some(removed)); }
+        f.write_fmt(format_args!("{} ({} to {})", self.operator, Typed(&self.value), self._type))
+    }
+}
+
+impl ReturnType for CastConstant<'_> {
+    fn return_type(&self) -> TypeId { self._type }
+}
+
+/// `getelementptr inbounds ([6 x i8], [6 x i8]* @str, i64 0, i64 0)`
+#[derive(Debug, Clone)]
+pub struct GetElementPointerConstant<'s> {
+    pub(crate) inbounds: bool,
+    pub(crate) source_type: TypeId,
+    pub(crate) pointer: Box<super::Constant<'s>>,
+    pub(crate) indices: Vec<super::Constant<'s>>,
+    pub(crate) _type: TypeId
+}
+
+impl<'s> GetElementPointerConstant<'s> {
+    /// Indexes `pointer`, which points to `source_type`. Struct fields have to be selected by `i32` constants.
+    /// The pointer and the other indices can be vectors of the same length, giving a vector of pointers.
+    pub fn new(inbounds: bool, source_type: impl Into<TypeId>, pointer: super::Constant<'s>, indices: Vec<super::Constant<'s>>) -> Result<Self, ConstantExpressionError> {
+        const OPERATOR: &str = "getelementptr";
+        let source_type = source_type.into();
+        let source = source_type.get();
+        if !source.is_sized() { return Err(ConstantExpressionError::InvalidOperand { operator: OPERATOR, operand: source.clone() }); }
+        let pointer_type = &*operand_type(&pointer);
+        if !pointer_type.is_pointer_or_pointer_vector() { return Err(ConstantExpressionError::InvalidOperand { operator: OPERATOR, operand: pointer_type.clone() }); }
+        let address_space = pointer_type.address_space().unwrap_or(0);
+        let pointee = pointer_type.scalar_type().pointee();
+        if pointee.is_some_and(|it| it.first_class() != Some(source)) {
+            let expected = PointerType::new_with_address_space(source.clone(), address_space);
+            return Err(ConstantExpressionError::TypeMismatch { operator: OPERATOR, expected: with_shape(pointer_type, expected.into()), found: pointer_type.clone() });
+        }
+        // The result is a vector if any operand is, and all vector operands have the same length.
+        let mut shape = pointer_type.clone();
+        let mut current = source;
+        for (i, index) in indices.iter().enumerate() {
+            let index_type = &*operand_type(index);
+            if !index_type.is_integer_or_integer_vector() { return Err(ConstantExpressionError::InvalidOperand { operator: OPERATOR, operand: index_type.clone() }); }
+            if index_type.is_vector() {
+                if !shape.is_vector() {
+                    shape = index_type.clone();
+                } else if !same_shape(&shape, index_type) {
+                    return Err(ConstantExpressionError::TypeMismatch { operator: OPERATOR, expected: with_shape(&shape, index_type.scalar_type().clone()), found: index_type.clone() });
+                }
+            }
+            // The first index steps over whole objects of the source type.
+            if i == 0 { continue; }
+            let invalid = || ConstantExpressionError::InvalidIndex { aggregate: current.clone(), index: Typed(index).to_string() };
+            let value = if current.is_struct() {
+                if index_type.integer_width() != Some(32) { return Err(invalid()); }
+                index_value(index).ok_or_else(invalid)?
+            } else { 0 };
+            current = indexed_type(current, value, true).ok_or_else(invalid)?;
+        }
+        let scalar = match pointee {
+            Some(_) => PointerType::new_with_address_space(current.clone(), address_space).into(),
+            None => pointer_type.scalar_type().clone()
+        };
+        let _type = TypeId::new(with_shape(&shape, scalar));
+        Ok(GetElementPointerConstant { inbounds, source_type, pointer: Box::new(pointer), indices, _type })
+    }
+}
+
+impl Display for GetElementPointerConstant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("getelementptr ")?;
+        if self.inbounds { f.write_str("inbounds ")?; }
+        f.write_fmt(format_args!("({}, {}", self.source_type, Typed(&self.pointer)))?;
+        for index in &self.indices {
+            f.write_fmt(format_args!(", {}", Typed(index)))?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl ReturnType for GetElementPointerConstant<'_> {
+    fn return_type(&self) -> TypeId { self._type }
+}
+
+fn check_vector(operator: &'static str, vector: &super::Constant<'_>) -> Result<TypeId, ConstantExpressionError> {
+    let _type = operand_type(vector);
+    if !_type.is_vector() { return Err(ConstantExpressionError::InvalidOperand { operator, operand: _type.get().clone() }); }
+    return Ok(_type);
+}
+
+fn check_integer(operator: &'static str, index: &super::Constant<'_>) -> Result<(), ConstantExpressionError> {
+    let _type = &*operand_type(index);
+    if !_type.is_integer() { return Err(ConstantExpressionError::InvalidOperand { operator, operand: _type.clone() }); }
+    return Ok(());
+}
+
+fn check_type(operator: &'static str, expected: &Type, operand: &super::Constant<'_>) -> Result<(), ConstantExpressionError> {
+    let found = &*operand_type(operand);
+    if found != expected { return Err(ConstantExpressionError::TypeMismatch { operator, expected: expected.clone(), found: found.clone() }); }
+    return Ok(());
+}
+
+/// `extractelement (<4 x i32> <...>, i32 1)`
+#[derive(Debug, Clone)]
+pub struct ExtractElementConstant<'s> {
+    pub(crate) vector: Box<super::Constant<'s>>,
+    pub(crate) index: Box<super::Constant<'s>>,
+    pub(crate) _type: TypeId
+}
+
+impl<'s> ExtractElementConstant<'s> {
+    pub fn new(vector: super::Constant<'s>, index: super::Constant<'s>) -> Result<Self, ConstantExpressionError> {
+        let vector_type = check_vector("extractelement", &vector)?;
+        check_integer("extractelement", &index)?;
+        let _type = TypeId::new(vector_type.scalar_type().clone());
+        Ok(ExtractElementConstant { vector: Box::new(vector), index: Box::new(index), _type })
+    }
+}
+
+impl Display for ExtractElementConstant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("extractelement ({}, {})", Typed(&self.vector), Typed(&self.index)))
+    }
+}
+
+impl ReturnType for ExtractElementConstant<'_> {
+    fn return_type(&self) -> TypeId { self._type }
+}
+
+/// `insertelement (<4 x i32> <...>, i32 7, i32 1)`
+#[derive(Debug, Clone)]
+pub struct InsertElementConstant<'s> {
+    pub(crate) vector: Box<super::Constant<'s>>,
+    pub(crate) element: Box<super::Constant<'s>>,
+    pub(crate) index: Box<super::Constant<'s>>
+}
+
+impl<'s> InsertElementConstant<'s> {
+    pub fn new(vector: super::Constant<'s>, element: super::Constant<'s>, index: super::Constant<'s>) -> Result<Self, ConstantExpressionError> {
+        let vector_type = check_vector("insertelement", &vector)?;
+        check_type("insertelement", vector_type.scalar_type(), &element)?;
+        check_integer("insertelement", &index)?;
+        Ok(InsertElementConstant { vector: Box::new(vector), element: Box::new(element), index: Box::new(index) })
+    }
+}
+
+impl Display for InsertElementConstant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("insertelement ({}, {}, {})", Typed(&self.vector), Typed(&self.element), Typed(&self.index)))
+    }
+}
+
+impl ReturnType for InsertElementConstant<'_> {
+    fn return_type(&self) -> TypeId { self.vector.return_type() }
+}
+
+/// `shufflevector (<2 x i32> <...>, <2 x i32> <...>, <4 x i32> <i32 0, i32 2, i32 1, i32 3>)`
+#[derive(Debug, Clone)]
+pub struct ShuffleVectorConstant<'s> {
+    pub(crate) first: Box<super::Constant<'s>>,
+    pub(crate) second: Box<super::Constant<'s>>,
+    pub(crate) mask: Box<super::Constant<'s>>,
+    pub(crate) _type: TypeId
+}
+
+impl<'s> ShuffleVectorConstant<'s> {
+    /// Picks elements of `first` and `second` by `mask`, a vector of `i32` indices into both vectors.
+    pub fn new(first: super::Constant<'s>, second: super::Constant<'s>, mask: super::Constant<'s>) -> Result<Self, ConstantExpressionError> {
+        let vector_type = check_vector("shufflevector", &first)?;
+        check_type("shufflevector", &vector_type, &second)?;
+        let mask_type = check_vector("shufflevector", &mask)?;
+        if mask_type.scalar_type().integer_width() != Some(32) || mask_type.is_scalable() != vector_type.is_scalable() {
+            return Err(ConstantExpressionError::InvalidOperand { operator: "shufflevector", operand: mask_type.get().clone() });
+        }
+        let _type = TypeId::new(with_shape(&mask_type, vector_type.scalar_type().clone()));
+        Ok(ShuffleVectorConstant { first: Box::new(first), second: Box::new(second), mask: Box::new(mask), _type })
+    }
+}
+
+impl Display for ShuffleVectorConstant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("shufflevector ({}, {}, {})", Typed(&self.first), Typed(&self.second), Typed(&self.mask)))
+    }
+}
+
+impl ReturnType for ShuffleVectorConstant<'_> {
+    fn return_type(&self) -> TypeId { self._type }
+}
+
+/// The type `indices` select in `aggregate`, as for `extractvalue` and `insertvalue`.
+fn aggregate_member<'a>(aggregate: &'a Type, indices: &[usize]) -> Result<&'a Type, ConstantExpressionError> {
+    if indices.is_empty() { return Err(ConstantExpressionError::MissingIndex); }
+    let mut current = aggregate;
+    for index in indices {
+        let in_bounds = current.struct_fields().map(<[TypeId]>::len).or(current.element_count()).is_some_and(|it| *index < it);
+        current = indexed_type(current, *index, false).filter(|_| in_bounds)
+            .ok_or_else(|| ConstantExpressionError::InvalidIndex { aggregate: current.clone(), index: Box::new(index).to_string() })?;
+    }
+    return Ok(current);
+}
+
+fn write_indices(f: &mut std::fmt::Formatter<'_>, indices: &[usize]) -> std::fmt::Result {
+    for index in indices {
+        f.write_fmt(format_args!(", {}", index))?;
+    }
+    Ok(())
+}
+
+/// `extractvalue ({ i32, i8 } { i32 1, i8 2 }, 1)`
+#[derive(Debug, Clone)]
+pub struct ExtractValueConstant<'s> {
+    pub(crate) aggregate: Box<super::Constant<'s>>,
+    pub(crate) indices: Vec<usize>,
+    pub(crate) _type: TypeId
+}
+
+impl<'s> ExtractValueConstant<'s> {
+    pub fn new(aggregate: super::Constant<'s>, indices: Vec<usize>) -> Result<Self, ConstantExpressionError> {
+        let _type = TypeId::new(aggregate_member(&operand_type(&aggregate), &indices)?.clone());
+        Ok(ExtractValueConstant { aggregate: Box::new(aggregate), indices, _type })
+    }
+}
+
+impl Display for ExtractValueConstant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::printer::supports("extractvalue constant expression", None, Some(15));
+        f.write_fmt(format_args!("extractvalue ({}", Typed(&self.aggregate)))?;
+        write_indices(f, &self.indices)?;
+        f.write_str(")")
+    }
+}
+
+impl ReturnType for ExtractValueConstant<'_> {
+    fn return_type(&self) -> TypeId { self._type }
+}
+
+/// `insertvalue ({ i32, i8 } zeroinitializer, i8 2, 1)`
+#[derive(Debug, Clone)]
+pub struct InsertValueConstant<'s> {
+    pub(crate) aggregate: Box<super::Constant<'s>>,
+    pub(crate) element: Box<super::Constant<'s>>,
+    pub(crate) indices: Vec<usize>
+}
+
+impl<'s> InsertValueConstant<'s> {
+    pub fn new(aggregate: super::Constant<'s>, element: super::Constant<'s>, indices: Vec<usize>) -> Result<Self, ConstantExpressionError> {
+        check_type("insertvalue", aggregate_member(&operand_type(&aggregate), &indices)?, &element)?;
+        Ok(InsertValueConstant { aggregate: Box::new(aggregate), element: Box::new(element), indices })
+    }
+}
+
+impl Display for InsertValueConstant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::printer::supports("insertvalue constant expression", None, Some(15));
+        f.write_fmt(format_args!("insertvalue ({}, {}", Typed(&self.aggregate), Typed(&self.element)))?;
+        write_indices(f, &self.indices)?;
+        f.write_str(")")
+    }
+}
+
+impl ReturnType for InsertValueConstant<'_> {
+    fn return_type(&self) -> TypeId { self.aggregate.return_type() }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Add { nuw: bool, nsw: bool },
+    Sub { nuw: bool, nsw: bool },
+    Multiply { nuw: bool, nsw: bool },
+    ShiftLeft { nuw: bool, nsw: bool },
+    LogicalShiftRight { exact: bool },
+    ArithmeticShiftRight { exact: bool },
+    UnsignedDivision { exact: bool },
+    SignedDivision { exact: bool },
+    UnsignedRemainder,
+    SignedRemainder,
+    And,
+    Or,
+    XOr,
+    FloatAdd,
+    FloatSub,
+    FloatMultiply,
+    FloatDivision,
+    FloatRemainder
+}
+
+impl BinaryOperator {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Add { .. } => "add",
+            Self::Sub { .. } => "sub",
+            Self::Multiply { .. } => "mul",
+            Self::ShiftLeft { .. } => "shl",
+            Self::LogicalShiftRight { .. } => "lshr",
+            Self::ArithmeticShiftRight { .. } => "ashr",
+            Self::UnsignedDivision { .. } => "udiv",
+            Self::SignedDivision { .. } => "sdiv",
+            Self::UnsignedRemainder => "urem",
+            Self::SignedRemainder => "srem",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::XOr => "xor",
+            Self::FloatAdd => "fadd",
+            Self::FloatSub => "fsub",
+            Self::FloatMultiply => "fmul",
+            Self::FloatDivision => "fdiv",
+            Self::FloatRemainder => "frem"
+        }
+    }
+    pub fn is_float(&self) -> bool {
+        matches!(self, Self::FloatAdd | Self::FloatSub | Self::FloatMultiply | Self::FloatDivision | Self::FloatRemainder)
+    }
+    /// The construct and version for operators that LLVM removed from constant expressions.
+    fn removal(&self) -> Option<(&'static str, u32)> {
+        match self {
+            Self::UnsignedDivision { .. } => Some(("udiv constant expression", 15)),
+            Self::SignedDivision { .. } => Some(("sdiv constant expression", 15)),
+            Self::UnsignedRemainder => Some(("urem constant expression", 15)),
+            Self::SignedRemainder => Some(("srem constant expression", 15)),
+            Self::FloatAdd => Some(("fadd constant expression", 15)),
+            Self::FloatSub => Some(("fsub constant expression", 15)),
+            Self::FloatMultiply => Some(("fmul constant expression", 15)),
+            Self::FloatDivision => Some(("fdiv constant expression", 15)),
+            Self::FloatRemainder => Some(("frem constant expression", 15)),
+            Self::LogicalShiftRight { .. } => Some(("lshr constant expression", 18)),
+            Self::ArithmeticShiftRight { .. } => Some(("ashr constant expression", 18)),
+            Self::And => Some(("and constant expression", 18)),
+            Self::Or => Some(("or constant expression", 18)),
+            Self::ShiftLeft { .. } => Some(("shl constant expression", 19)),
+            _ => None
+        }
+    }
+}
+
+impl Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())?;
+        match self {
+            Self::Add { nuw, nsw } | Self::Sub { nuw, nsw } | Self::Multiply { nuw, nsw } | Self::ShiftLeft { nuw, nsw } => {
+                if *nuw { f.write_str(" nuw")?; }
+                if *nsw { f.write_str(" nsw")?; }
+            }
+            Self::LogicalShiftRight { exact: true } | Self::ArithmeticShiftRight { exact: true } | Self::UnsignedDivision { exact: true } | Self::SignedDivision { exact: true } => {
+                f.write_str(" exact")?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// `add nsw (i32 1, i32 2)`, `xor (i64 ..., i64 -1)`, ...
+#[derive(Debug, Clone)]
+pub struct BinaryConstant<'s> {
+    pub(crate) operator: BinaryOperator,
+    pub(crate) lhs: Box<super::Constant<'s>>,
+    pub(crate) rhs: Box<super::Constant<'s>>
+}
+
+impl<'s> BinaryConstant<'s> {
+    /// Combines two integers, floats or vectors of them, depending on `operator`, of the same type.
+    pub fn new(operator: BinaryOperator, lhs: super::Constant<'s>, rhs: super::Constant<'s>) -> Result<Self, ConstantExpressionError> {
+        let _type = &*operand_type(&lhs);
+        let valid = if operator.is_float() { _type.is_float_or_float_vector() } else { _type.is_integer_or_integer_vector() };
+        if !valid { return Err(ConstantExpressionError::InvalidOperand { operator: operator.name(), operand: _type.clone() }); }
+        check_type(operator.name(), _type, &rhs)?;
+        Ok(BinaryConstant { operator, lhs: Box::new(lhs), rhs: Box::new(rhs) })
+    }
+}
+
+impl Display for BinaryConstant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((construct, removed)) = self.operator.removal() { crate::printer::supports(construct, None, Some(removed)); }
+        f.write_fmt(format_args!("{} ({}, {})", self.operator, Typed(&self.lhs), Typed(&self.rhs)))
+    }
+}
+
+impl ReturnType for BinaryConstant<'_> {
+    fn return_type(&self) -> TypeId { self.lhs.return_type() }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerPredicate {
+    Equal,
+    NotEqual,
+    UnsignedGreater,
+    UnsignedGreaterOrEqual,
+    UnsignedLess,
+    UnsignedLessOrEqual,
+    SignedGreater,
+    SignedGreaterOrEqual,
+    SignedLess,
+    SignedLessOrEqual
+}
+
+impl Display for IntegerPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Equal => "eq",
+            Self::NotEqual => "ne",
+            Self::UnsignedGreater => "ugt",
+            Self::UnsignedGreaterOrEqual => "uge",
+            Self::UnsignedLess => "ult",
+            Self::UnsignedLessOrEqual => "ule",
+            Self::SignedGreater => "sgt",
+            Self::SignedGreaterOrEqual => "sge",
+            Self::SignedLess => "slt",
+            Self::SignedLessOrEqual => "sle"
+        })
+    }
+}
+
+/// Ordered predicates are false if an operand is NaN, unordered ones are true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatPredicate {
+    False,
+    OrderedEqual,
+    OrderedGreater,
+    OrderedGreaterOrEqual,
+    OrderedLess,
+    OrderedLessOrEqual,
+    OrderedNotEqual,
+    Ordered,
+    UnorderedEqual,
+    UnorderedGreater,
+    UnorderedGreaterOrEqual,
+    UnorderedLess,
+    UnorderedLessOrEqual,
+    UnorderedNotEqual,
+    Unordered,
+    True
+}
+
+impl Display for FloatPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::False => "false",
+            Self::OrderedEqual => "oeq",
+            Self::OrderedGreater => "ogt",
+            Self::OrderedGreaterOrEqual => "oge",
+            Self::OrderedLess => "olt",
+            Self::OrderedLessOrEqual => "ole",
+            Self::OrderedNotEqual => "one",
+            Self::Ordered => "ord",
+            Self::UnorderedEqual => "ueq",
+            Self::UnorderedGreater => "ugt",
+            Self::UnorderedGreaterOrEqual => "uge",
+            Self::UnorderedLess => "ult",
+            Self::UnorderedLessOrEqual => "ule",
+            Self::UnorderedNotEqual => "une",
+            Self::Unordered => "uno",
+            Self::True => "true"
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparePredicate {
+    Integer(IntegerPredicate),
+    Float(FloatPredicate)
+}
+
+impl From<IntegerPredicate> for ComparePredicate {
+    fn from(predicate: IntegerPredicate) -> Self {
+        ComparePredicate::Integer(predicate)
+    }
+}
+
+impl From<FloatPredicate> for ComparePredicate {
+    fn from(predicate: FloatPredicate) -> Self {
+        ComparePredicate::Float(predicate)
+    }
+}
+
+impl Display for ComparePredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer(it) => f.write_fmt(format_args!("icmp {}", it)),
+            Self::Float(it) => f.write_fmt(format_args!("fcmp {}", it))
+        }
+    }
+}
+
+/// `icmp eq (i8* null, i8* null)`, `fcmp olt (double 1.0, double 2.0)`
+#[derive(Debug, Clone)]
+pub struct CompareConstant<'s> {
+    pub(crate) predicate: ComparePredicate,
+    pub(crate) lhs: Box<super::Constant<'s>>,
+    pub(crate) rhs: Box<super::Constant<'s>>,
+    pub(crate) _type: TypeId
+}
+
+impl<'s> CompareConstant<'s> {
+    /// Compares integers or pointers with an integer predicate and floats with a float predicate,
+    /// element-wise for vectors. The result is an `i1` or a vector of them.
+    pub fn new(predicate: impl Into<ComparePredicate>, lhs: super::Constant<'s>, rhs: super::Constant<'s>) -> Result<Self, ConstantExpressionError> {
+        let predicate = predicate.into();
+        let (operator, valid) = match predicate {
+            ComparePredicate::Integer(_) => {
+                let _type = &*operand_type(&lhs);
+                ("icmp", _type.is_integer_or_integer_vector() || _type.is_pointer_or_pointer_vector())
+            }
+            ComparePredicate::Float(_) => ("fcmp", operand_type(&lhs).is_float_or_float_vector())
+        };
+        let _type = &*operand_type(&lhs);
+        if !valid { return Err(ConstantExpressionError::InvalidOperand { operator, operand: _type.clone() }); }
+        check_type(operator, _type, &rhs)?;
+        let result = TypeId::new(with_shape(_type, IntegerType(1).into()));
+        Ok(CompareConstant { predicate, lhs: Box::new(lhs), rhs: Box::new(rhs), _type: result })
+    }
+}
+
+impl Display for CompareConstant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::printer::supports("icmp and fcmp constant expressions", None, Some(19));
+        f.write_fmt(format_args!("{} ({}, {})", self.predicate, Typed(&self.lhs), Typed(&self.rhs)))
+    }
+}
+
+impl ReturnType for CompareConstant<'_> {
+    fn return_type(&self) -> TypeId { self._type }
+}
+
+/// `select (i1 true, i32 1, i32 2)`
+#[derive(Debug, Clone)]
+pub struct SelectConstant<'s> {
+    pub(crate) condition: Box<super::Constant<'s>>,
+    pub(crate) true_value: Box<super::Constant<'s>>,
+    pub(crate) false_value: Box<super::Constant<'s>>
+}
+
+impl<'s> SelectConstant<'s> {
+    /// Picks `true_value` or `false_value` by an `i1` condition, or element-wise by a vector of `i1`.
+    pub fn new(condition: super::Constant<'s>, true_value: super::Constant<'s>, false_value: super::Constant<'s>) -> Result<Self, ConstantExpressionError> {
+        let condition_type = &*operand_type(&condition);
+        let value_type = &*operand_type(&true_value);
+        let valid = condition_type.scalar_type().integer_width() == Some(1) && (!condition_type.is_vector() || same_shape(condition_type, value_type));
+        if !valid { return Err(ConstantExpressionError::InvalidOperand { operator: "select", operand: condition_type.clone() }); }
+        check_type("select", value_type, &false_value)?;
+        Ok(SelectConstant { condition: Box::new(condition), true_value: Box::new(true_value), false_value: Box::new(false_value) })
+    }
+}
+
+impl Display for SelectConstant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::printer::supports("select constant expression", None, Some(17));
+        f.write_fmt(format_args!("select ({}, {}, {})", Typed(&self.condition), Typed(&self.true_value), Typed(&self.false_value)))
+    }
+}
+
+impl ReturnType for SelectConstant<'_> {
+    fn return_type(&self) -> TypeId { self.true_value.return_type() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::simple::{IntegerConstant, NullPointerConstant, PoisonConstant};
+    use crate::printer::{LLVMVersion, PrintError, UnsupportedConstructError};
+    use crate::types::first_class::aggregate::StructType;
+
+    fn poison(_type: impl Into<Type>) -> super::super::Constant<'static> {
+        PoisonConstant::new(_type.into()).unwrap().into()
+    }
+
+    fn int(value: i64) -> super::super::Constant<'static> {
+        IntegerConstant::from_i64(IntegerType(32), value).unwrap().into()
+    }
+
+    /// The type written as `s`.
+    fn t(s: &str) -> Type {
+        s.parse().unwrap()
+    }
+
+    fn typed(constant: &impl ReturnType, printed: &impl Display) -> String {
+        format!("{} {}", constant.return_type(), printed)
+    }
+
+    /// Prints `constant` as the initializer of a global, for `version`.
+    fn printed_for(version: u32, constant: super::super::Constant<'static>) -> Result<String, PrintError> {
+        use crate::module::{Module, global_variable::{GlobalVariable, GlobalVariableType}};
+        let mut module = Module::new();
+        let global = GlobalVariable::new(crate::identifier::GlobalIdentifier::new("g").unwrap(), GlobalVariableType::Global, constant.return_type(), Some(constant));
+        module.add_global_variable(global).unwrap();
+        module.printer().with_llvm_version(LLVMVersion(version)).print()
+    }
+
+    fn vector(factor: usize, _type: impl Into<TypeId>) -> Type {
+        VectorType::new(factor, _type).unwrap().into()
+    }
+
+    fn gep(source_type: impl Into<TypeId>, pointer: super::super::Constant<'static>, indices: Vec<super::super::Constant<'static>>) -> Result<String, ConstantExpressionError> {
+        let gep = GetElementPointerConstant::new(false, source_type, pointer, indices)?;
+        Ok(format!("{} {}", gep.return_type(), gep))
+    }
+
+    #[test]
+    fn getelementptr_takes_vectors() {
+        let i32_pointer = PointerType::new(IntegerType(32));
+        let index = || IntegerConstant::from_i64(IntegerType(64), 1).unwrap().into();
+        assert_eq!(gep(IntegerType(32), poison(vector(2, i32_pointer.clone())), vec![index()]).unwrap(), "<2 x i32*> getelementptr (i32, <2 x i32*> poison, i64 1)");
+        assert_eq!(gep(IntegerType(32), NullPointerConstant::new(i32_pointer.clone()).into(), vec![poison(vector(4, IntegerType(64)))]).unwrap(), "<4 x i32*> getelementptr (i32, i32* null, <4 x i64> poison)");
+        let pair = StructType::new(vec![IntegerType(32).into(), IntegerType(64).into()]).unwrap();
+        let field = IntegerConstant::from_i64(IntegerType(32), 1).unwrap().into();
+        let opaque = NullPointerConstant::new(PointerType::new_opaque()).into();
+        assert_eq!(gep(pair, opaque, vec![poison(vector(2, IntegerType(64))), field]).unwrap(), "<2 x ptr> getelementptr ({ i32, i64 }, ptr null, <2 x i64> poison, i32 1)");
+        let mismatch = gep(IntegerType(32), poison(vector(2, i32_pointer.clone())), vec![poison(vector(4, IntegerType(64)))]);
+        assert_eq!(mismatch.err(), Some(ConstantExpressionError::TypeMismatch { operator: "getelementptr", expected: vector(2, IntegerType(64)), found: vector(4, IntegerType(64)) }));
+        let wrong_pointee = gep(IntegerType(8), poison(vector(2, i32_pointer)), vec![index()]);
+        assert_eq!(wrong_pointee.err(), Some(ConstantExpressionError::TypeMismatch { operator: "getelementptr", expected: vector(2, PointerType::new(IntegerType(8))), found: vector(2, PointerType::new(IntegerType(32))) }));
+    }
+
+    #[test]
+    fn casts_check_their_types() {
+        use CastOperator::*;
+        for (operator, from, to) in [
+            (Truncate, "i32", "i8"), (ZeroExtend, "<2 x i8>", "<2 x i32>"), (SignExtend, "i1", "i64"), (FloatTruncate, "double", "half"),
+            (FloatExtend, "float", "fp128"), (FloatToUnsigned, "<vscale x 2 x float>", "<vscale x 2 x i32>"), (SignedToFloat, "i64", "double"),
+            (PointerToInteger, "ptr", "i64"), (IntegerToPointer, "<2 x i64>", "<2 x ptr addrspace(1)>"), (AddressSpaceCast, "i8*", "i8 addrspace(1)*"),
+            (BitCast, "i32", "float"), (BitCast, "<2 x i32>", "i64"), (BitCast, "<4 x i16>", "<2 x float>"), (BitCast, "i32*", "i8*"),
+            (BitCast, "<2 x ptr>", "<2 x i64*>"), (BitCast, "<vscale x 4 x i32>", "<vscale x 2 x i64>")
+        ] {
+            assert!(is_valid_cast(operator, &t(from), &t(to)), "{} {} to {}", operator, from, to);
+        }
+        for (operator, from, to) in [
+            (Truncate, "i8", "i32"), (ZeroExtend, "i32", "i32"), (ZeroExtend, "<2 x i8>", "<4 x i32>"), (SignExtend, "<2 x i8>", "i32"),
+            (FloatExtend, "double", "float"), (FloatToSigned, "i32", "float"), (UnsignedToFloat, "<2 x i32>", "<vscale x 2 x float>"),
+            (PointerToInteger, "i64", "i64"), (IntegerToPointer, "ptr", "ptr"), (AddressSpaceCast, "ptr", "ptr"), (AddressSpaceCast, "i64", "ptr"),
+            (BitCast, "i32", "i64"), (BitCast, "ptr", "i64"), (BitCast, "i64", "ptr"), (BitCast, "i32*", "i8 addrspace(1)*"), (BitCast, "<2 x ptr>", "ptr"),
+            (BitCast, "<vscale x 2 x i32>", "i64"), (BitCast, "{ i32 }", "i32"), (BitCast, "[ 2 x i32 ]", "i64")
+        ] {
+            assert!(!is_valid_cast(operator, &t(from), &t(to)), "{} {} to {}", operator, from, to);
+        }
+        let cast = CastConstant::new(Truncate, int(257), IntegerType(8)).unwrap();
+        assert_eq!(typed(&cast, &cast), "i8 trunc (i32 257 to i8)");
+        assert_eq!(CastConstant::new(ZeroExtend, int(1), IntegerType(8)).err(), Some(ConstantExpressionError::InvalidCast { operator: "zext", from: t("i32"), to: t("i8") }));
+    }
+
+    #[test]
+    fn aggregate_indices_are_checked() {
+        let aggregate = || poison(t("{ i32, [ 2 x i8 ] }"));
+        let extract = ExtractValueConstant::new(aggregate(), vec![1, 1]).unwrap();
+        assert_eq!(typed(&extract, &extract), "i8 extractvalue ({ i32, [ 2 x i8 ] } poison, 1, 1)");
+        assert_eq!(ExtractValueConstant::new(aggregate(), vec![1]).unwrap().return_type(), t("[ 2 x i8 ]"));
+        assert_eq!(ExtractValueConstant::new(aggregate(), vec![]).err(), Some(ConstantExpressionError::MissingIndex));
+        assert_eq!(ExtractValueConstant::new(aggregate(), vec![2]).err(), Some(ConstantExpressionError::InvalidIndex { aggregate: t("{ i32, [ 2 x i8 ] }"), index: "2".to_string() }));
+        assert_eq!(ExtractValueConstant::new(aggregate(), vec![1, 2]).err(), Some(ConstantExpressionError::InvalidIndex { aggregate: t("[ 2 x i8 ]"), index: "2".to_string() }));
+        assert_eq!(ExtractValueConstant::new(aggregate(), vec![0, 0]).err(), Some(ConstantExpressionError::InvalidIndex { aggregate: t("i32"), index: "0".to_string() }));
+        // Vectors are indexed with `extractelement` instead.
+        assert!(matches!(ExtractValueConstant::new(poison(t("<2 x i32>")), vec![0]), Err(ConstantExpressionError::InvalidIndex { .. })));
+        let byte = IntegerConstant::from_i64(IntegerType(8), 7).unwrap().into();
+        let insert = InsertValueConstant::new(aggregate(), byte, vec![1, 0]).unwrap();
+        assert_eq!(typed(&insert, &insert), "{ i32, [ 2 x i8 ] } insertvalue ({ i32, [ 2 x i8 ] } poison, i8 7, 1, 0)");
+        assert_eq!(InsertValueConstant::new(aggregate(), int(7), vec![1, 0]).err(), Some(ConstantExpressionError::TypeMismatch { operator: "insertvalue", expected: t("i8"), found: t("i32") }));
+        assert_eq!(InsertValueConstant::new(aggregate(), int(7), vec![]).err(), Some(ConstantExpressionError::MissingIndex));
+    }
+
+    #[test]
+    fn shufflevector_masks_are_i32_vectors() {
+        let shuffle = ShuffleVectorConstant::new(poison(t("<2 x i64>")), poison(t("<2 x i64>")), poison(t("<4 x i32>"))).unwrap();
+        assert_eq!(typed(&shuffle, &shuffle), "<4 x i64> shufflevector (<2 x i64> poison, <2 x i64> poison, <4 x i32> poison)");
+        let scalable = ShuffleVectorConstant::new(poison(t("<vscale x 2 x i8>")), poison(t("<vscale x 2 x i8>")), poison(t("<vscale x 4 x i32>"))).unwrap();
+        assert_eq!(scalable.return_type(), t("<vscale x 4 x i8>"));
+        let shuffle = |first: &str, second: &str, mask: &str| ShuffleVectorConstant::new(poison(t(first)), poison(t(second)), poison(t(mask))).err();
+        assert_eq!(shuffle("<2 x i64>", "<2 x i64>", "<4 x i64>"), Some(ConstantExpressionError::InvalidOperand { operator: "shufflevector", operand: t("<4 x i64>") }));
+        assert_eq!(shuffle("<vscale x 2 x i64>", "<vscale x 2 x i64>", "<4 x i32>"), Some(ConstantExpressionError::InvalidOperand { operator: "shufflevector", operand: t("<4 x i32>") }));
+        assert_eq!(shuffle("<2 x i64>", "<4 x i64>", "<4 x i32>"), Some(ConstantExpressionError::TypeMismatch { operator: "shufflevector", expected: t("<2 x i64>"), found: t("<4 x i64>") }));
+        assert_eq!(shuffle("i64", "i64", "<4 x i32>"), Some(ConstantExpressionError::InvalidOperand { operator: "shufflevector", operand: t("i64") }));
+        assert_eq!(shuffle("<2 x i64>", "<2 x i64>", "i32"), Some(ConstantExpressionError::InvalidOperand { operator: "shufflevector", operand: t("i32") }));
+    }
+
+    #[test]
+    fn select_conditions_match_the_values() {
+        let select = SelectConstant::new(poison(IntegerType(1)), int(1), int(2)).unwrap();
+        assert_eq!(typed(&select, &select), "i32 select (i1 poison, i32 1, i32 2)");
+        assert!(SelectConstant::new(poison(t("<2 x i1>")), poison(t("<2 x ptr>")), poison(t("<2 x ptr>"))).is_ok());
+        // A scalar condition picks whole vectors.
+        assert!(SelectConstant::new(poison(IntegerType(1)), poison(t("<2 x i32>")), poison(t("<2 x i32>"))).is_ok());
+        let select = |condition: &str, true_value: &str, false_value: &str| SelectConstant::new(poison(t(condition)), poison(t(true_value)), poison(t(false_value))).err();
+        assert_eq!(select("i8", "i32", "i32"), Some(ConstantExpressionError::InvalidOperand { operator: "select", operand: t("i8") }));
+        assert_eq!(select("<2 x i1>", "<4 x i32>", "<4 x i32>"), Some(ConstantExpressionError::InvalidOperand { operator: "select", operand: t("<2 x i1>") }));
+        assert_eq!(select("<2 x i1>", "i32", "i32"), Some(ConstantExpressionError::InvalidOperand { operator: "select", operand: t("<2 x i1>") }));
+        assert_eq!(select("i1", "i32", "i64"), Some(ConstantExpressionError::TypeMismatch { operator: "select", expected: t("i32"), found: t("i64") }));
+    }
+
+    #[test]
+    fn comparisons_give_booleans_of_the_operand_shape() {
+        let icmp = CompareConstant::new(IntegerPredicate::SignedLess, int(1), int(2)).unwrap();
+        assert_eq!(typed(&icmp, &icmp), "i1 icmp slt (i32 1, i32 2)");
+        let pointers = CompareConstant::new(IntegerPredicate::Equal, poison(t("<4 x ptr>")), poison(t("<4 x ptr>"))).unwrap();
+        assert_eq!(typed(&pointers, &pointers), "<4 x i1> icmp eq (<4 x ptr> poison, <4 x ptr> poison)");
+        let fcmp = CompareConstant::new(FloatPredicate::UnorderedNotEqual, poison(t("<vscale x 2 x double>")), poison(t("<vscale x 2 x double>"))).unwrap();
+        assert_eq!(typed(&fcmp, &fcmp), "<vscale x 2 x i1> fcmp une (<vscale x 2 x double> poison, <vscale x 2 x double> poison)");
+        assert_eq!(CompareConstant::new(IntegerPredicate::Equal, poison(t("float")), poison(t("float"))).err(), Some(ConstantExpressionError::InvalidOperand { operator: "icmp", operand: t("float") }));
+        assert_eq!(CompareConstant::new(FloatPredicate::OrderedLess, int(1), int(2)).err(), Some(ConstantExpressionError::InvalidOperand { operator: "fcmp", operand: t("i32") }));
+        assert_eq!(CompareConstant::new(FloatPredicate::True, poison(t("float")), poison(t("double"))).err(), Some(ConstantExpressionError::TypeMismatch { operator: "fcmp", expected: t("float"), found: t("double") }));
+    }
+
+    #[test]
+    fn removed_expressions_are_reported_by_the_printer() {
+        let removed = |construct, version, removed| Err(PrintError::from(UnsupportedConstructError { construct, version: LLVMVersion(version), introduced: None, removed: Some(LLVMVersion(removed)) }));
+        let extract = || ExtractValueConstant::new(poison(t("{ i32 }")), vec![0]).unwrap().into();
+        assert_eq!(printed_for(14, extract()).unwrap(), "@g = global i32 extractvalue ({ i32 } poison, 0)\n");
+        assert_eq!(printed_for(15, extract()), removed("extractvalue constant expression", 15, 15));
+        let udiv = || BinaryConstant::new(BinaryOperator::UnsignedDivision { exact: true }, int(4), int(2)).unwrap().into();
+        assert_eq!(printed_for(14, udiv()).unwrap(), "@g = global i32 udiv exact (i32 4, i32 2)\n");
+        assert_eq!(printed_for(16, udiv()), removed("udiv constant expression", 16, 15));
+        let select = || SelectConstant::new(poison(IntegerType(1)), int(1), int(2)).unwrap().into();
+        assert!(printed_for(16, select()).is_ok());
+        assert_eq!(printed_for(17, select()), removed("select constant expression", 17, 17));
+        let zext = || CastConstant::new(CastOperator::ZeroExtend, poison(IntegerType(8)), IntegerType(32)).unwrap().into();
+        assert!(printed_for(17, zext()).is_ok());
+        assert_eq!(printed_for(18, zext()), removed("zext constant expression", 18, 18));
+        let icmp = || CompareConstant::new(IntegerPredicate::Equal, int(1), int(2)).unwrap().into();
+        assert!(printed_for(18, icmp()).is_ok());
+        assert_eq!(printed_for(19, icmp()), removed("icmp and fcmp constant expressions", 19, 19));
+        // Expressions LLVM kept print for any version.
+        let trunc = CastConstant::new(CastOperator::Truncate, int(257), IntegerType(8)).unwrap().into();
+        assert_eq!(printed_for(19, trunc).unwrap(), "@g = global i8 trunc (i32 257 to i8)\n");
+    }
+}
@@ -0,0 +1,475 @@
+//! Folding of constant expressions into the constants they evaluate to.
+//!
+//! Folding follows LLVM's semantics: integer arithmetic wraps, and an operation whose `nuw`,
+//! `nsw` or `exact` flag doesn't hold, or that has a poison operand, evaluates to poison.
+//! Expressions that are undefined behavior, like a division by zero, are kept, as are operations
+//! on integers wider than 128 bits and on floats other than `float` and `double`. Kept
+//! expressions still have their operands folded.
+
+use super::{complex, expression, simple, Constant};
+use super::expression::{BinaryOperator, CastOperator, ComparePredicate, FloatPredicate, IntegerPredicate};
+use crate::types::first_class::{single_value, Type};
+use crate::types::first_class::single_value::{FloatType, IntegerType};
+use crate::types::interner::TypeId;
+use crate::types::ReturnType;
+
+/// Folds `constant` and every constant within it, as far as their values are known.
+pub fn fold(constant: Constant<'_>) -> Constant<'_> {
+    match constant {
+        Constant::Complex(complex::Constant::Struct(mut it)) => { it.0 = fold_all(it.0); it.into() }
+        Constant::Complex(complex::Constant::Array(mut it)) => { it.1 = fold_all(it.1); it.into() }
+        Constant::Complex(complex::Constant::Vector(mut it)) => { it.1 = fold_all(it.1); it.into() }
+        Constant::Expression(it) => fold_expression(it),
+        _ => constant
+    }
+}
+
+fn fold_all(constants: Vec<Constant<'_>>) -> Vec<Constant<'_>> {
+    constants.into_iter().map(fold).collect()
+}
+
+fn fold_expression(expression: expression::Constant<'_>) -> Constant<'_> {
+    match expression {
+        expression::Constant::Cast(it) => fold_cast(it),
+        expression::Constant::GetElementPointer(it) => {
+            let expression::GetElementPointerConstant { inbounds, source_type, pointer, indices, _type } = it;
+            let pointer = fold(*pointer);
+            let indices = fold_all(indices);
+            if is_poison(&pointer) || indices.iter().any(is_poison) { return poison(&_type); }
+            return expression::GetElementPointerConstant { inbounds, source_type, pointer: Box::new(pointer), indices, _type }.into();
+        }
+        expression::Constant::ExtractElement(it) => fold_extract_element(it),
+        expression::Constant::InsertElement(it) => {
+            let expression::InsertElementConstant { vector, element, index } = it;
+            return expression::InsertElementConstant { vector: Box::new(fold(*vector)), element: Box::new(fold(*element)), index: Box::new(fold(*index)) }.into();
+        }
+        expression::Constant::ShuffleVector(it) => {
+            let expression::ShuffleVectorConstant { first, second, mask, _type } = it;
+            return expression::ShuffleVectorConstant { first: Box::new(fold(*first)), second: Box::new(fold(*second)), mask: Box::new(fold(*mask)), _type }.into();
+        }
+        expression::Constant::ExtractValue(it) => {
+            let expression::ExtractValueConstant { aggregate, indices, _type } = it;
+            let aggregate = fold(*aggregate);
+            if let Some(member) = extract(&aggregate, &indices, &_type) { return member; }
+            return expression::ExtractValueConstant { aggregate: Box::new(aggregate), indices, _type }.into();
+        }
+        expression::Constant::InsertValue(it) => {
+            let expression::InsertValueConstant { aggregate, element, indices } = it;
+            return expression::InsertValueConstant { aggregate: Box::new(fold(*aggregate)), element: Box::new(fold(*element)), indices }.into();
+        }
+        expression::Constant::Binary(it) => fold_binary(it),
+        expression::Constant::Compare(it) => {
+            let expression::CompareConstant { predicate, lhs, rhs, _type } = it;
+            let (lhs, rhs) = (fold(*lhs), fold(*rhs));
+            if is_poison(&lhs) || is_poison(&rhs) { return poison(&_type); }
+            if let Some(result) = compare(predicate, &lhs, &rhs) { return simple::BooleanConstant::from(result).into(); }
+            return expression::CompareConstant { predicate, lhs: Box::new(lhs), rhs: Box::new(rhs), _type }.into();
+        }
+        expression::Constant::Select(it) => {
+            let expression::SelectConstant { condition, true_value, false_value } = it;
+            let condition = fold(*condition);
+            let (true_value, false_value) = (fold(*true_value), fold(*false_value));
+            if is_poison(&condition) { return poison(&true_value.return_type()); }
+            match integer(&condition) {
+                Some((1, 1)) => return true_value,
+                Some((_, 1)) => return false_value,
+                _ => {}
+            }
+            return expression::SelectConstant { condition: Box::new(condition), true_value: Box::new(true_value), false_value: Box::new(false_value) }.into();
+        }
+    }
+}
+
+fn is_poison(constant: &Constant<'_>) -> bool {
+    matches!(constant, Constant::Simple(simple::Constant::Poison(_)))
+}
+
+fn poison<'s>(_type: &TypeId) -> Constant<'s> {
+    simple::PoisonConstant::new(_type.get().clone()).expect("folded values have poison values").into()
+}
+
+/// The null value of `_type`, written as a number or `null` where that is possible.
+fn zero<'s>(_type: &TypeId) -> Constant<'s> {
+    match _type.get() {
+        Type::SingleValue(single_value::Type::Integer(it)) => integer_constant(0, it.0),
+        Type::SingleValue(single_value::Type::Float(it)) => simple::FloatConstant::from_bits(it.clone(), 0).expect("zero fits every float type").into(),
+        Type::SingleValue(single_value::Type::Pointer(it)) => simple::NullPointerConstant::new(it.clone()).into(),
+        _ => complex::ZeroInitializionConstant::new(*_type).into()
+    }
+}
+
+fn mask(width: usize) -> u128 {
+    if width >= 128 { u128::MAX } else { (1 << width) - 1 }
+}
+
+/// Reads the low `width` bits of `bits` as two's complement.
+fn signed(bits: u128, width: usize) -> i128 {
+    ((bits << (128 - width)) as i128) >> (128 - width)
+}
+
+fn fits_signed(value: i128, width: usize) -> bool {
+    width >= 128 || (-(1 << (width - 1))..(1 << (width - 1))).contains(&value)
+}
+
+/// The bits and width of an integer constant of at most 128 bits.
+fn integer(constant: &Constant<'_>) -> Option<(u128, usize)> {
+    match constant {
+        Constant::Simple(simple::Constant::Boolean(it)) => Some((it.0 as u128, 1)),
+        Constant::Simple(simple::Constant::Integer(it)) => {
+            // Wider integers are kept unfolded.
+            let width = it.return_type().get().integer_width().filter(|it| *it <= 128)?;
+            // The limbs hold the two's complement, so any value of at most 128 bits reads as unsigned.
+            return Some((it.to_u128()?, width));
+        }
+        _ => None
+    }
+}
+
+fn integer_constant<'s>(bits: u128, width: usize) -> Constant<'s> {
+    simple::IntegerConstant::from_u128(IntegerType(width), bits & mask(width)).expect("masked bits fit the width").into()
+}
+
+/// The value of a `float` or `double` constant, with its type. `float`s widen to `double` exactly.
+fn float(constant: &Constant<'_>) -> Option<(f64, FloatType)> {
+    let Constant::Simple(simple::Constant::Float(it)) = constant else { return None; };
+    match it.return_type().get().float_type()? {
+        FloatType::Float => Some((f32::from_bits(it.bits() as u32) as f64, FloatType::Float)),
+        FloatType::Double => Some((f64::from_bits(it.bits() as u64), FloatType::Double)),
+        _ => None
+    }
+}
+
+/// `_type` if it is `float` or `double`.
+fn float_target(_type: &TypeId) -> Option<FloatType> {
+    _type.get().float_type().filter(|it| matches!(it, FloatType::Float | FloatType::Double)).cloned()
+}
+
+fn float_constant<'s>(value: f64, _type: &FloatType) -> Constant<'s> {
+    if *_type == FloatType::Float { simple::FloatConstant::from_f32(value as f32).into() } else { simple::FloatConstant::from_f64(value).into() }
+}
+
+fn fold_cast(cast: expression::CastConstant<'_>) -> Constant<'_> {
+    let expression::CastConstant { operator, value, _type } = cast;
+    let value = fold(*value);
+    if is_poison(&value) { return poison(&_type); }
+    if let Some(result) = evaluate_cast(operator, &value, &_type) { return result; }
+    return expression::CastConstant { operator, value: Box::new(value), _type }.into();
+}
+
+fn evaluate_cast<'s>(operator: CastOperator, value: &Constant<'s>, _type: &TypeId) -> Option<Constant<'s>> {
+    match operator {
+        CastOperator::Truncate | CastOperator::ZeroExtend | CastOperator::SignExtend => {
+            let (bits, width) = integer(value)?;
+            let target = _type.get().integer_width().filter(|it| *it <= 128)?;
+            let bits = if operator == CastOperator::SignExtend { signed(bits, width) as u128 } else { bits };
+            return Some(integer_constant(bits, target));
+        }
+        // Rounding a `double` to a `float` rounds to nearest, ties to even, as LLVM does.
+        CastOperator::FloatTruncate | CastOperator::FloatExtend => {
+            let (value, _) = float(value)?;
+            return Some(float_constant(value, &float_target(_type)?));
+        }
+        CastOperator::FloatToUnsigned | CastOperator::FloatToSigned => {
+            let (value, _) = float(value)?;
+            let width = _type.get().integer_width().filter(|it| *it <= 128)?;
+            let value = value.trunc();
+            // NaN and values out of the integer's range are poison.
+            let (low, high) = if operator == CastOperator::FloatToSigned {
+                (-(2f64.powi(width as i32 - 1)), 2f64.powi(width as i32 - 1))
+            } else {
+                (0.0, 2f64.powi(width as i32))
+            };
+            if !(low..high).contains(&value) { return Some(poison(_type)); }
+            let bits = if operator == CastOperator::FloatToSigned { value as i128 as u128 } else { value as u128 };
+            return Some(integer_constant(bits, width));
+        }
+        // Converted straight from the integer so that it is rounded only once.
+        CastOperator::UnsignedToFloat | CastOperator::SignedToFloat => {
+            let (bits, width) = integer(value)?;
+            let target = float_target(_type)?;
+            let constant = match (operator == CastOperator::SignedToFloat, target) {
+                (false, FloatType::Float) => simple::FloatConstant::from_f32(bits as f32),
+                (false, _) => simple::FloatConstant::from_f64(bits as f64),
+                (true, FloatType::Float) => simple::FloatConstant::from_f32(signed(bits, width) as f32),
+                (true, _) => simple::FloatConstant::from_f64(signed(bits, width) as f64)
+            };
+            return Some(constant.into());
+        }
+        CastOperator::BitCast => {
+            if value.return_type() == *_type { return Some(value.clone()); }
+            let target = _type.get();
+            if let (Some((bits, _)), Some(float_type)) = (integer(value), target.float_type()) {
+                return simple::FloatConstant::from_bits(float_type.clone(), bits).ok().map(Into::into);
+            }
+            if let (Constant::Simple(simple::Constant::Float(it)), Some(width)) = (value, target.integer_width()) {
+                return Some(integer_constant(it.bits(), width));
+            }
+            return None;
+        }
+        CastOperator::PointerToInteger | CastOperator::IntegerToPointer | CastOperator::AddressSpaceCast => None
+    }
+}
+
+fn fold_binary(binary: expression::BinaryConstant<'_>) -> Constant<'_> {
+    let expression::BinaryConstant { operator, lhs, rhs } = binary;
+    let (lhs, rhs) = (fold(*lhs), fold(*rhs));
+    if is_poison(&lhs) || is_poison(&rhs) { return poison(&lhs.return_type()); }
+    if let (Some((a, width)), Some((b, _))) = (integer(&lhs), integer(&rhs)) {
+        match evaluate_integer(operator, a, b, width) {
+            Some(Some(bits)) => return integer_constant(bits, width),
+            Some(None) => return poison(&lhs.return_type()),
+            None => {}
+        }
+    }
+    if let (Some((a, _type)), Some((b, _))) = (float(&lhs), float(&rhs)) {
+        // Exact in `double` for `float`s, so rounding the result gives the correctly rounded `float`.
+        let value = match operator {
+            BinaryOperator::FloatAdd => Some(a + b),
+            BinaryOperator::FloatSub => Some(a - b),
+            BinaryOperator::FloatMultiply => Some(a * b),
+            BinaryOperator::FloatDivision => Some(a / b),
+            BinaryOperator::FloatRemainder => Some(a % b),
+            _ => None
+        };
+        if let Some(value) = value { return float_constant(value, &_type); }
+    }
+    return expression::BinaryConstant { operator, lhs: Box::new(lhs), rhs: Box::new(rhs) }.into();
+}
+
+/// `Some(None)` for poison, `None` for undefined behavior, which is left to LLVM.
+fn evaluate_integer(operator: BinaryOperator, a: u128, b: u128, width: usize) -> Option<Option<u128>> {
+    let (signed_a, signed_b) = (signed(a, width), signed(b, width));
+    let unsigned_overflow = |it: Option<u128>| it.is_none_or(|it| it > mask(width));
+    let signed_overflow = |it: Option<i128>| it.is_none_or(|it| !fits_signed(it, width));
+    let undefined_division = b == 0 || (matches!(operator, BinaryOperator::SignedDivision { .. } | BinaryOperator::SignedRemainder) && signed_a == signed(1 << (width - 1), width) && signed_b == -1);
+    let (value, poison) = match operator {
+        BinaryOperator::Add { nuw, nsw } => (a.wrapping_add(b), (nuw && unsigned_overflow(a.checked_add(b))) || (nsw && signed_overflow(signed_a.checked_add(signed_b)))),
+        BinaryOperator::Sub { nuw, nsw } => (a.wrapping_sub(b), (nuw && a < b) || (nsw && signed_overflow(signed_a.checked_sub(signed_b)))),
+        BinaryOperator::Multiply { nuw, nsw } => (a.wrapping_mul(b), (nuw && unsigned_overflow(a.checked_mul(b))) || (nsw && signed_overflow(signed_a.checked_mul(signed_b)))),
+        BinaryOperator::ShiftLeft { .. } | BinaryOperator::LogicalShiftRight { .. } | BinaryOperator::ArithmeticShiftRight { .. } if b >= width as u128 => return Some(None),
+        BinaryOperator::ShiftLeft { nuw, nsw } => {
+            let value = (a << b) & mask(width);
+            // Shifting back has to give the operand again, unsigned for `nuw` and signed for `nsw`.
+            (value, (nuw && value >> b != a) || (nsw && signed(value, width) >> b != signed_a))
+        }
+        BinaryOperator::LogicalShiftRight { exact } => (a >> b, exact && (a >> b) << b != a),
+        BinaryOperator::ArithmeticShiftRight { exact } => ((signed_a >> b) as u128, exact && a & mask(b as usize) != 0),
+        BinaryOperator::UnsignedDivision { .. } | BinaryOperator::SignedDivision { .. } | BinaryOperator::UnsignedRemainder | BinaryOperator::SignedRemainder if undefined_division => return None,
+        BinaryOperator::UnsignedDivision { exact } => (a / b, exact && !a.is_multiple_of(b)),
+        BinaryOperator::SignedDivision { exact } => ((signed_a / signed_b) as u128, exact && signed_a % signed_b != 0),
+        BinaryOperator::UnsignedRemainder => (a % b, false),
+        BinaryOperator::SignedRemainder => ((signed_a % signed_b) as u128, false),
+        BinaryOperator::And => (a & b, false),
+        BinaryOperator::Or => (a | b, false),
+        BinaryOperator::XOr => (a ^ b, false),
+        _ => return None
+    };
+    return Some(if poison { None } else { Some(value & mask(width)) });
+}
+
+fn compare(predicate: ComparePredicate, lhs: &Constant<'_>, rhs: &Constant<'_>) -> Option<bool> {
+    match predicate {
+        ComparePredicate::Integer(predicate) => {
+            let ((a, width), (b, _)) = match (lhs, rhs) {
+                (Constant::Simple(simple::Constant::NullPointer(_)), Constant::Simple(simple::Constant::NullPointer(_))) => ((0, 1), (0, 1)),
+                _ => (integer(lhs)?, integer(rhs)?)
+            };
+            let (signed_a, signed_b) = (signed(a, width), signed(b, width));
+            return Some(match predicate {
+                IntegerPredicate::Equal => a == b,
+                IntegerPredicate::NotEqual => a != b,
+                IntegerPredicate::UnsignedGreater => a > b,
+                IntegerPredicate::UnsignedGreaterOrEqual => a >= b,
+                IntegerPredicate::UnsignedLess => a < b,
+                IntegerPredicate::UnsignedLessOrEqual => a <= b,
+                IntegerPredicate::SignedGreater => signed_a > signed_b,
+                IntegerPredicate::SignedGreaterOrEqual => signed_a >= signed_b,
+                IntegerPredicate::SignedLess => signed_a < signed_b,
+                IntegerPredicate::SignedLessOrEqual => signed_a <= signed_b
+            });
+        }
+        ComparePredicate::Float(predicate) => {
+            let ((a, _), (b, _)) = (float(lhs)?, float(rhs)?);
+            let unordered = a.is_nan() || b.is_nan();
+            return Some(match predicate {
+                FloatPredicate::False => false,
+                FloatPredicate::OrderedEqual => a == b,
+                FloatPredicate::OrderedGreater => a > b,
+                FloatPredicate::OrderedGreaterOrEqual => a >= b,
+                FloatPredicate::OrderedLess => a < b,
+                FloatPredicate::OrderedLessOrEqual => a <= b,
+                FloatPredicate::OrderedNotEqual => !unordered && a != b,
+                FloatPredicate::Ordered => !unordered,
+                FloatPredicate::UnorderedEqual => unordered || a == b,
+                FloatPredicate::UnorderedGreater => unordered || a > b,
+                FloatPredicate::UnorderedGreaterOrEqual => unordered || a >= b,
+                FloatPredicate::UnorderedLess => unordered || a < b,
+                FloatPredicate::UnorderedLessOrEqual => unordered || a <= b,
+                FloatPredicate::UnorderedNotEqual => a != b,
+                FloatPredicate::Unordered => unordered,
+                FloatPredicate::True => true
+            });
+        }
+    }
+}
+
+/// The member of a known aggregate at `indices`, which has type `_type`.
+fn extract<'s>(aggregate: &Constant<'s>, indices: &[usize], _type: &TypeId) -> Option<Constant<'s>> {
+    let Some((index, rest)) = indices.split_first() else { return Some(aggregate.clone()); };
+    match aggregate {
+        Constant::Complex(complex::Constant::Struct(it)) => extract(it.0.get(*index)?, rest, _type),
+        Constant::Complex(complex::Constant::Array(it)) => extract(it.1.get(*index)?, rest, _type),
+        Constant::Complex(complex::Constant::ByteString(it)) => Some(integer_constant(*it.bytes().get(*index)? as u128, 8)),
+        Constant::Complex(complex::Constant::ZeroInitializion(_)) => Some(zero(_type)),
+        Constant::Simple(simple::Constant::Undefined(_)) => Some(simple::UndefinedConstant::new(_type.get().clone()).expect("folded values have undef values").into()),
+        Constant::Simple(simple::Constant::Poison(_)) => Some(poison(_type)),
+        _ => None
+    }
+}
+
+fn fold_extract_element(extract_element: expression::ExtractElementConstant<'_>) -> Constant<'_> {
+    let expression::ExtractElementConstant { vector, index, _type } = extract_element;
+    let (vector, index) = (fold(*vector), fold(*index));
+    if is_poison(&vector) || is_poison(&index) { return poison(&_type); }
+    if let Some((index, _)) = integer(&index) {
+        let vector_type = vector.return_type();
+        // An index past the end of a fixed-length vector is poison.
+        if !vector_type.is_scalable() && index >= vector_type.element_count().unwrap_or(0) as u128 { return poison(&_type); }
+        let element = match &vector {
+            Constant::Complex(complex::Constant::Vector(it)) => it.1.get(index as usize).cloned(),
+            Constant::Complex(complex::Constant::ZeroInitializion(_)) => Some(zero(&_type)),
+            Constant::Simple(simple::Constant::Undefined(_)) => Some(simple::UndefinedConstant::new(_type.get().clone()).expect("folded values have undef values").into()),
+            _ => None
+        };
+        if let Some(element) = element { return element; }
+    }
+    return expression::ExtractElementConstant { vector: Box::new(vector), index: Box::new(index), _type }.into();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fold;
+    use crate::constant::Constant;
+    use crate::constant::complex::{ArrayConstant, ByteStringConstant, StructConstant, VectorConstant, ZeroInitializionConstant};
+    use crate::constant::expression::*;
+    use crate::constant::simple::{FloatConstant, IntegerConstant, PoisonConstant};
+    use crate::types::ReturnType;
+    use crate::types::first_class::aggregate::StructType;
+    use crate::types::first_class::single_value::{FloatType, IntegerType, PointerType, VectorType};
+
+    fn int(width: usize, value: i128) -> Constant<'static> {
+        IntegerConstant::from_i128(IntegerType(width), value).unwrap().into()
+    }
+
+    fn double(value: f64) -> Constant<'static> {
+        FloatConstant::from_f64(value).into()
+    }
+
+    fn binary(operator: BinaryOperator, lhs: Constant<'static>, rhs: Constant<'static>) -> Constant<'static> {
+        BinaryConstant::new(operator, lhs, rhs).unwrap().into()
+    }
+
+    fn compare(predicate: impl Into<ComparePredicate>, lhs: Constant<'static>, rhs: Constant<'static>) -> Constant<'static> {
+        CompareConstant::new(predicate, lhs, rhs).unwrap().into()
+    }
+
+    fn cast(operator: CastOperator, value: Constant<'static>, _type: impl Into<crate::types::first_class::Type>) -> Constant<'static> {
+        CastConstant::new(operator, value, _type.into()).unwrap().into()
+    }
+
+    /// The folded constant with its type, as it would be written as an operand.
+    fn folded(constant: Constant<'_>) -> String {
+        let constant = fold(constant);
+        return format!("{} {}", constant.return_type(), constant);
+    }
+
+    const ADD: BinaryOperator = BinaryOperator::Add { nuw: false, nsw: false };
+
+    #[test]
+    fn integer_arithmetic_wraps() {
+        assert_eq!(folded(binary(ADD, int(32, 2), int(32, 3))), "i32 5");
+        assert_eq!(folded(binary(ADD, int(8, 127), int(8, 1))), "i8 -128");
+        assert_eq!(folded(binary(BinaryOperator::Multiply { nuw: false, nsw: false }, int(16, 300), int(16, 300))), "i16 24464");
+        assert_eq!(folded(binary(BinaryOperator::XOr, int(1, 1), int(1, 1))), "i1 false");
+        assert_eq!(folded(binary(BinaryOperator::UnsignedRemainder, int(8, -1), int(8, 10))), "i8 5");
+        assert_eq!(folded(binary(BinaryOperator::SignedDivision { exact: false }, int(8, -7), int(8, 2))), "i8 -3");
+        assert_eq!(folded(binary(BinaryOperator::SignedRemainder, int(8, -7), int(8, 2))), "i8 -1");
+        assert_eq!(folded(binary(BinaryOperator::ArithmeticShiftRight { exact: false }, int(8, -128), int(8, 7))), "i8 -1");
+        assert_eq!(folded(binary(BinaryOperator::Multiply { nuw: false, nsw: false }, binary(ADD, int(32, 2), int(32, 3)), int(32, 4))), "i32 20");
+    }
+
+    #[test]
+    fn broken_flags_are_poison() {
+        assert_eq!(folded(binary(BinaryOperator::Add { nuw: false, nsw: true }, int(8, 127), int(8, 1))), "i8 poison");
+        assert_eq!(folded(binary(BinaryOperator::Add { nuw: true, nsw: false }, int(8, 127), int(8, 1))), "i8 -128");
+        assert_eq!(folded(binary(BinaryOperator::Add { nuw: true, nsw: false }, int(8, -1), int(8, 1))), "i8 poison");
+        assert_eq!(folded(binary(BinaryOperator::Sub { nuw: true, nsw: false }, int(8, 1), int(8, 2))), "i8 poison");
+        assert_eq!(folded(binary(BinaryOperator::Multiply { nuw: false, nsw: true }, int(128, i128::MIN), int(128, -1))), "i128 poison");
+        assert_eq!(folded(binary(BinaryOperator::ShiftLeft { nuw: false, nsw: true }, int(8, 64), int(8, 1))), "i8 poison");
+        assert_eq!(folded(binary(BinaryOperator::ShiftLeft { nuw: false, nsw: true }, int(8, -64), int(8, 1))), "i8 -128");
+        assert_eq!(folded(binary(BinaryOperator::LogicalShiftRight { exact: true }, int(8, 3), int(8, 1))), "i8 poison");
+        assert_eq!(folded(binary(BinaryOperator::ArithmeticShiftRight { exact: true }, int(8, -4), int(8, 2))), "i8 -1");
+        assert_eq!(folded(binary(BinaryOperator::UnsignedDivision { exact: true }, int(8, 7), int(8, 2))), "i8 poison");
+        assert_eq!(folded(binary(BinaryOperator::SignedDivision { exact: true }, int(8, -8), int(8, 2))), "i8 -4");
+    }
+
+    #[test]
+    fn poison_propagates() {
+        assert_eq!(folded(binary(BinaryOperator::ShiftLeft { nuw: false, nsw: false }, int(8, 1), int(8, 8))), "i8 poison");
+        assert_eq!(folded(binary(ADD, PoisonConstant::new(IntegerType(32)).unwrap().into(), int(32, 1))), "i32 poison");
+        assert_eq!(folded(compare(IntegerPredicate::Equal, PoisonConstant::new(IntegerType(8)).unwrap().into(), int(8, 1))), "i1 poison");
+    }
+
+    #[test]
+    fn undefined_behavior_is_kept() {
+        assert_eq!(folded(binary(BinaryOperator::UnsignedDivision { exact: false }, int(8, -1), int(8, 0))), "i8 udiv (i8 -1, i8 0)");
+        assert_eq!(folded(binary(BinaryOperator::SignedDivision { exact: false }, int(8, -128), int(8, -1))), "i8 sdiv (i8 -128, i8 -1)");
+    }
+
+    #[test]
+    fn wide_integers_are_kept() {
+        assert_eq!(folded(binary(ADD, int(256, 1), int(256, 2))), "i256 add (i256 1, i256 2)");
+        assert_eq!(folded(compare(IntegerPredicate::SignedLess, int(256, -1), int(256, 0))), "i1 icmp slt (i256 -1, i256 0)");
+        assert_eq!(folded(cast(CastOperator::Truncate, int(256, 3), IntegerType(8))), "i8 trunc (i256 3 to i8)");
+        assert_eq!(folded(cast(CastOperator::SignExtend, int(8, -1), IntegerType(256))), "i256 sext (i8 -1 to i256)");
+        assert_eq!(folded(cast(CastOperator::SignedToFloat, int(200, -1), FloatType::Double)), "double sitofp (i200 -1 to double)");
+        assert_eq!(folded(binary(ADD, int(256, 1), binary(ADD, int(256, 1), int(256, 1)))), "i256 add (i256 1, i256 add (i256 1, i256 1))");
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(folded(binary(BinaryOperator::FloatAdd, double(0.5), double(0.25))), format!("double {}", FloatConstant::from_f64(0.75)));
+        assert_eq!(folded(binary(BinaryOperator::FloatDivision, FloatConstant::from_f32(1.0).into(), FloatConstant::from_f32(3.0).into())), format!("float {}", FloatConstant::from_f32(1.0 / 3.0)));
+        assert_eq!(folded(compare(FloatPredicate::UnorderedEqual, double(f64::NAN), double(1.0))), "i1 true");
+        assert_eq!(folded(compare(FloatPredicate::OrderedNotEqual, double(f64::NAN), double(1.0))), "i1 false");
+    }
+
+    #[test]
+    fn casts() {
+        assert_eq!(folded(cast(CastOperator::Truncate, int(32, 257), IntegerType(8))), "i8 1");
+        assert_eq!(folded(cast(CastOperator::SignExtend, int(8, -2), IntegerType(128))), "i128 -2");
+        assert_eq!(folded(cast(CastOperator::ZeroExtend, int(8, -2), IntegerType(32))), "i32 254");
+        assert_eq!(folded(cast(CastOperator::FloatToSigned, double(-3.9), IntegerType(8))), "i8 -3");
+        assert_eq!(folded(cast(CastOperator::FloatToUnsigned, double(256.0), IntegerType(8))), "i8 poison");
+        assert_eq!(folded(cast(CastOperator::FloatToSigned, double(f64::NAN), IntegerType(8))), "i8 poison");
+        assert_eq!(folded(cast(CastOperator::UnsignedToFloat, int(8, -3), FloatType::Float)), format!("float {}", FloatConstant::from_f32(253.0)));
+        assert_eq!(folded(cast(CastOperator::FloatTruncate, double(0.1), FloatType::Float)), format!("float {}", FloatConstant::from_f32(0.1)));
+        assert_eq!(folded(cast(CastOperator::BitCast, FloatConstant::from_f32(1.0).into(), IntegerType(32))), "i32 1065353216");
+        assert_eq!(folded(cast(CastOperator::BitCast, int(16, 0x3c00), FloatType::Half)), "half 0xH3C00");
+    }
+
+    #[test]
+    fn aggregates() {
+        let pair: Constant<'_> = StructConstant::new(vec![(IntegerType(32).into(), int(32, 1)), (IntegerType(8).into(), int(8, 2))], false).unwrap().into();
+        assert_eq!(folded(ExtractValueConstant::new(pair, vec![1]).unwrap().into()), "i8 2");
+        assert_eq!(folded(ExtractValueConstant::new(ByteStringConstant::new(&b"hi"[..], true).into(), vec![1]).unwrap().into()), "i8 105");
+        let zero = ZeroInitializionConstant::new(StructType::new(vec![IntegerType(32).into(), PointerType::new(IntegerType(8)).into()]).unwrap());
+        assert_eq!(folded(ExtractValueConstant::new(zero.into(), vec![1]).unwrap().into()), "i8* null");
+        let vector = || -> Constant<'static> { VectorConstant::new(VectorType::new(2, IntegerType(32)).unwrap(), vec![int(32, 7), int(32, 9)]).unwrap().into() };
+        assert_eq!(folded(ExtractElementConstant::new(vector(), int(32, 1)).unwrap().into()), "i32 9");
+        assert_eq!(folded(ExtractElementConstant::new(vector(), int(32, 2)).unwrap().into()), "i32 poison");
+        assert_eq!(folded(SelectConstant::new(compare(IntegerPredicate::Equal, int(8, 1), int(8, 1)), int(32, 10), int(32, 20)).unwrap().into()), "i32 10");
+        let array: Constant<'_> = ArrayConstant::new(IntegerType(32).into(), vec![binary(ADD, int(32, 2), int(32, 3))]).unwrap().into();
+        assert_eq!(folded(array), "[ 1 x i32 ] [ i32 5 ]");
+    }
+}
@@ -1,19 +1,24 @@
 use core::fmt::Display;
 
-use crate::types::first_class::Type;
+use crate::types::interner::TypeId;
 use crate::types::ReturnType;
 
 pub mod simple;
 pub mod complex;
+pub mod expression;
+pub mod address;
+pub mod fold;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Constant<'s> {
-    Simple(simple::Constant<'s>),
+    Simple(simple::Constant),
     Complex(complex::Constant<'s>),
+    Expression(expression::Constant<'s>),
+    Address(address::Constant<'s>),
 }
 
-impl<'s> From<simple::Constant<'s>> for Constant<'s> {
-    fn from(constant: simple::Constant<'s>) -> Self {
+impl<'s> From<simple::Constant> for Constant<'s> {
+    fn from(constant: simple::Constant) -> Self {
         Constant::Simple(constant)
     }
 }
@@ -24,14 +29,14 @@ impl<'s> From<simple::BooleanConstant> for Constant<'s> {
     }
 }
 
-impl<'s> From<simple::IntegerConstant<'s>> for Constant<'s> {
-    fn from(constant: simple::IntegerConstant<'s>) -> Self {
+impl<'s> From<simple::IntegerConstant> for Constant<'s> {
+    fn from(constant: simple::IntegerConstant) -> Self {
         Constant::from(simple::Constant::Integer(constant))
     }
 }
 
-impl<'s> From<simple::FloatConstant<'s>> for Constant<'s> {
-    fn from(constant: simple::FloatConstant<'s>) -> Self {
+impl<'s> From<simple::FloatConstant> for Constant<'s> {
+    fn from(constant: simple::FloatConstant) -> Self {
         Constant::from(simple::Constant::Float(constant))
     }
 }
@@ -48,6 +53,18 @@ impl<'s> From<simple::TokenConstant> for Constant<'s> {
     }
 }
 
+impl<'s> From<simple::UndefinedConstant> for Constant<'s> {
+    fn from(constant: simple::UndefinedConstant) -> Self {
+        Constant::from(simple::Constant::Undefined(constant))
+    }
+}
+
+impl<'s> From<simple::PoisonConstant> for Constant<'s> {
+    fn from(constant: simple::PoisonConstant) -> Self {
+        Constant::from(simple::Constant::Poison(constant))
+    }
+}
+
 impl<'s> From<complex::Constant<'s>> for Constant<'s> {
     fn from(constant: complex::Constant<'s>) -> Self {
         Constant::Complex(constant)
@@ -66,6 +83,12 @@ impl<'s> From<complex::ArrayConstant<'s>> for Constant<'s> {
     }
 }
 
+impl<'s> From<complex::ByteStringConstant<'s>> for Constant<'s> {
+    fn from(constant: complex::ByteStringConstant<'s>) -> Self {
+        Constant::from(complex::Constant::ByteString(constant))
+    }
+}
+
 impl<'s> From<complex::VectorConstant<'s>> for Constant<'s> {
     fn from(constant: complex::VectorConstant<'s>) -> Self {
         Constant::from(complex::Constant::Vector(constant))
@@ -78,20 +101,120 @@ impl<'s> From<complex::ZeroInitializionConstant> for Constant<'s> {
     }
 }
 
+impl<'s> From<expression::Constant<'s>> for Constant<'s> {
+    fn from(constant: expression::Constant<'s>) -> Self {
+        Constant::Expression(constant)
+    }
+}
+
+impl<'s> From<expression::CastConstant<'s>> for Constant<'s> {
+    fn from(constant: expression::CastConstant<'s>) -> Self {
+        Constant::from(expression::Constant::Cast(constant))
+    }
+}
+
+impl<'s> From<expression::GetElementPointerConstant<'s>> for Constant<'s> {
+    fn from(constant: expression::GetElementPointerConstant<'s>) -> Self {
+        Constant::from(expression::Constant::GetElementPointer(constant))
+    }
+}
+
+impl<'s> From<expression::ExtractElementConstant<'s>> for Constant<'s> {
+    fn from(constant: expression::ExtractElementConstant<'s>) -> Self {
+        Constant::from(expression::Constant::ExtractElement(constant))
+    }
+}
+
+impl<'s> From<expression::InsertElementConstant<'s>> for Constant<'s> {
+    fn from(constant: expression::InsertElementConstant<'s>) -> Self {
+        Constant::from(expression::Constant::InsertElement(constant))
+    }
+}
+
+impl<'s> From<expression::ShuffleVectorConstant<'s>> for Constant<'s> {
+    fn from(constant: expression::ShuffleVectorConstant<'s>) -> Self {
+        Constant::from(expression::Constant::ShuffleVector(constant))
+    }
+}
+
+impl<'s> From<expression::ExtractValueConstant<'s>> for Constant<'s> {
+    fn from(constant: expression::ExtractValueConstant<'s>) -> Self {
+        Constant::from(expression::Constant::ExtractValue(constant))
+    }
+}
+
+impl<'s> From<expression::InsertValueConstant<'s>> for Constant<'s> {
+    fn from(constant: expression::InsertValueConstant<'s>) -> Self {
+        Constant::from(expression::Constant::InsertValue(constant))
+    }
+}
+
+impl<'s> From<expression::BinaryConstant<'s>> for Constant<'s> {
+    fn from(constant: expression::BinaryConstant<'s>) -> Self {
+        Constant::from(expression::Constant::Binary(constant))
+    }
+}
+
+impl<'s> From<expression::CompareConstant<'s>> for Constant<'s> {
+    fn from(constant: expression::CompareConstant<'s>) -> Self {
+        Constant::from(expression::Constant::Compare(constant))
+    }
+}
+
+impl<'s> From<expression::SelectConstant<'s>> for Constant<'s> {
+    fn from(constant: expression::SelectConstant<'s>) -> Self {
+        Constant::from(expression::Constant::Select(constant))
+    }
+}
+
+impl<'s> From<address::Constant<'s>> for Constant<'s> {
+    fn from(constant: address::Constant<'s>) -> Self {
+        Constant::Address(constant)
+    }
+}
+
+impl<'s> From<address::GlobalAddressConstant<'s>> for Constant<'s> {
+    fn from(constant: address::GlobalAddressConstant<'s>) -> Self {
+        Constant::from(address::Constant::Global(constant))
+    }
+}
+
+impl<'s> From<address::BlockAddressConstant<'s>> for Constant<'s> {
+    fn from(constant: address::BlockAddressConstant<'s>) -> Self {
+        Constant::from(address::Constant::Block(constant))
+    }
+}
+
+impl<'s> From<address::DSOLocalEquivalentConstant<'s>> for Constant<'s> {
+    fn from(constant: address::DSOLocalEquivalentConstant<'s>) -> Self {
+        Constant::from(address::Constant::DSOLocalEquivalent(constant))
+    }
+}
+
+impl<'s> From<address::NoCFIConstant<'s>> for Constant<'s> {
+    fn from(constant: address::NoCFIConstant<'s>) -> Self {
+        Constant::from(address::Constant::NoCFI(constant))
+    }
+}
+
 impl<'s> Display for Constant<'s> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         (match self {
             Constant::Simple(it) => it as &dyn Display,
-            Constant::Complex(it) => it as &dyn Display
+            Constant::Complex(it) => it as &dyn Display,
+            Constant::Expression(it) => it as &dyn Display,
+            Constant::Address(it) => it as &dyn Display
         }).fmt(f)
     }
 }
 
 impl<'s> ReturnType for Constant<'s> {
-    fn return_type(&self) -> Type {
+    fn return_type(&self) -> TypeId {
         (match self {
             Constant::Simple(it) => it as &dyn ReturnType,
-            Constant::Complex(it) => it as &dyn ReturnType
+            Constant::Complex(it) => it as &dyn ReturnType,
+            Constant::Expression(it) => it as &dyn ReturnType,
+            Constant::Address(it) => it as &dyn ReturnType
         }).return_type()
     }
 }
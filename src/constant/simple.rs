@@ -1,74 +1,93 @@
 use core::fmt::Display;
 
-use crate::types::first_class::Type;
+use crate::types::first_class::single_value::{IntegerType, FloatType};
+use crate::types::interner::TypeId;
 use crate::types::ReturnType;
 
-#[derive(Debug)]
-pub enum Constant<'s> {
+#[derive(Debug, Clone)]
+pub enum Constant {
     Boolean(BooleanConstant),
-    Integer(IntegerConstant<'s>),
-    Float(FloatConstant<'s>),
+    Integer(IntegerConstant),
+    Float(FloatConstant),
     NullPointer(NullPointerConstant),
     Token(TokenConstant),
+    Undefined(UndefinedConstant),
+    Poison(PoisonConstant),
 }
 
-impl<'s> From<BooleanConstant> for Constant<'s> {
+impl From<BooleanConstant> for Constant {
     fn from(constant: BooleanConstant) -> Self {
         Constant::Boolean(constant)
     }
 }
 
-impl<'s> From<IntegerConstant<'s>> for Constant<'s> {
-    fn from(constant: IntegerConstant<'s>) -> Self {
+impl From<IntegerConstant> for Constant {
+    fn from(constant: IntegerConstant) -> Self {
         Constant::Integer(constant)
     }
 }
 
-impl<'s> From<FloatConstant<'s>> for Constant<'s> {
-    fn from(constant: FloatConstant<'s>) -> Self {
+impl From<FloatConstant> for Constant {
+    fn from(constant: FloatConstant) -> Self {
         Constant::Float(constant)
     }
 }
 
-impl<'s> From<NullPointerConstant> for Constant<'s> {
+impl From<NullPointerConstant> for Constant {
     fn from(constant: NullPointerConstant) -> Self {
         Constant::NullPointer(constant)
     }
 }
 
-impl<'s> From<TokenConstant> for Constant<'s> {
+impl From<TokenConstant> for Constant {
     fn from(constant: TokenConstant) -> Self {
         Constant::Token(constant)
     }
 }
 
-impl<'s> Display for Constant<'s> {
+impl From<UndefinedConstant> for Constant {
+    fn from(constant: UndefinedConstant) -> Self {
+        Constant::Undefined(constant)
+    }
+}
+
+impl From<PoisonConstant> for Constant {
+    fn from(constant: PoisonConstant) -> Self {
+        Constant::Poison(constant)
+    }
+}
+
+impl Display for Constant {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         (match self {
             Self::Boolean(it) => it as &dyn Display,
             Self::Integer(it) => it as &dyn Display,
             Self::Float(it) => it as &dyn Display,
             Self::NullPointer(it) => it as &dyn Display,
-            Self::Token(it) => it as &dyn Display
+            Self::Token(it) => it as &dyn Display,
+            Self::Undefined(it) => it as &dyn Display,
+            Self::Poison(it) => it as &dyn Display
         }).fmt(f)
     }
 }
 
-impl<'s> ReturnType for Constant<'s> {
-    fn return_type(&self) -> Type {
+impl ReturnType for Constant {
+    fn return_type(&self) -> TypeId {
         (match self {
             Constant::Boolean(it) => it as &dyn ReturnType,
             Constant::Integer(it) => it as &dyn ReturnType,
             Constant::Float(it) => it as &dyn ReturnType,
             Constant::NullPointer(it) => it as &dyn ReturnType,
-            Constant::Token(it) => it as &dyn ReturnType
+            Constant::Token(it) => it as &dyn ReturnType,
+            Constant::Undefined(it) => it as &dyn ReturnType,
+            Constant::Poison(it) => it as &dyn ReturnType
         }).return_type()
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[repr(transparent)]
-pub struct BooleanConstant(bool);
+pub struct BooleanConstant(pub(crate) bool);
 
 impl From<bool> for BooleanConstant {
     fn from(boolean: bool) -> Self {
@@ -83,55 +102,286 @@ impl Display for BooleanConstant {
 }
 
 impl ReturnType for BooleanConstant {
-    fn return_type(&self) -> Type {
-        crate::types::first_class::single_value::IntegerType(1).into()
+    fn return_type(&self) -> TypeId {
+        TypeId::new(crate::types::first_class::single_value::IntegerType(1))
     }
 }
 
-#[derive(Debug)]
-pub struct IntegerConstant<'s>(&'s str, crate::types::first_class::single_value::IntegerType);
+/// A value that doesn't fit the width of its integer type, neither signed nor unsigned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegerOutOfRangeError {
+    pub value: String,
+    pub _type: IntegerType
+}
 
-impl<'s> IntegerConstant<'s> {
-    fn new(_type: crate::types::first_class::single_value::IntegerType, value: &'s str) -> IntegerConstant<'s> {
-        IntegerConstant(value, _type)
+impl Display for IntegerOutOfRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} doesn't fit in `{}`", self.value, self._type))
     }
 }
 
-impl<'s> Display for IntegerConstant<'s> {
+impl std::error::Error for IntegerOutOfRangeError {}
+
+fn bit(limbs: &[u64], index: usize) -> bool {
+    limbs.get(index / 64).is_some_and(|limb| (limb >> (index % 64)) & 1 == 1)
+}
+
+/// `limbs` as a hexadecimal number, for values too wide to be written in decimal without big integers.
+struct Hexadecimal<'a>(&'a [u64]);
+
+impl Display for Hexadecimal<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.0)
+        let mut limbs = self.0.iter().rev().skip_while(|it| **it == 0);
+        f.write_fmt(format_args!("0x{:X}", limbs.next().unwrap_or(&0)))?;
+        for limb in limbs {
+            f.write_fmt(format_args!("{:016X}", limb))?;
+        }
+        Ok(())
+    }
+}
+
+/// The value of `limbs` in decimal if it fits 128 bits, in hexadecimal otherwise.
+fn describe(limbs: &[u64], negative: bool) -> String {
+    let fill = if negative { u64::MAX } else { 0 };
+    if limbs.iter().skip(2).all(|it| *it == fill) {
+        let low = limbs.first().copied().unwrap_or(fill) as u128;
+        let high = limbs.get(1).copied().unwrap_or(fill) as u128;
+        let value = low | (high << 64);
+        return if negative { (value as i128).to_string() } else { value.to_string() };
+    }
+    if !negative { return Hexadecimal(limbs).to_string(); }
+    // Two's complement negation, with the carry of the added one rippling up.
+    let mut carry = true;
+    let magnitude: Vec<u64> = limbs.iter().map(|limb| {
+        let (sum, overflow) = (!limb).overflowing_add(carry as u64);
+        carry = overflow;
+        sum
+    }).collect();
+    return format!("-{}", Hexadecimal(&magnitude));
+}
+
+/// An integer of any width, stored as the bits of its two's complement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntegerConstant {
+    /// Little-endian, with exactly as many limbs as the width needs and the unused high bits cleared.
+    limbs: Vec<u64>,
+    _type: IntegerType
+}
+
+impl IntegerConstant {
+    pub fn from_u64(_type: IntegerType, value: u64) -> Result<Self, IntegerOutOfRangeError> {
+        IntegerConstant::from_limbs(_type, &[value], false)
+    }
+    pub fn from_i64(_type: IntegerType, value: i64) -> Result<Self, IntegerOutOfRangeError> {
+        IntegerConstant::from_limbs(_type, &[value as u64], true)
+    }
+    pub fn from_u128(_type: IntegerType, value: u128) -> Result<Self, IntegerOutOfRangeError> {
+        IntegerConstant::from_limbs(_type, &[value as u64, (value >> 64) as u64], false)
+    }
+    pub fn from_i128(_type: IntegerType, value: i128) -> Result<Self, IntegerOutOfRangeError> {
+        IntegerConstant::from_limbs(_type, &[value as u64, (value >> 64) as u64], true)
+    }
+    /// Creates an integer from little-endian 64 bit limbs, read as two's complement if `signed`.
+    ///
+    /// Like LLVM, a value fits a width if it can be represented either signed or unsigned:
+    /// `i8` takes everything from -128 to 255.
+    pub fn from_limbs(_type: IntegerType, limbs: &[u64], signed: bool) -> Result<Self, IntegerOutOfRangeError> {
+        let width = _type.0;
+        let negative = signed && limbs.last().is_some_and(|it| (*it as i64) < 0);
+        // Every bit from `start` on has to repeat the sign.
+        let start = if negative { width.wrapping_sub(1) } else { width };
+        if width == 0 || (start..limbs.len() * 64).any(|i| bit(limbs, i) != negative) {
+            return Err(IntegerOutOfRangeError { value: describe(limbs, negative), _type });
+        }
+        let fill = if negative { u64::MAX } else { 0 };
+        let mut bits: Vec<u64> = (0..width.div_ceil(64)).map(|i| limbs.get(i).copied().unwrap_or(fill)).collect();
+        if !width.is_multiple_of(64) {
+            if let Some(last) = bits.last_mut() { *last &= (1 << (width % 64)) - 1; }
+        }
+        return Ok(IntegerConstant { limbs: bits, _type });
+    }
+    /// The bits of the value, see [`IntegerConstant::from_limbs`]. Bits above the width are zero.
+    pub fn limbs(&self) -> &[u64] {
+        &self.limbs
+    }
+    pub fn is_negative(&self) -> bool {
+        bit(&self.limbs, self._type.0 - 1)
+    }
+    /// The value read as unsigned, if it fits in a `u128`.
+    pub fn to_u128(&self) -> Option<u128> {
+        if (128..self._type.0).any(|i| bit(&self.limbs, i)) { return None; }
+        let low = self.limbs.first().copied().unwrap_or(0) as u128;
+        let high = self.limbs.get(1).copied().unwrap_or(0) as u128;
+        return Some(low | (high << 64));
+    }
+    /// The value read as signed, if it fits in an `i128`.
+    pub fn to_i128(&self) -> Option<i128> {
+        let width = self._type.0;
+        let negative = self.is_negative();
+        if (127..width).any(|i| bit(&self.limbs, i) != negative) { return None; }
+        let low = self.limbs.first().copied().unwrap_or(0) as u128;
+        let high = self.limbs.get(1).copied().unwrap_or(0) as u128;
+        let extension = if negative && width < 128 { u128::MAX << width } else { 0 };
+        return Some((low | (high << 64) | extension) as i128);
+    }
+    /// The value, if it is a non-negative number that fits in a `usize`.
+    pub(crate) fn as_index(&self) -> Option<usize> {
+        self.to_i128().and_then(|it| usize::try_from(it).ok())
+    }
+}
+
+/// Written like LLVM does, as a signed decimal number or `true`/`false` for `i1`. Values beyond
+/// 128 bits use LLVM's hexadecimal forms: `u0x` for positive values, and `s0x` with just enough
+/// digits to carry the sign for negative values.
+impl Display for IntegerConstant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self._type.0 == 1 { return f.write_str(if self.limbs[0] == 1 { "true" } else { "false" }); }
+        if let Some(value) = self.to_i128() { return f.write_fmt(format_args!("{}", value)); }
+        if !self.is_negative() { return f.write_fmt(format_args!("u{}", Hexadecimal(&self.limbs))); }
+        // LLVM reads the digits of `s0x` as a number of their own width, so the first digit has to start with the sign bit.
+        let width = self._type.0;
+        let sign_extended = |i: usize| i >= width || bit(&self.limbs, i);
+        let mut digits = 1;
+        while !(digits * 4 - 1..width).all(sign_extended) { digits += 1; }
+        f.write_str("s0x")?;
+        for digit in (0..digits).rev() {
+            let nibble = (0..4).fold(0, |nibble, i| nibble | ((sign_extended(digit * 4 + i) as u8) << i));
+            f.write_fmt(format_args!("{:X}", nibble))?;
+        }
+        Ok(())
+    }
+}
+
+impl ReturnType for IntegerConstant {
+    fn return_type(&self) -> TypeId { TypeId::new(self._type.clone()) }
+}
+
+/// Bits that don't fit a float type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FloatConstantCreationError {
+    /// `bits` has bits set beyond the width of `_type`.
+    TooWide { bits: u128, _type: FloatType },
+    /// A NaN payload that reaches the quiet bit of `_type` or beyond.
+    PayloadTooWide { payload: u128, _type: FloatType }
+}
+
+impl Display for FloatConstantCreationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooWide { bits, _type } => f.write_fmt(format_args!("0x{:X} has more bits than `{}`", bits, _type)),
+            Self::PayloadTooWide { payload, _type } => f.write_fmt(format_args!("NaN payload 0x{:X} doesn't fit in `{}`", payload, _type))
+        }
+    }
+}
+
+impl std::error::Error for FloatConstantCreationError {}
+
+/// The widths of the exponent and the significand. `x86_fp80` stores the integer bit of the
+/// significand explicitly, `ppc_fp128` is a pair of doubles of which the first decides the category.
+fn float_layout(_type: &FloatType) -> (usize, usize) {
+    match _type {
+        FloatType::Half => (5, 10),
+        FloatType::BFloat => (8, 7),
+        FloatType::Float => (8, 23),
+        FloatType::Double | FloatType::PPCFP128 => (11, 52),
+        FloatType::X86FP80 => (15, 64),
+        FloatType::FP128 => (15, 112)
     }
 }
 
-impl<'s> ReturnType for IntegerConstant<'s> {
-    fn return_type(&self) -> Type { self.1.clone().into() }
+/// A decimal as Rust writes it, with the `.` that LLVM needs to read it as a float.
+fn float_decimal(decimal: String) -> String {
+    if decimal.contains('.') { return decimal; }
+    match decimal.find('e') {
+        Some(exponent) => format!("{}.0{}", &decimal[..exponent], &decimal[exponent..]),
+        None => decimal + ".0"
+    }
 }
 
-#[derive(Debug)]
-pub struct FloatConstant<'s>(&'s str, crate::types::first_class::single_value::FloatType);
+/// A floating-point value of any float type, stored as its bits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FloatConstant {
+    /// The bits as LLVM lays them out: `ppc_fp128` has its first double in the low half.
+    bits: u128,
+    _type: FloatType
+}
 
-impl<'s> FloatConstant<'s> {
-    fn new(_type: crate::types::first_class::single_value::FloatType, value: &'s str) -> FloatConstant {
-        FloatConstant(value, _type)
+impl FloatConstant {
+    /// A `float`, NaN payloads included.
+    pub fn from_f32(value: f32) -> Self {
+        FloatConstant { bits: value.to_bits() as u128, _type: FloatType::Float }
+    }
+    /// A `double`, NaN payloads included.
+    pub fn from_f64(value: f64) -> Self {
+        FloatConstant { bits: value.to_bits() as u128, _type: FloatType::Double }
+    }
+    /// Takes `bits` as the value of `_type` in memory, e.g. the 80 bits of an `x86_fp80`.
+    pub fn from_bits(_type: FloatType, bits: u128) -> Result<Self, FloatConstantCreationError> {
+        if _type.bit_width() < 128 && bits >> _type.bit_width() != 0 {
+            return Err(FloatConstantCreationError::TooWide { bits, _type });
+        }
+        return Ok(FloatConstant { bits, _type });
+    }
+    pub fn infinity(_type: FloatType, negative: bool) -> Self {
+        let (exponent, significand) = float_layout(&_type);
+        let mut bits = ((1 << exponent) - 1) << significand;
+        if _type == FloatType::X86FP80 { bits |= 1 << 63; }
+        if negative { bits |= 1 << (exponent + significand); }
+        return FloatConstant { bits, _type };
+    }
+    /// A positive quiet NaN with `payload` in the significand bits below the quiet bit.
+    pub fn nan(_type: FloatType, payload: u128) -> Result<Self, FloatConstantCreationError> {
+        let (_, significand) = float_layout(&_type);
+        let quiet = if _type == FloatType::X86FP80 { 1 << (significand - 2) } else { 1 << (significand - 1) };
+        if payload >= quiet { return Err(FloatConstantCreationError::PayloadTooWide { payload, _type }); }
+        let infinity = FloatConstant::infinity(_type, false);
+        return Ok(FloatConstant { bits: infinity.bits | quiet | payload, _type: infinity._type });
+    }
+    pub fn bits(&self) -> u128 {
+        self.bits
     }
 }
 
-impl<'s> Display for FloatConstant<'s> {
+/// Finite `float`s and `double`s that have a short decimal form are written in decimal, everything
+/// else in LLVM's hexadecimal forms, which always keep the exact bits.
+impl Display for FloatConstant {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.0)
+        match self._type {
+            FloatType::Float => {
+                let value = f32::from_bits(self.bits as u32);
+                let decimal = format!("{:?}", value);
+                // LLVM reads decimals as doubles, so the shortest form of the float only works if the double is the same.
+                if value.is_finite() && decimal.parse::<f64>() == Ok(value as f64) { return f.write_str(&float_decimal(decimal)); }
+                // Hexadecimal `float`s are written as doubles as well. Infinity and NaN are widened bit by bit, so that their payload survives.
+                let bits = self.bits as u64;
+                let double = if value.is_finite() { (value as f64).to_bits() } else { (bits >> 31) << 63 | 0x7FF << 52 | (bits & 0x7F_FFFF) << 29 };
+                f.write_fmt(format_args!("0x{:016X}", double))
+            }
+            FloatType::Double => {
+                let value = f64::from_bits(self.bits as u64);
+                if value.is_finite() { return f.write_str(&float_decimal(format!("{:?}", value))); }
+                f.write_fmt(format_args!("0x{:016X}", self.bits))
+            }
+            FloatType::Half => f.write_fmt(format_args!("0xH{:04X}", self.bits)),
+            FloatType::BFloat => f.write_fmt(format_args!("0xR{:04X}", self.bits)),
+            FloatType::X86FP80 => f.write_fmt(format_args!("0xK{:04X}{:016X}", self.bits >> 64, self.bits as u64)),
+            // LLVM writes the low half of these first.
+            FloatType::FP128 => f.write_fmt(format_args!("0xL{:016X}{:016X}", self.bits as u64, self.bits >> 64)),
+            FloatType::PPCFP128 => f.write_fmt(format_args!("0xM{:016X}{:016X}", self.bits as u64, self.bits >> 64))
+        }
     }
 }
 
-impl<'s> ReturnType for FloatConstant<'s> {
-    fn return_type(&self) -> Type { self.1.clone().into() }
+impl ReturnType for FloatConstant {
+    fn return_type(&self) -> TypeId { TypeId::new(self._type.clone()) }
 }
 
-#[derive(Debug)]
-pub struct NullPointerConstant(crate::types::first_class::single_value::PointerType);
+#[derive(Debug, Clone)]
+pub struct NullPointerConstant(TypeId);
 
 impl NullPointerConstant {
-    fn new(_type: crate::types::first_class::single_value::PointerType) -> NullPointerConstant {
-        NullPointerConstant(_type)
+    pub fn new(_type: crate::types::first_class::single_value::PointerType) -> NullPointerConstant {
+        NullPointerConstant(TypeId::new(_type))
     }
 }
 
@@ -142,10 +392,10 @@ impl Display for NullPointerConstant {
 }
 
 impl ReturnType for NullPointerConstant {
-    fn return_type(&self) -> Type { self.0.clone().into() }
+    fn return_type(&self) -> TypeId { self.0 }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TokenConstant;
 
 impl Display for TokenConstant {
@@ -155,8 +405,128 @@ impl Display for TokenConstant {
 }
 
 impl ReturnType for TokenConstant {
-    fn return_type(&self) -> Type { 
-        crate::types::first_class::TokenType.into()
+    fn return_type(&self) -> TypeId {
+        TypeId::new(crate::types::first_class::TokenType)
+    }
+}
+
+/// A type without `undef` and `poison` values, i.e. `label` or `metadata`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoUndefinedValueError(pub crate::types::first_class::Type);
+
+impl Display for NoUndefinedValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("`{}` has no undef or poison value", self.0))
+    }
+}
+
+impl std::error::Error for NoUndefinedValueError {}
+
+fn check_undefined(_type: crate::types::first_class::Type) -> Result<TypeId, NoUndefinedValueError> {
+    if _type.is_label() || _type.is_metadata() { return Err(NoUndefinedValueError(_type)); }
+    return Ok(TypeId::new(_type));
+}
+
+/// `undef`, an unspecified value of its type that may differ at every use.
+#[derive(Debug, Clone)]
+pub struct UndefinedConstant(TypeId);
+
+impl UndefinedConstant {
+    pub fn new(_type: impl Into<crate::types::first_class::Type>) -> Result<UndefinedConstant, NoUndefinedValueError> {
+        Ok(UndefinedConstant(check_undefined(_type.into())?))
+    }
+}
+
+impl Display for UndefinedConstant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("undef")
     }
 }
 
+impl ReturnType for UndefinedConstant {
+    fn return_type(&self) -> TypeId { self.0 }
+}
+
+/// `poison`, the result of an operation whose behavior is undefined, which taints everything computed from it.
+#[derive(Debug, Clone)]
+pub struct PoisonConstant(TypeId);
+
+impl PoisonConstant {
+    pub fn new(_type: impl Into<crate::types::first_class::Type>) -> Result<PoisonConstant, NoUndefinedValueError> {
+        Ok(PoisonConstant(check_undefined(_type.into())?))
+    }
+}
+
+impl Display for PoisonConstant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::printer::supports("poison", Some(12), None);
+        f.write_str("poison")
+    }
+}
+
+impl ReturnType for PoisonConstant {
+    fn return_type(&self) -> TypeId { self.0 }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn integer(width: usize, limbs: &[u64], signed: bool) -> String {
+        IntegerConstant::from_limbs(IntegerType(width), limbs, signed).unwrap().to_string()
+    }
+
+    #[test]
+    fn wide_integers_print_in_hexadecimal() {
+        assert_eq!(integer(1, &[1], false), "true");
+        assert_eq!(integer(8, &[255], false), "-1");
+        assert_eq!(integer(128, &[u64::MAX, u64::MAX >> 1], false), "170141183460469231731687303715884105727");
+        // 2^130 and -(2^130), which need more than 128 bits.
+        assert_eq!(integer(200, &[0, 0, 4], false), "u0x400000000000000000000000000000000");
+        assert_eq!(integer(200, &[0, 0, u64::MAX - 3, u64::MAX], true), "s0xC00000000000000000000000000000000");
+        // The sign bit of an `i129` is bit 128, so the leading digit has to repeat it.
+        assert_eq!(integer(129, &[5, 0, 1], false), "s0xF00000000000000000000000000000005");
+        assert_eq!(integer(200, &[u64::MAX; 4], true), "-1");
+    }
+
+    #[test]
+    fn floats_print_in_their_hexadecimal_forms() {
+        assert_eq!(FloatConstant::infinity(FloatType::X86FP80, true).to_string(), "0xKFFFF8000000000000000");
+        // `fp128` and `ppc_fp128` are written with their low half first.
+        assert_eq!(FloatConstant::nan(FloatType::FP128, 1).unwrap().to_string(), "0xL00000000000000017FFF800000000000");
+        assert_eq!(FloatConstant::from_bits(FloatType::PPCFP128, 0x3FF0000000000000).unwrap().to_string(), "0xM3FF00000000000000000000000000000");
+        assert_eq!(FloatConstant::nan(FloatType::Half, 1).unwrap().to_string(), "0xH7E01");
+        assert_eq!(FloatConstant::from_bits(FloatType::BFloat, 0x3F80).unwrap().to_string(), "0xR3F80");
+        assert_eq!(FloatConstant::from_f64(0.5).to_string(), "0.5");
+        assert_eq!(FloatConstant::from_f64(f64::INFINITY).to_string(), "0x7FF0000000000000");
+        assert!(FloatConstant::from_bits(FloatType::Half, 0x1_0000).is_err());
+        assert!(FloatConstant::nan(FloatType::X86FP80, 1 << 62).is_err());
+    }
+
+    #[test]
+    fn float_nans_are_widened_bit_by_bit() {
+        // 0.1 has no short decimal form as a double, so it is written as the equal double.
+        assert_eq!(FloatConstant::from_f32(0.1).to_string(), "0x3FB99999A0000000");
+        assert_eq!(FloatConstant::infinity(FloatType::Float, true).to_string(), "0xFFF0000000000000");
+        // The payload moves up with the significand instead of being lost in a conversion.
+        assert_eq!(FloatConstant::nan(FloatType::Float, 5).unwrap().to_string(), "0x7FF80000A0000000");
+        let signaling = FloatConstant::from_f32(f32::from_bits(0xFF80_0001));
+        assert_eq!(signaling.to_string(), "0xFFF0000020000000");
+    }
+
+    #[test]
+    fn undef_and_poison_need_a_value_type() {
+        use crate::types::first_class::{LabelType, MetadataType, Type};
+        for _type in ["i32", "<vscale x 2 x float>", "{ i8, ptr }", "[ 0 x i8 ]", "token", "x86_amx"] {
+            let _type: Type = _type.parse().unwrap();
+            let undef = UndefinedConstant::new(_type.clone()).unwrap();
+            assert_eq!(format!("{} {}", undef.return_type(), undef), format!("{} undef", _type));
+            let poison = PoisonConstant::new(_type.clone()).unwrap();
+            assert_eq!(format!("{} {}", poison.return_type(), poison), format!("{} poison", _type));
+        }
+        for _type in [Type::from(LabelType), Type::from(MetadataType)] {
+            assert_eq!(UndefinedConstant::new(_type.clone()).err(), Some(NoUndefinedValueError(_type.clone())));
+            assert_eq!(PoisonConstant::new(_type.clone()).err(), Some(NoUndefinedValueError(_type)));
+        }
+        assert_eq!(NoUndefinedValueError(LabelType.into()).to_string(), "`label` has no undef or poison value");
+    }
+}
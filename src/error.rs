@@ -0,0 +1,160 @@
+//! One error type for everything in this crate that can fail, so that `?` works across modules.
+//! Each variant wraps the more specific error of the failing module.
+
+use std::fmt::Display;
+
+use crate::identifier::ParseError;
+use crate::types::{first_class::TypeCreationError, parse::TypeParseError};
+use crate::constant::{simple::{IntegerOutOfRangeError, FloatConstantCreationError, NoUndefinedValueError}, complex::ComplexConstantCreationError, expression::ConstantExpressionError, address::BlockAddressError};
+use crate::module::{DuplicateNameError, data_layout::DataLayoutParseError, function::{NumberingError, instruction::{memory_operator::MemoryAccessError, other_operator::CallError}}};
+use crate::printer::{PrintError, UnsupportedConstructError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Identifier(ParseError),
+    TypeCreation(TypeCreationError),
+    TypeParse(TypeParseError),
+    IntegerOutOfRange(IntegerOutOfRangeError),
+    FloatCreation(FloatConstantCreationError),
+    NoUndefinedValue(NoUndefinedValueError),
+    ConstantCreation(ComplexConstantCreationError),
+    ConstantExpression(ConstantExpressionError),
+    BlockAddress(BlockAddressError),
+    DataLayoutParse(DataLayoutParseError),
+    Numbering(NumberingError),
+    DuplicateName(DuplicateNameError),
+    Call(CallError),
+    MemoryAccess(MemoryAccessError),
+    Print(PrintError)
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Identifier(error)
+    }
+}
+
+impl From<TypeCreationError> for Error {
+    fn from(error: TypeCreationError) -> Self {
+        Error::TypeCreation(error)
+    }
+}
+
+impl From<TypeParseError> for Error {
+    fn from(error: TypeParseError) -> Self {
+        Error::TypeParse(error)
+    }
+}
+
+impl From<IntegerOutOfRangeError> for Error {
+    fn from(error: IntegerOutOfRangeError) -> Self {
+        Error::IntegerOutOfRange(error)
+    }
+}
+
+impl From<FloatConstantCreationError> for Error {
+    fn from(error: FloatConstantCreationError) -> Self {
+        Error::FloatCreation(error)
+    }
+}
+
+impl From<NoUndefinedValueError> for Error {
+    fn from(error: NoUndefinedValueError) -> Self {
+        Error::NoUndefinedValue(error)
+    }
+}
+
+impl From<ComplexConstantCreationError> for Error {
+    fn from(error: ComplexConstantCreationError) -> Self {
+        Error::ConstantCreation(error)
+    }
+}
+
+impl From<ConstantExpressionError> for Error {
+    fn from(error: ConstantExpressionError) -> Self {
+        Error::ConstantExpression(error)
+    }
+}
+
+impl From<BlockAddressError> for Error {
+    fn from(error: BlockAddressError) -> Self {
+        Error::BlockAddress(error)
+    }
+}
+
+impl From<DataLayoutParseError> for Error {
+    fn from(error: DataLayoutParseError) -> Self {
+        Error::DataLayoutParse(error)
+    }
+}
+
+impl From<NumberingError> for Error {
+    fn from(error: NumberingError) -> Self {
+        Error::Numbering(error)
+    }
+}
+
+impl From<DuplicateNameError> for Error {
+    fn from(error: DuplicateNameError) -> Self {
+        Error::DuplicateName(error)
+    }
+}
+
+impl From<CallError> for Error {
+    fn from(error: CallError) -> Self {
+        Error::Call(error)
+    }
+}
+
+impl From<MemoryAccessError> for Error {
+    fn from(error: MemoryAccessError) -> Self {
+        Error::MemoryAccess(error)
+    }
+}
+
+impl From<UnsupportedConstructError> for Error {
+    fn from(error: UnsupportedConstructError) -> Self {
+        Error::Print(PrintError::Unsupported(error))
+    }
+}
+
+impl From<PrintError> for Error {
+    fn from(error: PrintError) -> Self {
+        Error::Print(error)
+    }
+}
+
+impl Error {
+    fn inner(&self) -> &(dyn std::error::Error + 'static) {
+        match self {
+            Self::Identifier(it) => it,
+            Self::TypeCreation(it) => it,
+            Self::TypeParse(it) => it,
+            Self::IntegerOutOfRange(it) => it,
+            Self::FloatCreation(it) => it,
+            Self::NoUndefinedValue(it) => it,
+            Self::ConstantCreation(it) => it,
+            Self::ConstantExpression(it) => it,
+            Self::BlockAddress(it) => it,
+            Self::DataLayoutParse(it) => it,
+            Self::Numbering(it) => it,
+            Self::DuplicateName(it) => it,
+            Self::Call(it) => it,
+            Self::MemoryAccess(it) => it,
+            Self::Print(it) => it
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.inner(), f)
+    }
+}
+
+// The wrapper is transparent: it shows the message of the wrapped error and passes on its source.
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner().source()
+    }
+}
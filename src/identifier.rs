@@ -1,34 +1,191 @@
-use std::{str::Chars, fmt::{Formatter, Display, Debug}};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Formatter, Display, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use crate::IRElement;
 
-pub trait Identifiable {
-    fn id(&self) -> Identifier;
+pub trait Identifiable<'s> {
+    fn id(&self) -> Identifier<'s>;
 }
 
-#[derive(Debug, Clone)]
+/// The name of a global value, printed as `@name`.
+///
+/// Names that aren't plain identifiers or numbers are printed quoted and escaped, e.g. `@"foo bar"`.
+/// Like in LLVM, a name is a sequence of bytes that need not be UTF-8.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[repr(transparent)]
-pub struct GlobalIdentifier<'s>(pub(crate) &'s str);
+pub struct GlobalIdentifier<'s>(pub(crate) Cow<'s, [u8]>);
 
-#[derive(Debug, Clone)]
+/// The name of a local value or block, printed as `%name`.
+///
+/// Unnamed locals are numbered `%0`, `%1`, ... in definition order when their function is printed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[repr(transparent)]
-pub struct LocalIdentifier<'s>(&'s str);
+pub struct LocalIdentifier<'s>(pub(crate) LocalName<'s>);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum LocalName<'s> {
+    Named(Cow<'s, [u8]>),
+    /// A unique key, mapped to the value's number by the function defining it.
+    Unnamed(usize)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Identifier<'s> {
     Global(GlobalIdentifier<'s>),
     Local(LocalIdentifier<'s>)
 }
 
-#[derive(Debug)]
-pub enum ParseError {
-    NotEnoughTokens,
-    UnexpectedToken,
-    IllegalToken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    Empty,
+    ExpectedSigil(&'static str),
+    /// A character that is only allowed in quoted names.
+    IllegalCharacter(char),
+    UnterminatedQuote,
+    InvalidEscape,
+    NulByte
+}
+
+/// A name that could not be parsed or is not allowed, with the byte offset of the problem in `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub text: String,
+    pub position: usize,
+    pub kind: ParseErrorKind
+}
+
+impl ParseError {
+    fn new(text: &str, position: usize, kind: ParseErrorKind) -> Self {
+        ParseError { text: text.to_string(), position, kind }
+    }
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseErrorKind::Empty => f.write_str("empty name"),
+            ParseErrorKind::ExpectedSigil(sigil) => f.write_fmt(format_args!("expected {}", sigil)),
+            ParseErrorKind::IllegalCharacter(c) => f.write_fmt(format_args!("`{}` is only allowed in quoted names", c.escape_default())),
+            ParseErrorKind::UnterminatedQuote => f.write_str("missing closing quote"),
+            ParseErrorKind::InvalidEscape => f.write_str("expected two hexadecimal digits or `\\` after `\\`"),
+            ParseErrorKind::NulByte => f.write_str("names can't contain NUL bytes")
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_fmt(format_args!("invalid identifier `{}`: {} at position {}", self.text.escape_debug(), self.kind, self.position))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<'s> GlobalIdentifier<'s> {
+    /// A global named `name`, without the `@`. Any name except an empty one or one containing
+    /// a NUL byte is allowed; names consisting of digits only refer to unnamed values.
+    pub fn new(name: impl Into<Cow<'s, str>>) -> Result<Self, ParseError> {
+        Ok(GlobalIdentifier(verify_bytes(str_bytes(name.into()))?))
+    }
+    /// A global named by bytes that need not be UTF-8, with the same rules as `new`.
+    pub fn from_bytes(name: impl Into<Cow<'s, [u8]>>) -> Result<Self, ParseError> {
+        Ok(GlobalIdentifier(verify_bytes(name.into())?))
+    }
+    /// The name, with bytes that aren't UTF-8 replaced by `U+FFFD`.
+    pub fn name(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.0)
+    }
+    pub fn name_bytes(&self) -> &[u8] {
+        &self.0
+    }
+    /// The identifier with its name copied if borrowed, e.g. one parsed from a temporary string.
+    pub fn into_owned(self) -> GlobalIdentifier<'static> {
+        GlobalIdentifier(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl<'s> LocalIdentifier<'s> {
+    /// A local named `name`, without the `%`, with the same rules as `GlobalIdentifier::new`.
+    /// Numeric names have to match the number LLVM would give the value.
+    pub fn new(name: impl Into<Cow<'s, str>>) -> Result<Self, ParseError> {
+        Ok(LocalIdentifier(LocalName::Named(verify_bytes(str_bytes(name.into()))?)))
+    }
+    /// A local named by bytes that need not be UTF-8, with the same rules as `new`.
+    pub fn from_bytes(name: impl Into<Cow<'s, [u8]>>) -> Result<Self, ParseError> {
+        Ok(LocalIdentifier(LocalName::Named(verify_bytes(name.into())?)))
+    }
+    /// A new unnamed local, distinct from every other one. Clones refer to the same value.
+    pub fn unnamed() -> Self {
+        static NEXT_KEY: AtomicUsize = AtomicUsize::new(0);
+        LocalIdentifier(LocalName::Unnamed(NEXT_KEY.fetch_add(1, Ordering::Relaxed)))
+    }
+    /// The name, with bytes that aren't UTF-8 replaced by `U+FFFD`.
+    pub fn name(&self) -> Option<Cow<'_, str>> {
+        self.name_bytes().map(String::from_utf8_lossy)
+    }
+    pub fn name_bytes(&self) -> Option<&[u8]> {
+        match &self.0 {
+            LocalName::Named(name) => Some(name),
+            LocalName::Unnamed(_) => None
+        }
+    }
+    pub fn is_unnamed(&self) -> bool {
+        matches!(self.0, LocalName::Unnamed(_))
+    }
+    /// The identifier with its name copied if borrowed. Unnamed identifiers still refer to the same value.
+    pub fn into_owned(self) -> LocalIdentifier<'static> {
+        LocalIdentifier(match self.0 {
+            LocalName::Named(name) => LocalName::Named(Cow::Owned(name.into_owned())),
+            LocalName::Unnamed(key) => LocalName::Unnamed(key)
+        })
+    }
+}
+
+impl<'s> Identifier<'s> {
+    pub fn name(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Identifier::Global(id) => Some(id.name()),
+            Identifier::Local(id) => id.name()
+        }
+    }
+    pub fn name_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Identifier::Global(id) => Some(id.name_bytes()),
+            Identifier::Local(id) => id.name_bytes()
+        }
+    }
+    pub fn into_owned(self) -> Identifier<'static> {
+        match self {
+            Identifier::Global(id) => Identifier::Global(id.into_owned()),
+            Identifier::Local(id) => Identifier::Local(id.into_owned())
+        }
+    }
+}
+
+pub(crate) fn verify_name(name: Cow<'_, str>) -> Result<Cow<'_, str>, ParseError> {
+    if name.is_empty() { return Err(ParseError::new(&name, 0, ParseErrorKind::Empty)); }
+    if let Some(position) = name.find('\0') { return Err(ParseError::new(&name, position, ParseErrorKind::NulByte)); }
+    return Ok(name);
+}
+
+pub(crate) fn verify_bytes(name: Cow<'_, [u8]>) -> Result<Cow<'_, [u8]>, ParseError> {
+    if name.is_empty() { return Err(ParseError::new("", 0, ParseErrorKind::Empty)); }
+    if let Some(position) = name.iter().position(|&byte| byte == 0) { return Err(ParseError::new(&String::from_utf8_lossy(&name), position, ParseErrorKind::NulByte)); }
+    return Ok(name);
+}
+
+fn str_bytes(name: Cow<'_, str>) -> Cow<'_, [u8]> {
+    match name {
+        Cow::Borrowed(name) => Cow::Borrowed(name.as_bytes()),
+        Cow::Owned(name) => Cow::Owned(name.into_bytes())
+    }
 }
 
 impl<'s> Display for GlobalIdentifier<'s> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        f.write_fmt(format_args!("@{}", self.0))
+        f.write_fmt(format_args!("@{}", Name(&self.0)))
     }
 }
 impl<'s> Display for LocalIdentifier<'s> {
@@ -36,6 +193,32 @@ impl<'s> Display for LocalIdentifier<'s> {
         f.write_fmt(format_args!("%{}", self.0))
     }
 }
+
+/// The local name without the `%`, as written before the colon of a label.
+impl<'s> Display for LocalName<'s> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LocalName::Named(name) => Display::fmt(&Name(name), f),
+            LocalName::Unnamed(key) => match NUMBERING.with(|numbering| numbering.borrow().as_ref().and_then(|it| it.get(key).copied())) {
+                Some(number) => f.write_fmt(format_args!("{}", number)),
+                // Outside of the function defining it the value has no number.
+                None => crate::printer::report(crate::printer::PrintError::UnnumberedLocal)
+            }
+        }
+    }
+}
+
+thread_local! {
+    static NUMBERING: RefCell<Option<HashMap<usize, usize>>> = const { RefCell::new(None) };
+}
+
+/// Runs `print` with unnamed locals printed as their number in `numbering`.
+pub(crate) fn with_numbering<R>(numbering: HashMap<usize, usize>, print: impl FnOnce() -> R) -> R {
+    let previous = NUMBERING.with(|it| it.replace(Some(numbering)));
+    let result = print();
+    NUMBERING.with(|it| it.replace(previous));
+    return result;
+}
 impl<'s> Display for Identifier<'s> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
@@ -49,61 +232,143 @@ impl<'s> IRElement for GlobalIdentifier<'s> {}
 impl<'s> IRElement for LocalIdentifier<'s> {}
 impl<'s> IRElement for Identifier<'s> {}
 
-//TODO: allow quotes and escapes
+pub(crate) fn is_char_valid(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '$' || c == '.' || c == '_'
+}
 
-impl<'s> Identifier<'s> {
-    fn is_char_valid(c: char) -> bool {
-        c.is_digit(10) || c.is_alphabetic() || c == '$' || c == '.' || c == '_'
-    }
-    fn verify_num_ident(chars: &mut Chars) -> Result<(), ParseError> {
-        let mut null = true;
-        while let Some(c) = chars.next() {
-            if null { null = false }
-            if !c.is_digit(10) { return Err(ParseError::IllegalToken); }
-        }
-        if null { return Err(ParseError::NotEnoughTokens); }
-        return Ok(());
-    }
-    fn verify_normal_ident(chars: &mut Chars) -> Result<(), ParseError> {
-        let first = chars.next();
-        if let Some(first) = first {
-            if !Self::is_char_valid(first) { return Err(ParseError::IllegalToken); }
-            while let Some(c) = chars.next() {
-                if !Self::is_char_valid(c) || !c.is_digit(10) { return Err(ParseError::IllegalToken); }
+/// The first character that keeps `name` from being written without quotes.
+fn illegal_character(name: &str) -> Option<(usize, char)> {
+    if name.chars().all(|c| c.is_ascii_digit()) { return None; }
+    // Names starting with a digit have to be numbers.
+    let numeric = name.starts_with(|c: char| c.is_ascii_digit());
+    return name.char_indices().find(|(_, c)| if numeric { !c.is_ascii_digit() } else { !is_char_valid(*c) });
+}
+
+/// `name` if it can be written without quotes: `[-a-zA-Z$._][-a-zA-Z$._0-9]*` or a number.
+fn plain_name(name: &[u8]) -> Option<&str> {
+    return std::str::from_utf8(name).ok().filter(|it| !it.is_empty() && illegal_character(it).is_none());
+}
+
+/// A name as it appears after a sigil or before the colon of a label, quoted if necessary.
+pub(crate) struct Name<'a>(pub(crate) &'a [u8]);
+
+impl Display for Name<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if let Some(name) = plain_name(self.0) { return f.write_str(name); }
+        Display::fmt(&QuotedBytes(self.0), f)
+    }
+}
+
+/// A string literal in double quotes. Quotes, backslashes and bytes that aren't
+/// printable ASCII are escaped as `\xx`.
+pub(crate) struct QuotedString<'a>(pub(crate) &'a str);
+
+impl Display for QuotedString<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&QuotedBytes(self.0.as_bytes()), f)
+    }
+}
+
+/// Bytes that need not be UTF-8 as a string literal, escaped like [`QuotedString`].
+pub(crate) struct QuotedBytes<'a>(pub(crate) &'a [u8]);
+
+impl Display for QuotedBytes<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("\"")?;
+        for &byte in self.0 {
+            if (byte == b' ' || byte.is_ascii_graphic()) && byte != b'"' && byte != b'\\' {
+                f.write_char(byte as char)?;
+            } else {
+                f.write_fmt(format_args!("\\{:02X}", byte))?;
             }
-            return Ok(());
         }
-        return Err(ParseError::NotEnoughTokens);
-    }
-    fn verify_special_ident(chars: &mut Chars) -> Result<(), ParseError> {
-        let first = chars.next();
-        if let Some(first) = first {
-            if first != '"' { return Err(ParseError::IllegalToken); }
-            while let Some(c) = chars.next() {
-                if c == '"' { 
-                    if chars.next().is_none() { return Err(ParseError::IllegalToken); }
-                }
-                if c == '\\' {
-                    'inner:for _ in 0..2 {
-                        if let Some(c) = chars.next() {
-                            if c.is_digit(16) { continue 'inner; }
-                        }
-                        return Err(ParseError::IllegalToken);
-                    }
-                }
-            }
-            return Ok(());
+        f.write_str("\"")
+    }
+}
+
+/// Parses a name following a sigil, either plain, numeric or quoted with `\xx` escapes.
+/// Escaped bytes need not form UTF-8. Errors carry the byte offset within `s`.
+pub(crate) fn parse_name(s: &str) -> Result<Cow<'_, [u8]>, (usize, ParseErrorKind)> {
+    let Some(quoted) = s.strip_prefix('"') else {
+        if s.is_empty() { return Err((0, ParseErrorKind::Empty)); }
+        if let Some((position, c)) = illegal_character(s) { return Err((position, ParseErrorKind::IllegalCharacter(c))); }
+        return Ok(Cow::Borrowed(s.as_bytes()));
+    };
+    let Some(quoted) = quoted.strip_suffix('"') else { return Err((s.len(), ParseErrorKind::UnterminatedQuote)); };
+    if let Some(position) = quoted.find('"') { return Err((position + 1, ParseErrorKind::IllegalCharacter('"'))); }
+    if quoted.is_empty() { return Err((1, ParseErrorKind::Empty)); }
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut position = 0;
+    let source = quoted.as_bytes();
+    while let Some(&byte) = source.get(position) {
+        if byte != b'\\' {
+            bytes.push(byte);
+            position += 1;
+            continue;
+        }
+        if source.get(position + 1) == Some(&b'\\') {
+            bytes.push(b'\\');
+            position += 2;
+            continue;
+        }
+        let hex = source.get(position + 1..position + 3).and_then(|it| std::str::from_utf8(it).ok());
+        let Some(byte) = hex.and_then(|it| u8::from_str_radix(it, 16).ok()) else { return Err((position + 1, ParseErrorKind::InvalidEscape)); };
+        if byte == 0 { return Err((position + 1, ParseErrorKind::NulByte)); }
+        bytes.push(byte);
+        position += 3;
+    }
+    if let Some(position) = quoted.find('\0') { return Err((position + 1, ParseErrorKind::NulByte)); }
+    return Ok(Cow::Owned(bytes));
+}
+
+/// Hands out names that are unique within a scope, like the locals of a function.
+///
+/// A fresh name is the requested base name if it's free, otherwise the base with the lowest
+/// free suffix: `tmp`, `tmp.1`, `tmp.2`, ... Names defined by hand have to be reserved
+/// so that they aren't handed out. A name can be reserved ahead of its definition, but
+/// defined only once.
+#[derive(Debug, Clone, Default)]
+pub struct NameSupply {
+    reserved: HashSet<Vec<u8>>,
+    defined: HashSet<Vec<u8>>,
+    next_suffix: HashMap<String, usize>
+}
+
+impl NameSupply {
+    pub fn new() -> Self {
+        NameSupply::default()
+    }
+    /// Marks `name` as taken. Returns whether it was free before.
+    pub fn reserve(&mut self, name: impl AsRef<[u8]>) -> bool {
+        let name = name.as_ref();
+        if self.reserved.contains(name) { return false; }
+        self.reserved.insert(name.to_vec());
+        return true;
+    }
+    pub fn is_reserved(&self, name: impl AsRef<[u8]>) -> bool {
+        self.reserved.contains(name.as_ref())
+    }
+    /// Marks `name` as the name of a definition, which reserves it too. Returns whether
+    /// nothing was defined with it before.
+    pub fn define(&mut self, name: impl AsRef<[u8]>) -> bool {
+        self.reserve(name.as_ref());
+        self.defined.insert(name.as_ref().to_vec())
+    }
+    pub fn is_defined(&self, name: impl AsRef<[u8]>) -> bool {
+        self.defined.contains(name.as_ref())
+    }
+    /// A new name based on `base`, which is reserved from now on. Numeric and empty bases
+    /// always get a suffix, as those names would refer to unnamed values.
+    pub fn fresh(&mut self, base: &str) -> String {
+        if !base.is_empty() && !base.chars().all(|c| c.is_ascii_digit()) && self.reserve(base) {
+            return base.to_string();
+        }
+        let suffix = self.next_suffix.entry(base.to_string()).or_insert(1);
+        loop {
+            let name = format!("{}.{}", base, suffix);
+            *suffix += 1;
+            if self.reserved.insert(name.clone().into_bytes()) { return name; }
         }
-        return Err(ParseError::NotEnoughTokens);
-    }
-    fn verify_ident(string: &str) -> Result<(), ParseError> {
-        Self::verify_num_ident(&mut string.chars()).or_else(|err| match err {
-            ParseError::IllegalToken => Self::verify_normal_ident(&mut string.chars()).or_else(|err| match err {
-                ParseError::IllegalToken => Self::verify_special_ident(&mut string.chars()),
-                err => Err(err)
-            }),
-            err => Err(err)
-        })
     }
 }
 
@@ -119,35 +384,71 @@ impl<'s> From<LocalIdentifier<'s>> for Identifier<'s> {
     }
 }
 
+/// Parses `@name`, `@42` or `@"quoted name"`.
 impl<'s> TryFrom<&'s str> for GlobalIdentifier<'s> {
     type Error = ParseError;
     fn try_from(s: &'s str) -> Result<Self, Self::Error> {
-        if s.len() < 3 { return Err(ParseError::NotEnoughTokens); }
-        let mut chars = s.chars();
-        if chars.next().unwrap() != '@' { return Err(ParseError::UnexpectedToken); }
-        Identifier::verify_ident(s)?;
-        Ok(GlobalIdentifier(&s[1..]))
+        let Some(name) = s.strip_prefix('@') else { return Err(ParseError::new(s, 0, ParseErrorKind::ExpectedSigil("`@`"))); };
+        let name = parse_name(name).map_err(|(position, kind)| ParseError::new(s, position + 1, kind))?;
+        Ok(GlobalIdentifier(name))
     }
 }
 
+/// Parses `%name`, `%42` or `%"quoted name"`.
 impl<'s> TryFrom<&'s str> for LocalIdentifier<'s> {
     type Error = ParseError;
     fn try_from(s: &'s str) -> Result<Self, Self::Error> {
-        if s.len() < 3 { return Err(ParseError::NotEnoughTokens); }
-        let mut chars = s.chars();
-        if chars.next().unwrap() != '%' { return Err(ParseError::UnexpectedToken); }
-        Identifier::verify_ident(s)?;
-        Ok(LocalIdentifier(&s[1..]))
+        let Some(name) = s.strip_prefix('%') else { return Err(ParseError::new(s, 0, ParseErrorKind::ExpectedSigil("`%`"))); };
+        let name = parse_name(name).map_err(|(position, kind)| ParseError::new(s, position + 1, kind))?;
+        Ok(LocalIdentifier(LocalName::Named(name)))
     }
 }
 
 impl<'s> TryFrom<&'s str> for Identifier<'s> {
     type Error = ParseError;
     fn try_from(s: &'s str) -> Result<Self, Self::Error> {
-        GlobalIdentifier::try_from(s).map(|it| Identifier::Global(it)).or_else(|err| match err {
-            ParseError::UnexpectedToken => LocalIdentifier::try_from(s).map(|it| Identifier::Local(it)),
-            _ => Err(err)
-        })
+        match s.chars().next() {
+            Some('@') => GlobalIdentifier::try_from(s).map(Identifier::Global),
+            Some('%') => LocalIdentifier::try_from(s).map(Identifier::Local),
+            _ => Err(ParseError::new(s, 0, ParseErrorKind::ExpectedSigil("`@` or `%`")))
+        }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_print_as_parsed() {
+        for name in ["@f", "@42", "%x.1", "@\"foo bar\"", "%\"\\22quoted\\22\"", "@\"\\5C\""] {
+            assert_eq!(Identifier::try_from(name).unwrap().to_string(), name);
+        }
+        assert_eq!(GlobalIdentifier::new("foo bar").unwrap().to_string(), "@\"foo bar\"");
+        assert_eq!(LocalIdentifier::try_from("%\"plain\"").unwrap().to_string(), "%plain");
+    }
+
+    #[test]
+    fn escaped_bytes_need_not_be_utf8() {
+        let identifier = GlobalIdentifier::try_from("@\"\\FF\\C3\\A9\"").unwrap();
+        assert_eq!(identifier.name_bytes(), b"\xFF\xC3\xA9");
+        assert_eq!(identifier.name(), "\u{FFFD}\u{E9}");
+        assert_eq!(identifier.to_string(), "@\"\\FF\\C3\\A9\"");
+        assert_eq!(LocalIdentifier::from_bytes(b"\xFF".as_slice()).unwrap().to_string(), "%\"\\FF\"");
+    }
+
+    #[test]
+    fn malformed_names_are_rejected() {
+        let kind = |s: &str| Identifier::try_from(s).unwrap_err().kind;
+        assert_eq!(kind("x"), ParseErrorKind::ExpectedSigil("`@` or `%`"));
+        assert_eq!(kind("@"), ParseErrorKind::Empty);
+        assert_eq!(kind("@\"\""), ParseErrorKind::Empty);
+        assert_eq!(kind("%a b"), ParseErrorKind::IllegalCharacter(' '));
+        assert_eq!(kind("%1a"), ParseErrorKind::IllegalCharacter('a'));
+        assert_eq!(kind("@\"a"), ParseErrorKind::UnterminatedQuote);
+        assert_eq!(kind("@\"\\G0\""), ParseErrorKind::InvalidEscape);
+        assert_eq!(kind("@\"\\00\""), ParseErrorKind::NulByte);
+        assert_eq!(GlobalIdentifier::from_bytes(b"a\0".as_slice()).unwrap_err().kind, ParseErrorKind::NulByte);
+    }
+}
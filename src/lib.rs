@@ -4,7 +4,12 @@ pub mod identifier;
 pub mod module;
 pub mod types;
 pub mod constant;
+pub mod metadata;
 pub mod reference;
+pub mod printer;
+pub mod error;
+
+pub use error::Error;
 
 pub trait IRElement: Display + Debug {}
 
@@ -0,0 +1,334 @@
+//! Metadata: strings, nodes and the attachments and named metadata that refer to them.
+//!
+//! Nodes are printed as references like `!0`. A module numbers every node reachable from its
+//! attachments and named metadata and prints their definitions after the functions.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Display, Formatter, Write};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, OnceLock};
+
+use crate::IRElement;
+use crate::constant::Constant;
+use crate::identifier::ParseError;
+use crate::printer::PrintError;
+use crate::types::ReturnType;
+
+/// An operand of a metadata node.
+#[derive(Debug, Clone)]
+pub enum Metadata {
+    String(MetadataString),
+    Constant(Constant<'static>),
+    Node(MetadataNode),
+    Null
+}
+
+impl From<MetadataString> for Metadata {
+    fn from(metadata: MetadataString) -> Self {
+        Metadata::String(metadata)
+    }
+}
+
+impl From<Constant<'static>> for Metadata {
+    fn from(constant: Constant<'static>) -> Self {
+        Metadata::Constant(constant)
+    }
+}
+
+impl From<MetadataNode> for Metadata {
+    fn from(metadata: MetadataNode) -> Self {
+        Metadata::Node(metadata)
+    }
+}
+
+impl IRElement for Metadata {}
+
+impl Display for Metadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(it) => Display::fmt(it, f),
+            Self::Constant(it) => f.write_fmt(format_args!("{} {}", it.return_type(), it)),
+            Self::Node(it) => Display::fmt(it, f),
+            Self::Null => f.write_str("null")
+        }
+    }
+}
+
+/// A string, printed as `!"text"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MetadataString(Cow<'static, str>);
+
+impl MetadataString {
+    pub fn new(text: impl Into<Cow<'static, str>>) -> Self {
+        MetadataString(text.into())
+    }
+    pub fn text(&self) -> &str {
+        &self.0
+    }
+}
+
+impl IRElement for MetadataString {}
+
+impl Display for MetadataString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("!{}", crate::identifier::QuotedString(&self.0)))
+    }
+}
+
+/// A tuple of metadata, printed as `!{...}` where it is defined and as `!N` where it is used.
+///
+/// Clones refer to the same node, so a distinct node can be declared, used in its own operands
+/// (as loop metadata does) and given its operands later. Two nodes are equal if they are clones
+/// of the same node. As nodes are shared, their operands own everything they refer to.
+#[derive(Clone)]
+pub struct MetadataNode {
+    distinct: bool,
+    operands: Arc<OnceLock<Vec<Metadata>>>
+}
+
+impl MetadataNode {
+    pub fn new(operands: Vec<Metadata>) -> Self {
+        let node = MetadataNode { distinct: false, operands: Arc::new(OnceLock::new()) };
+        let _ = node.operands.set(operands);
+        node
+    }
+    /// A node that LLVM never merges with another node of the same operands.
+    pub fn new_distinct(operands: Vec<Metadata>) -> Self {
+        let node = MetadataNode::declare_distinct();
+        let _ = node.operands.set(operands);
+        node
+    }
+    /// A distinct node without operands yet, see [`MetadataNode::set_operands`].
+    /// Printing fails if the operands are never set.
+    pub fn declare_distinct() -> Self {
+        MetadataNode { distinct: true, operands: Arc::new(OnceLock::new()) }
+    }
+    /// Sets the operands of a declared node. Gives the operands back if they were already set.
+    pub fn set_operands(&self, operands: Vec<Metadata>) -> Result<(), Vec<Metadata>> {
+        self.operands.set(operands)
+    }
+    /// The operands, none while a declared node has no operands yet.
+    pub fn operands(&self) -> &[Metadata] {
+        self.operands.get().map(Vec::as_slice).unwrap_or(&[])
+    }
+    pub fn is_distinct(&self) -> bool {
+        self.distinct
+    }
+    fn key(&self) -> usize {
+        Arc::as_ptr(&self.operands) as *const () as usize
+    }
+    /// The definition of this node: `!{...}`, or `distinct !{...}`.
+    pub(crate) fn definition(&self) -> NodeDefinition<'_> {
+        NodeDefinition(self)
+    }
+}
+
+impl PartialEq for MetadataNode {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.operands, &other.operands)
+    }
+}
+
+impl Eq for MetadataNode {}
+
+impl Hash for MetadataNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+// Nodes may refer back to themselves, so the operands aren't followed.
+impl Debug for MetadataNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetadataNode").field("distinct", &self.distinct).field("operands", &self.operands().len()).finish()
+    }
+}
+
+impl IRElement for MetadataNode {}
+
+impl Display for MetadataNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match NUMBERING.with(|numbering| numbering.borrow().as_ref().and_then(|it| it.get(&self.key()).copied())) {
+            Some(number) => f.write_fmt(format_args!("!{}", number)),
+            // Outside of a module the node is written in place, which it can't be within itself.
+            None => {
+                if INLINE.with(|it| it.borrow().contains(&self.key())) { return crate::printer::report(PrintError::MetadataCycle); }
+                INLINE.with(|it| it.borrow_mut().push(self.key()));
+                let result = Display::fmt(&self.definition(), f);
+                INLINE.with(|it| it.borrow_mut().pop());
+                return result;
+            }
+        }
+    }
+}
+
+pub(crate) struct NodeDefinition<'a>(&'a MetadataNode);
+
+impl Display for NodeDefinition<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0.operands.get().is_none() { crate::printer::report(PrintError::UndefinedMetadataNode)?; }
+        if self.0.distinct { f.write_str("distinct ")?; }
+        f.write_str("!{")?;
+        for (i, operand) in self.0.operands().iter().enumerate() {
+            if i != 0 { f.write_str(", ")?; }
+            Display::fmt(operand, f)?;
+        }
+        f.write_str("}")
+    }
+}
+
+thread_local! {
+    static NUMBERING: RefCell<Option<HashMap<usize, usize>>> = const { RefCell::new(None) };
+    /// The unnumbered nodes being written in place.
+    static INLINE: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Numbers `nodes` and every node within them, in the order they are reached.
+pub(crate) fn number_nodes<'a>(nodes: impl Iterator<Item = &'a MetadataNode>) -> Vec<MetadataNode> {
+    let mut numbered = Vec::new();
+    let mut seen = HashSet::new();
+    let mut pending: Vec<MetadataNode> = nodes.cloned().collect();
+    pending.reverse();
+    while let Some(node) = pending.pop() {
+        if !seen.insert(node.key()) { continue; }
+        let operands = node.operands().iter().rev().filter_map(|it| if let Metadata::Node(it) = it { Some(it.clone()) } else { None });
+        pending.extend(operands);
+        numbered.push(node);
+    }
+    return numbered;
+}
+
+/// Runs `print` with the nodes in `numbered` printed as `!N`, N being their index.
+pub(crate) fn with_node_numbering<R>(numbered: &[MetadataNode], print: impl FnOnce() -> R) -> R {
+    let numbering = numbered.iter().enumerate().map(|(number, node)| (node.key(), number)).collect();
+    let previous = NUMBERING.with(|it| it.replace(Some(numbering)));
+    let result = print();
+    NUMBERING.with(|it| it.replace(previous));
+    return result;
+}
+
+/// A metadata name after `!`, as in `!dbg` or `!llvm.ident`. Characters that aren't allowed
+/// in plain names, and a leading digit, are escaped as `\xx`.
+struct MetadataName<'a>(&'a str);
+
+impl Display for MetadataName<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_char('!')?;
+        for (i, &byte) in self.0.as_bytes().iter().enumerate() {
+            let plain = byte.is_ascii_alphabetic() || matches!(byte, b'-' | b'$' | b'.' | b'_') || (i != 0 && byte.is_ascii_digit());
+            if plain { f.write_char(byte as char)?; } else { f.write_fmt(format_args!("\\{:02X}", byte))?; }
+        }
+        Ok(())
+    }
+}
+
+/// A node attached to a global variable, function or instruction under a kind, like `!dbg !0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataAttachment<'s> {
+    kind: Cow<'s, str>,
+    node: MetadataNode
+}
+
+impl<'s> MetadataAttachment<'s> {
+    /// Attaches `node` as `kind`, without the `!`. Kinds follow the rules of `GlobalIdentifier::new`.
+    pub fn new(kind: impl Into<Cow<'s, str>>, node: MetadataNode) -> Result<Self, ParseError> {
+        Ok(MetadataAttachment { kind: crate::identifier::verify_name(kind.into())?, node })
+    }
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+    pub fn node(&self) -> &MetadataNode {
+        &self.node
+    }
+}
+
+impl<'s> IRElement for MetadataAttachment<'s> {}
+
+impl<'s> Display for MetadataAttachment<'s> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} {}", MetadataName(&self.kind), self.node))
+    }
+}
+
+/// A module-level list of nodes under a name, like `!llvm.ident = !{!0}`.
+#[derive(Debug, Clone)]
+pub struct NamedMetadata<'s> {
+    name: Cow<'s, str>,
+    nodes: Vec<MetadataNode>
+}
+
+impl<'s> NamedMetadata<'s> {
+    /// Names follow the rules of `GlobalIdentifier::new`, without the `!`.
+    pub fn new(name: impl Into<Cow<'s, str>>, nodes: Vec<MetadataNode>) -> Result<Self, ParseError> {
+        Ok(NamedMetadata { name: crate::identifier::verify_name(name.into())?, nodes })
+    }
+    pub fn with_node(mut self, node: MetadataNode) -> Self {
+        self.nodes.push(node);
+        self
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn nodes(&self) -> &[MetadataNode] {
+        &self.nodes
+    }
+}
+
+impl<'s> IRElement for NamedMetadata<'s> {}
+
+impl<'s> Display for NamedMetadata<'s> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} = !{{", MetadataName(&self.name)))?;
+        for (i, node) in self.nodes.iter().enumerate() {
+            if i != 0 { f.write_str(", ")?; }
+            Display::fmt(node, f)?;
+        }
+        f.write_str("}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::simple::IntegerConstant;
+    use crate::identifier::GlobalIdentifier;
+    use crate::module::Module;
+    use crate::module::function::Function;
+    use crate::types::first_class::single_value::IntegerType;
+
+    fn declaration(attachment: MetadataAttachment<'static>) -> Module<'static> {
+        let mut module = Module::new();
+        module.add_function(Function::new(GlobalIdentifier::new("f").unwrap(), crate::types::Type::Void, vec![]).unwrap().with_metadata(attachment)).unwrap();
+        return module;
+    }
+
+    #[test]
+    fn nodes_are_numbered_in_the_order_they_are_reached() {
+        let shared = MetadataNode::new(vec![Constant::from(IntegerConstant::from_i64(IntegerType(32), 7).unwrap()).into(), Metadata::Null]);
+        let outer = MetadataNode::new(vec![shared.clone().into(), MetadataString::new("a \"b\"").into(), shared.into()]);
+        let mut module = declaration(MetadataAttachment::new("9kind", outer.clone()).unwrap());
+        module.add_named_metadata(NamedMetadata::new("llvm.ident", vec![outer]).unwrap()).unwrap();
+        let expected = "declare !\\39kind !0 void @f()\n\n!llvm.ident = !{!0}\n\n!0 = !{!1, !\"a \\22b\\22\", !1}\n!1 = !{i32 7, null}\n";
+        assert_eq!(module.printer().print().unwrap(), expected);
+    }
+
+    #[test]
+    fn distinct_nodes_may_refer_to_themselves() {
+        let node = MetadataNode::declare_distinct();
+        node.set_operands(vec![node.clone().into()]).unwrap();
+        assert!(node.set_operands(vec![]).is_err());
+        let module = declaration(MetadataAttachment::new("loop", node.clone()).unwrap());
+        assert!(module.printer().print().unwrap().ends_with("!0 = distinct !{!0}\n"));
+        // Outside of a module the node would have to be written within itself.
+        assert!(std::fmt::Write::write_fmt(&mut String::new(), format_args!("{}", node)).is_err());
+        assert_eq!(MetadataNode::new(vec![MetadataNode::new(vec![]).into()]).to_string(), "!{!{}}");
+    }
+
+    #[test]
+    fn undefined_nodes_are_reported() {
+        let module = declaration(MetadataAttachment::new("loop", MetadataNode::declare_distinct()).unwrap());
+        assert_eq!(module.printer().print(), Err(PrintError::UndefinedMetadataNode));
+    }
+}
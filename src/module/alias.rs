@@ -0,0 +1,124 @@
+use std::fmt::Display;
+
+use crate::identifier::{GlobalIdentifier, Identifiable};
+use crate::constant::Constant;
+use crate::constant::expression::ConstantExpressionError;
+use crate::module::{LinkageType, RuntimePreemptionSpecifier, VisibilityStyle, DLLStorageClass, UnnamedAddress};
+use crate::types::ReturnType;
+use crate::types::first_class::single_value::PointerType;
+use crate::types::interner::TypeId;
+
+use super::ThreadLocalStorageModel;
+
+/// A second name for a global variable or function, or for a constant expression based on one.
+#[derive(Debug)]
+pub struct Alias<'s> {
+    identifier: GlobalIdentifier<'s>,
+    linkage: Option<LinkageType>,
+    preemtion_specifier: RuntimePreemptionSpecifier,
+    visibility: VisibilityStyle,
+    dll_storage_class: Option<DLLStorageClass>,
+    thread_local: Option<ThreadLocalStorageModel>,
+    unnamed_address: Option<UnnamedAddress>,
+    return_type: TypeId,
+    aliasee: Constant<'s>
+}
+
+impl<'s> Alias<'s> {
+    /// Names `aliasee`, a pointer to a value of `return_type`. A typed pointer has to point
+    /// to `return_type`.
+    pub fn new(identifier: GlobalIdentifier<'s>, return_type: impl Into<TypeId>, aliasee: Constant<'s>) -> Result<Self, ConstantExpressionError> {
+        let return_type = return_type.into();
+        let aliasee_type = &*aliasee.return_type();
+        if !aliasee_type.is_pointer() { return Err(ConstantExpressionError::InvalidOperand { operator: "alias", operand: aliasee_type.clone() }); }
+        if aliasee_type.pointee().is_some_and(|pointee| *pointee != crate::types::Type::from(return_type.get().clone())) {
+            let address_space = aliasee_type.address_space().unwrap_or(0);
+            let expected = PointerType::new_with_address_space(return_type.get().clone(), address_space).into();
+            return Err(ConstantExpressionError::TypeMismatch { operator: "alias", expected, found: aliasee_type.clone() });
+        }
+        Ok(Alias {
+            identifier,
+            linkage: None,
+            preemtion_specifier: RuntimePreemptionSpecifier::Preemptable,
+            visibility: VisibilityStyle::Default,
+            dll_storage_class: None,
+            thread_local: None,
+            unnamed_address: None,
+            return_type,
+            aliasee
+        })
+    }
+    pub fn with_linkage(mut self, linkage: LinkageType) -> Self {
+        self.linkage = Some(linkage);
+        self
+    }
+    pub fn with_preemption_specifier(mut self, preemption_specifier: RuntimePreemptionSpecifier) -> Self {
+        self.preemtion_specifier = preemption_specifier;
+        self
+    }
+    pub fn with_visibility(mut self, visibility: VisibilityStyle) -> Self {
+        self.visibility = visibility;
+        self
+    }
+    pub fn with_dll_storage_class(mut self, dll_storage_class: DLLStorageClass) -> Self {
+        self.dll_storage_class = Some(dll_storage_class);
+        self
+    }
+    pub fn with_thread_local(mut self, thread_local: ThreadLocalStorageModel) -> Self {
+        self.thread_local = Some(thread_local);
+        self
+    }
+    pub fn with_unnamed_address(mut self, unnamed_address: UnnamedAddress) -> Self {
+        self.unnamed_address = Some(unnamed_address);
+        self
+    }
+    pub fn identifier(&self) -> &GlobalIdentifier<'s> {
+        &self.identifier
+    }
+    /// The address space of the aliasee, which the alias shares.
+    pub fn address_space(&self) -> usize {
+        self.aliasee.return_type().get().address_space().unwrap_or(0)
+    }
+}
+
+impl<'s> Display for Alias<'s> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} = ", self.identifier))?;
+        if let Some(linkage) = &self.linkage { f.write_fmt(format_args!("{} ", linkage))?; }
+        if let RuntimePreemptionSpecifier::Local = self.preemtion_specifier { f.write_fmt(format_args!("{} ", self.preemtion_specifier))?; }
+        if let VisibilityStyle::Default = self.visibility {} else { f.write_fmt(format_args!("{} ", self.visibility))?; }
+        if let Some(dll_storage_class) = &self.dll_storage_class { f.write_fmt(format_args!("{} ", dll_storage_class))?; }
+        if let Some(thread_local) = &self.thread_local { f.write_fmt(format_args!("{} ", thread_local))?; }
+        if let Some(unnamed_address) = &self.unnamed_address { f.write_fmt(format_args!("{} ", unnamed_address))?; }
+        f.write_fmt(format_args!("alias {}, {} {}", self.return_type, self.aliasee.return_type(), self.aliasee))
+    }
+}
+
+impl<'s> Identifiable<'s> for Alias<'s> {
+    fn id(&self) -> crate::identifier::Identifier<'s> { self.identifier.clone().into() }
+}
+
+impl<'s> ReturnType for Alias<'s> {
+    fn return_type(&self) -> TypeId { self.return_type }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::address::GlobalAddressConstant;
+    use crate::module::global_variable::{GlobalVariable, GlobalVariableType};
+    use crate::types::first_class::single_value::IntegerType;
+
+    #[test]
+    fn typed_aliasees_point_to_the_alias_type() {
+        let counter = GlobalVariable::new(GlobalIdentifier::new("counter").unwrap(), GlobalVariableType::Global, IntegerType(32), None);
+        let aliasee = || Constant::from(GlobalAddressConstant::from(&counter));
+        let alias = Alias::new(GlobalIdentifier::new("a").unwrap(), IntegerType(32), aliasee()).unwrap();
+        assert_eq!(alias.to_string(), "@a = alias i32, i32* @counter");
+        let error = ConstantExpressionError::TypeMismatch { operator: "alias", expected: PointerType::new(IntegerType(64)).into(), found: PointerType::new(IntegerType(32)).into() };
+        assert_eq!(Alias::new(GlobalIdentifier::new("a").unwrap(), IntegerType(64), aliasee()).err(), Some(error));
+        // Without a pointee there is nothing to check.
+        let opaque = crate::constant::simple::NullPointerConstant::new(PointerType::new_opaque());
+        assert!(Alias::new(GlobalIdentifier::new("a").unwrap(), IntegerType(64), Constant::from(opaque)).is_ok());
+    }
+}
@@ -0,0 +1,657 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::IRElement;
+use crate::types::first_class::{Type, single_value, aggregate};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub enum Endianness {
+    Little,
+    Big
+}
+
+impl IRElement for Endianness {}
+
+impl Display for Endianness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Endianness::Little => "e",
+            Endianness::Big => "E"
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub enum ManglingMode {
+    Elf,
+    Mips,
+    MachO,
+    WindowsX86Coff,
+    WindowsCoff,
+    XCoff,
+    Goff
+}
+
+impl IRElement for ManglingMode {}
+
+impl Display for ManglingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ManglingMode::Elf => "e",
+            ManglingMode::Mips => "m",
+            ManglingMode::MachO => "o",
+            ManglingMode::WindowsX86Coff => "x",
+            ManglingMode::WindowsCoff => "w",
+            ManglingMode::XCoff => "a",
+            ManglingMode::Goff => "l"
+        })
+    }
+}
+
+/// An ABI and preferred alignment, both in bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alignment {
+    pub abi: usize,
+    pub preferred: usize
+}
+
+impl Alignment {
+    pub fn new(abi: usize, preferred: usize) -> Self {
+        Alignment { abi, preferred }
+    }
+}
+
+/// Alignment of an integer, float or vector type of `size` bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrimitiveLayout {
+    pub size: usize,
+    pub alignment: Alignment
+}
+
+impl PrimitiveLayout {
+    pub fn new(size: usize, abi: usize, preferred: usize) -> Self {
+        PrimitiveLayout { size, alignment: Alignment::new(abi, preferred) }
+    }
+}
+
+/// Size, alignment and index width (all in bits) of pointers in an address space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerLayout {
+    pub address_space: usize,
+    pub size: usize,
+    pub alignment: Alignment,
+    pub index_size: usize
+}
+
+impl PointerLayout {
+    pub fn new(address_space: usize, size: usize, abi: usize, preferred: usize, index_size: usize) -> Self {
+        PointerLayout { address_space, size, alignment: Alignment::new(abi, preferred), index_size }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum FunctionPointerAlignment {
+    Independent(usize),
+    MultipleOfFunctionAlignment(usize)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataLayoutParseErrorKind {
+    UnknownSpecification,
+    MissingField,
+    IllegalNumber,
+    IllegalValue
+}
+
+/// A data layout string that could not be parsed, with the `-` separated specification that failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataLayoutParseError {
+    pub specification: String,
+    pub kind: DataLayoutParseErrorKind
+}
+
+impl Display for DataLayoutParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self.kind {
+            DataLayoutParseErrorKind::UnknownSpecification => "unknown specification",
+            DataLayoutParseErrorKind::MissingField => "missing field in specification",
+            DataLayoutParseErrorKind::IllegalNumber => "illegal number in specification",
+            DataLayoutParseErrorKind::IllegalValue => "illegal value in specification"
+        })?;
+        f.write_fmt(format_args!(" `{}`", self.specification))
+    }
+}
+
+impl std::error::Error for DataLayoutParseError {}
+
+/// A parsed `target datalayout` string.
+///
+/// Only the specifications that were set explicitly are stored, so printing a
+/// parsed layout gives back an equivalent string. The lookup methods fall back
+/// to the defaults from the LLVM language reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataLayout {
+    pub endianness: Endianness,
+    pub mangling: Option<ManglingMode>,
+    pub stack_alignment: Option<usize>,
+    pub program_address_space: Option<usize>,
+    pub global_address_space: Option<usize>,
+    pub alloca_address_space: Option<usize>,
+    pub pointers: Vec<PointerLayout>,
+    pub integers: Vec<PrimitiveLayout>,
+    pub floats: Vec<PrimitiveLayout>,
+    pub vectors: Vec<PrimitiveLayout>,
+    pub aggregate_alignment: Option<Alignment>,
+    pub function_pointer_alignment: Option<FunctionPointerAlignment>,
+    pub native_integer_widths: Vec<usize>,
+    pub non_integral_address_spaces: Vec<usize>
+}
+
+impl Default for DataLayout {
+    fn default() -> Self {
+        DataLayout {
+            endianness: Endianness::Little,
+            mangling: None,
+            stack_alignment: None,
+            program_address_space: None,
+            global_address_space: None,
+            alloca_address_space: None,
+            pointers: Vec::new(),
+            integers: Vec::new(),
+            floats: Vec::new(),
+            vectors: Vec::new(),
+            aggregate_alignment: None,
+            function_pointer_alignment: None,
+            native_integer_widths: Vec::new(),
+            non_integral_address_spaces: Vec::new()
+        }
+    }
+}
+
+const DEFAULT_POINTER: PointerLayout = PointerLayout { address_space: 0, size: 64, alignment: Alignment { abi: 64, preferred: 64 }, index_size: 64 };
+
+const DEFAULT_INTEGERS: [PrimitiveLayout; 5] = [
+    PrimitiveLayout { size: 1, alignment: Alignment { abi: 8, preferred: 8 } },
+    PrimitiveLayout { size: 8, alignment: Alignment { abi: 8, preferred: 8 } },
+    PrimitiveLayout { size: 16, alignment: Alignment { abi: 16, preferred: 16 } },
+    PrimitiveLayout { size: 32, alignment: Alignment { abi: 32, preferred: 32 } },
+    PrimitiveLayout { size: 64, alignment: Alignment { abi: 32, preferred: 64 } }
+];
+
+const DEFAULT_FLOATS: [PrimitiveLayout; 4] = [
+    PrimitiveLayout { size: 16, alignment: Alignment { abi: 16, preferred: 16 } },
+    PrimitiveLayout { size: 32, alignment: Alignment { abi: 32, preferred: 32 } },
+    PrimitiveLayout { size: 64, alignment: Alignment { abi: 64, preferred: 64 } },
+    PrimitiveLayout { size: 128, alignment: Alignment { abi: 128, preferred: 128 } }
+];
+
+const DEFAULT_VECTORS: [PrimitiveLayout; 2] = [
+    PrimitiveLayout { size: 64, alignment: Alignment { abi: 64, preferred: 64 } },
+    PrimitiveLayout { size: 128, alignment: Alignment { abi: 128, preferred: 128 } }
+];
+
+const DEFAULT_AGGREGATE: Alignment = Alignment { abi: 0, preferred: 64 };
+
+fn natural_alignment(size: usize) -> Alignment {
+    let bits = size.next_power_of_two().max(8);
+    Alignment::new(bits, bits)
+}
+
+impl DataLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn pointer_layout(&self, address_space: usize) -> PointerLayout {
+        if let Some(layout) = self.pointers.iter().find(|it| it.address_space == address_space) { return *layout; }
+        let default = self.pointers.iter().find(|it| it.address_space == 0).copied().unwrap_or(DEFAULT_POINTER);
+        PointerLayout { address_space, ..default }
+    }
+    pub fn pointer_size(&self, address_space: usize) -> usize {
+        self.pointer_layout(address_space).size
+    }
+    /// Follows LLVM: an exact match wins, then the next wider integer entry, then the widest one.
+    pub fn integer_alignment(&self, size: usize) -> Alignment {
+        let mut entries = self.integers.clone();
+        for default in DEFAULT_INTEGERS {
+            if !entries.iter().any(|it| it.size == default.size) { entries.push(default); }
+        }
+        entries.iter().find(|it| it.size == size)
+            .or_else(|| entries.iter().filter(|it| it.size > size).min_by_key(|it| it.size))
+            .or_else(|| entries.iter().max_by_key(|it| it.size))
+            .map(|it| it.alignment)
+            .unwrap_or_else(|| natural_alignment(size))
+    }
+    pub fn float_alignment(&self, size: usize) -> Alignment {
+        self.floats.iter().chain(DEFAULT_FLOATS.iter())
+            .find(|it| it.size == size)
+            .map(|it| it.alignment)
+            .unwrap_or_else(|| natural_alignment(size))
+    }
+    pub fn vector_alignment(&self, size: usize) -> Alignment {
+        self.vectors.iter().chain(DEFAULT_VECTORS.iter())
+            .find(|it| it.size == size)
+            .map(|it| it.alignment)
+            .unwrap_or_else(|| natural_alignment(size))
+    }
+    pub fn aggregate_alignment(&self) -> Alignment {
+        self.aggregate_alignment.unwrap_or(DEFAULT_AGGREGATE)
+    }
+    pub fn is_big_endian(&self) -> bool {
+        self.endianness == Endianness::Big
+    }
+    pub fn is_legal_integer(&self, size: usize) -> bool {
+        self.native_integer_widths.contains(&size)
+    }
+}
+
+impl IRElement for DataLayout {}
+
+impl Display for DataLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn alignment(f: &mut std::fmt::Formatter<'_>, alignment: &Alignment) -> std::fmt::Result {
+            f.write_fmt(format_args!("{}", alignment.abi))?;
+            if alignment.preferred != alignment.abi { f.write_fmt(format_args!(":{}", alignment.preferred))?; }
+            Ok(())
+        }
+        self.endianness.fmt(f)?;
+        if let Some(mangling) = &self.mangling { f.write_fmt(format_args!("-m:{}", mangling))?; }
+        if let Some(address_space) = self.program_address_space { f.write_fmt(format_args!("-P{}", address_space))?; }
+        if let Some(address_space) = self.alloca_address_space { f.write_fmt(format_args!("-A{}", address_space))?; }
+        if let Some(address_space) = self.global_address_space { f.write_fmt(format_args!("-G{}", address_space))?; }
+        for pointer in &self.pointers {
+            f.write_str("-p")?;
+            if pointer.address_space != 0 { f.write_fmt(format_args!("{}", pointer.address_space))?; }
+            f.write_fmt(format_args!(":{}:", pointer.size))?;
+            if pointer.index_size != pointer.size {
+                f.write_fmt(format_args!("{}:{}:{}", pointer.alignment.abi, pointer.alignment.preferred, pointer.index_size))?;
+            } else {
+                alignment(f, &pointer.alignment)?;
+            }
+        }
+        for (prefix, entries) in [("i", &self.integers), ("f", &self.floats), ("v", &self.vectors)] {
+            for entry in entries {
+                f.write_fmt(format_args!("-{}{}:", prefix, entry.size))?;
+                alignment(f, &entry.alignment)?;
+            }
+        }
+        if let Some(aggregate) = &self.aggregate_alignment {
+            f.write_str("-a:")?;
+            alignment(f, aggregate)?;
+        }
+        match self.function_pointer_alignment {
+            Some(FunctionPointerAlignment::Independent(abi)) => f.write_fmt(format_args!("-Fi{}", abi))?,
+            Some(FunctionPointerAlignment::MultipleOfFunctionAlignment(abi)) => f.write_fmt(format_args!("-Fn{}", abi))?,
+            None => {}
+        }
+        if !self.native_integer_widths.is_empty() {
+            f.write_str("-n")?;
+            for (i, width) in self.native_integer_widths.iter().enumerate() {
+                if i != 0 { f.write_str(":")?; }
+                f.write_fmt(format_args!("{}", width))?;
+            }
+        }
+        if !self.non_integral_address_spaces.is_empty() {
+            f.write_str("-ni")?;
+            for address_space in &self.non_integral_address_spaces {
+                f.write_fmt(format_args!(":{}", address_space))?;
+            }
+        }
+        if let Some(stack_alignment) = self.stack_alignment { f.write_fmt(format_args!("-S{}", stack_alignment))?; }
+        Ok(())
+    }
+}
+
+impl DataLayout {
+    fn parse_number(string: &str) -> Result<usize, DataLayoutParseErrorKind> {
+        if string.is_empty() { return Err(DataLayoutParseErrorKind::MissingField); }
+        string.parse().map_err(|_| DataLayoutParseErrorKind::IllegalNumber)
+    }
+    fn parse_alignment(fields: &[&str]) -> Result<Alignment, DataLayoutParseErrorKind> {
+        let abi = Self::parse_number(fields.first().ok_or(DataLayoutParseErrorKind::MissingField)?)?;
+        let preferred = match fields.get(1) {
+            Some(preferred) => Self::parse_number(preferred)?,
+            None => abi
+        };
+        if abi % 8 != 0 || preferred % 8 != 0 || preferred < abi { return Err(DataLayoutParseErrorKind::IllegalValue); }
+        Ok(Alignment::new(abi, preferred))
+    }
+    fn parse_primitive(specification: &str) -> Result<PrimitiveLayout, DataLayoutParseErrorKind> {
+        let fields: Vec<&str> = specification.split(':').collect();
+        let size = Self::parse_number(fields[0])?;
+        if size == 0 { return Err(DataLayoutParseErrorKind::IllegalValue); }
+        Ok(PrimitiveLayout { size, alignment: Self::parse_alignment(&fields[1..])? })
+    }
+    fn parse_specification(&mut self, specification: &str) -> Result<(), DataLayoutParseErrorKind> {
+        let mut chars = specification.chars();
+        let kind = chars.next().ok_or(DataLayoutParseErrorKind::MissingField)?;
+        let rest = chars.as_str();
+        match kind {
+            'e' if rest.is_empty() => self.endianness = Endianness::Little,
+            'E' if rest.is_empty() => self.endianness = Endianness::Big,
+            'S' => {
+                let stack_alignment = Self::parse_number(rest)?;
+                if stack_alignment % 8 != 0 { return Err(DataLayoutParseErrorKind::IllegalValue); }
+                self.stack_alignment = Some(stack_alignment);
+            }
+            'P' => self.program_address_space = Some(Self::parse_number(rest)?),
+            'G' => self.global_address_space = Some(Self::parse_number(rest)?),
+            'A' => self.alloca_address_space = Some(Self::parse_number(rest)?),
+            'm' => {
+                self.mangling = Some(match rest {
+                    ":e" => ManglingMode::Elf,
+                    ":m" => ManglingMode::Mips,
+                    ":o" => ManglingMode::MachO,
+                    ":x" => ManglingMode::WindowsX86Coff,
+                    ":w" => ManglingMode::WindowsCoff,
+                    ":a" => ManglingMode::XCoff,
+                    ":l" => ManglingMode::Goff,
+                    _ => return Err(DataLayoutParseErrorKind::IllegalValue)
+                })
+            }
+            'n' if rest.starts_with('i') => {
+                let mut fields = rest[1..].split(':');
+                if fields.next() != Some("") { return Err(DataLayoutParseErrorKind::UnknownSpecification); }
+                for address_space in fields {
+                    let address_space = Self::parse_number(address_space)?;
+                    if address_space == 0 { return Err(DataLayoutParseErrorKind::IllegalValue); }
+                    self.non_integral_address_spaces.push(address_space);
+                }
+            }
+            'n' => {
+                self.native_integer_widths = rest.split(':').map(Self::parse_number).collect::<Result<_, _>>()?;
+            }
+            'p' => {
+                let fields: Vec<&str> = rest.split(':').collect();
+                let address_space = if fields[0].is_empty() { 0 } else { Self::parse_number(fields[0])? };
+                let size = Self::parse_number(fields.get(1).ok_or(DataLayoutParseErrorKind::MissingField)?)?;
+                let alignment = Self::parse_alignment(&fields[2..])?;
+                let index_size = match fields.get(4) {
+                    Some(index_size) => Self::parse_number(index_size)?,
+                    None => size
+                };
+                if size == 0 || index_size > size { return Err(DataLayoutParseErrorKind::IllegalValue); }
+                let pointer = PointerLayout { address_space, size, alignment, index_size };
+                match self.pointers.iter_mut().find(|it| it.address_space == address_space) {
+                    Some(existing) => *existing = pointer,
+                    None => self.pointers.push(pointer)
+                }
+            }
+            'i' => Self::set_primitive(&mut self.integers, Self::parse_primitive(rest)?),
+            'f' => Self::set_primitive(&mut self.floats, Self::parse_primitive(rest)?),
+            'v' => Self::set_primitive(&mut self.vectors, Self::parse_primitive(rest)?),
+            'a' => {
+                let fields: Vec<&str> = rest.split(':').collect();
+                if !fields[0].is_empty() && fields[0] != "0" { return Err(DataLayoutParseErrorKind::IllegalValue); }
+                self.aggregate_alignment = Some(Self::parse_alignment(&fields[1..])?);
+            }
+            'F' => {
+                let mut chars = rest.chars();
+                let kind = chars.next().ok_or(DataLayoutParseErrorKind::MissingField)?;
+                let abi = Self::parse_number(chars.as_str())?;
+                self.function_pointer_alignment = Some(match kind {
+                    'i' => FunctionPointerAlignment::Independent(abi),
+                    'n' => FunctionPointerAlignment::MultipleOfFunctionAlignment(abi),
+                    _ => return Err(DataLayoutParseErrorKind::IllegalValue)
+                });
+            }
+            _ => return Err(DataLayoutParseErrorKind::UnknownSpecification)
+        }
+        Ok(())
+    }
+    fn set_primitive(entries: &mut Vec<PrimitiveLayout>, entry: PrimitiveLayout) {
+        match entries.iter_mut().find(|it| it.size == entry.size) {
+            Some(existing) => *existing = entry,
+            None => entries.push(entry)
+        }
+    }
+}
+
+impl FromStr for DataLayout {
+    type Err = DataLayoutParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut layout = DataLayout::default();
+        if s.is_empty() { return Ok(layout); }
+        for specification in s.split('-') {
+            layout.parse_specification(specification).map_err(|kind| DataLayoutParseError { specification: specification.to_string(), kind })?;
+        }
+        Ok(layout)
+    }
+}
+
+/// Byte offsets of the fields of a struct type, together with its size and alignment in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructLayout {
+    pub size: usize,
+    pub alignment: usize,
+    pub field_offsets: Vec<usize>
+}
+
+impl StructLayout {
+    /// The index of the field containing the byte at `offset`.
+    pub fn field_containing_offset(&self, offset: usize) -> Option<usize> {
+        if offset >= self.size { return None; }
+        self.field_offsets.iter().rposition(|it| *it <= offset)
+    }
+}
+
+fn align_to(value: usize, alignment: usize) -> usize {
+    if alignment <= 1 { return value; }
+    value.div_ceil(alignment) * alignment
+}
+
+/// Size and alignment queries. Sizes of scalable vectors are their minimum size,
+/// i.e. the size for `vscale = 1`. All queries return `None` for unsized types, including
+/// named structs that contain themselves other than through a pointer.
+impl DataLayout {
+    pub fn type_size_in_bits(&self, _type: &Type) -> Option<usize> {
+        if !_type.is_sized() { return None; }
+        self.size_in_bits(_type)
+    }
+    /// The maximum number of bytes a store of `_type` may overwrite.
+    pub fn type_store_size(&self, _type: &Type) -> Option<usize> {
+        if !_type.is_sized() { return None; }
+        self.store_size(_type)
+    }
+    /// The offset in bytes between successive objects of `_type`, including padding.
+    pub fn type_alloc_size(&self, _type: &Type) -> Option<usize> {
+        if !_type.is_sized() { return None; }
+        self.alloc_size(_type)
+    }
+    pub fn abi_alignment(&self, _type: &Type) -> Option<usize> {
+        if !_type.is_sized() { return None; }
+        self.alignment(_type, true)
+    }
+    pub fn preferred_alignment(&self, _type: &Type) -> Option<usize> {
+        if !_type.is_sized() { return None; }
+        self.alignment(_type, false)
+    }
+    pub fn struct_layout(&self, _type: &aggregate::StructType) -> Option<StructLayout> {
+        if !_type.fields().iter().all(|it| it.is_sized()) { return None; }
+        self.layout(_type)
+    }
+    pub fn field_offset(&self, _type: &aggregate::StructType, index: usize) -> Option<usize> {
+        self.struct_layout(_type)?.field_offsets.get(index).copied()
+    }
+}
+
+// The queries below recurse into the parts of a type, so they are only called for sized types,
+// which can't contain themselves.
+impl DataLayout {
+    fn size_in_bits(&self, _type: &Type) -> Option<usize> {
+        match _type {
+            Type::SingleValue(_type) => Some(match _type {
+                single_value::Type::Integer(integer) => integer.0,
+                single_value::Type::Float(float) => float.bit_width(),
+                single_value::Type::X86AMX(_) => 8192,
+                single_value::Type::X86MMX(_) => 64,
+                single_value::Type::Pointer(pointer) => self.pointer_size(pointer.address_space),
+                single_value::Type::Vector(vector) => self.size_in_bits(vector._type.get())? * vector.factor
+            }),
+            Type::Aggregate(aggregate::Type::Array(array)) => Some(self.alloc_size(array._type.get())? * array.size * 8),
+            Type::Aggregate(aggregate::Type::Struct(_type)) => Some(self.layout(_type)?.size * 8),
+            Type::Aggregate(aggregate::Type::NamedStruct(_type)) => Some(self.layout(_type.body()?)?.size * 8),
+            Type::Label(_) | Type::Token(_) | Type::Metadata(_) => None
+        }
+    }
+    fn store_size(&self, _type: &Type) -> Option<usize> {
+        Some(self.size_in_bits(_type)?.div_ceil(8))
+    }
+    fn alloc_size(&self, _type: &Type) -> Option<usize> {
+        Some(align_to(self.store_size(_type)?, self.alignment(_type, true)?))
+    }
+    fn alignment(&self, _type: &Type, abi: bool) -> Option<usize> {
+        let select = |alignment: Alignment| if abi { alignment.abi } else { alignment.preferred };
+        let bits = match _type {
+            Type::SingleValue(single_value::Type::Integer(integer)) => select(self.integer_alignment(integer.0)),
+            Type::SingleValue(single_value::Type::Float(float)) => select(self.float_alignment(float.bit_width())),
+            Type::SingleValue(single_value::Type::Pointer(pointer)) => select(self.pointer_layout(pointer.address_space).alignment),
+            Type::SingleValue(single_value::Type::X86AMX(_)) | Type::SingleValue(single_value::Type::X86MMX(_)) | Type::SingleValue(single_value::Type::Vector(_)) =>
+                select(self.vector_alignment(self.size_in_bits(_type)?)),
+            Type::Aggregate(aggregate::Type::Array(array)) => return self.alignment(array._type.get(), abi),
+            Type::Aggregate(aggregate::Type::Struct(_type)) => return self.struct_alignment(_type, abi),
+            Type::Aggregate(aggregate::Type::NamedStruct(_type)) => return self.struct_alignment(_type.body()?, abi),
+            Type::Label(_) | Type::Token(_) | Type::Metadata(_) => return None
+        };
+        Some((bits / 8).max(1))
+    }
+    fn struct_alignment(&self, _type: &aggregate::StructType, abi: bool) -> Option<usize> {
+        if _type.packed && abi { return Some(1); }
+        let aggregate = self.aggregate_alignment();
+        let aggregate = if abi { aggregate.abi } else { aggregate.preferred };
+        Some(self.layout(_type)?.alignment.max(aggregate / 8))
+    }
+    fn layout(&self, _type: &aggregate::StructType) -> Option<StructLayout> {
+        let mut size = 0;
+        let mut alignment = 1;
+        let mut field_offsets = Vec::with_capacity(_type.types.len());
+        for field in &_type.types {
+            let field_alignment = if _type.packed { 1 } else { self.alignment(field.get(), true)? };
+            size = align_to(size, field_alignment);
+            alignment = alignment.max(field_alignment);
+            field_offsets.push(size);
+            size += self.alloc_size(field.get())?;
+        }
+        Some(StructLayout { size: align_to(size, alignment), alignment, field_offsets })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::target_triple::TargetTriple;
+    use crate::types::first_class::aggregate::{ArrayType, NamedStructType, StructType};
+    use crate::types::first_class::single_value::{FloatType, IntegerType, PointerType, VectorType};
+
+    fn x86_64() -> DataLayout {
+        TargetTriple::X86_64_LINUX_GNU.data_layout().unwrap()
+    }
+
+    #[test]
+    fn primitive_sizes() {
+        let layout = x86_64();
+        let i1: Type = IntegerType(1).into();
+        assert_eq!((layout.type_size_in_bits(&i1), layout.type_store_size(&i1), layout.type_alloc_size(&i1)), (Some(1), Some(1), Some(1)));
+        let fp80: Type = FloatType::X86FP80.into();
+        assert_eq!((layout.type_size_in_bits(&fp80), layout.type_store_size(&fp80), layout.type_alloc_size(&fp80)), (Some(80), Some(10), Some(16)));
+        let pointer: Type = PointerType::new(IntegerType(8)).into();
+        assert_eq!((layout.type_alloc_size(&pointer), layout.abi_alignment(&pointer)), (Some(8), Some(8)));
+        let pointer: Type = PointerType::new_with_address_space(IntegerType(8), 270).into();
+        assert_eq!((layout.type_alloc_size(&pointer), layout.abi_alignment(&pointer)), (Some(4), Some(4)));
+        let vector: Type = VectorType::new(4, IntegerType(32)).unwrap().into();
+        assert_eq!((layout.type_size_in_bits(&vector), layout.abi_alignment(&vector)), (Some(128), Some(16)));
+        let array: Type = ArrayType::new(3, IntegerType(16)).unwrap().into();
+        assert_eq!((layout.type_alloc_size(&array), layout.abi_alignment(&array)), (Some(6), Some(2)));
+    }
+
+    #[test]
+    fn struct_layouts() {
+        let layout = x86_64();
+        let padded = StructType::new(vec![IntegerType(8).into(), IntegerType(32).into(), IntegerType(8).into()]).unwrap();
+        let expected = StructLayout { size: 12, alignment: 4, field_offsets: vec![0, 4, 8] };
+        assert_eq!(layout.struct_layout(&padded), Some(expected.clone()));
+        assert_eq!(layout.field_offset(&padded, 2), Some(8));
+        assert_eq!((expected.field_containing_offset(5), expected.field_containing_offset(12)), (Some(1), None));
+        let packed = StructType::new_packed(vec![IntegerType(8).into(), IntegerType(32).into()]).unwrap();
+        assert_eq!(layout.struct_layout(&packed), Some(StructLayout { size: 5, alignment: 1, field_offsets: vec![0, 1] }));
+        // `i64` is only 4-byte aligned on 32-bit x86.
+        let i386 = TargetTriple::new("i686-unknown-linux-gnu").data_layout().unwrap();
+        let wide = StructType::new(vec![IntegerType(8).into(), IntegerType(64).into()]).unwrap();
+        assert_eq!(i386.struct_layout(&wide).map(|it| (it.size, it.field_offsets)), Some((12, vec![0, 4])));
+        assert_eq!(layout.struct_layout(&wide).map(|it| (it.size, it.field_offsets)), Some((16, vec![0, 8])));
+    }
+
+    #[test]
+    fn every_preset_parses() {
+        let others = ["x86_64-apple-darwin", "i386-apple-ios", "i686-pc-windows-msvc", "aarch64-apple-ios", "wasm64-unknown-unknown"].map(TargetTriple::new);
+        let presets = [
+            TargetTriple::X86_64_LINUX_GNU, TargetTriple::X86_64_APPLE_MACOS, TargetTriple::X86_64_WINDOWS_MSVC, TargetTriple::I686_LINUX_GNU,
+            TargetTriple::AARCH64_LINUX_GNU, TargetTriple::AARCH64_APPLE_MACOS, TargetTriple::AARCH64_WINDOWS_MSVC,
+            TargetTriple::WASM32_UNKNOWN_UNKNOWN, TargetTriple::WASM32_WASI
+        ];
+        for triple in presets.into_iter().chain(others) {
+            let layout = triple.data_layout().unwrap();
+            assert_eq!(layout.to_string().parse::<DataLayout>().as_ref(), Ok(&layout), "{}", triple);
+        }
+        assert_eq!(TargetTriple::new("riscv64-unknown-linux-gnu").data_layout(), None);
+    }
+
+    #[test]
+    fn parsing_round_trips_through_display() {
+        let string = "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128";
+        assert_eq!(string.parse::<DataLayout>().unwrap().to_string(), string);
+        let string = "E-m:m-P1-A5-G1-p:64:64:64:32-i8:8:32-f64:32:64-v128:128-a:0:64-Fn32-n32:64-ni:1:2-S64";
+        assert_eq!(string.parse::<DataLayout>().unwrap().to_string(), string);
+        // Specifications come out in a fixed order.
+        let layout: DataLayout = "S128-e-i64:64".parse().unwrap();
+        assert_eq!(layout.to_string(), "e-i64:64-S128");
+        assert_eq!("".parse::<DataLayout>().unwrap().to_string(), "e");
+    }
+
+    #[test]
+    fn specifications_are_parsed() {
+        let layout: DataLayout = "e-m:o-p270:32:32-n8:16:32-S128".parse().unwrap();
+        assert_eq!(layout.mangling, Some(ManglingMode::MachO));
+        assert_eq!(layout.pointers, vec![PointerLayout::new(270, 32, 32, 32, 32)]);
+        assert_eq!(layout.native_integer_widths, vec![8, 16, 32]);
+        assert_eq!(layout.stack_alignment, Some(128));
+        let layout: DataLayout = "p:64:64:64:32-i32:32:64".parse().unwrap();
+        assert_eq!(layout.pointers, vec![PointerLayout::new(0, 64, 64, 64, 32)]);
+        assert_eq!(layout.integers, vec![PrimitiveLayout::new(32, 32, 64)]);
+    }
+
+    #[test]
+    fn invalid_layouts_are_rejected() {
+        let error = |string: &str| string.parse::<DataLayout>().map_err(|it| (it.specification, it.kind)).err();
+        assert_eq!(error("e-x"), Some(("x".to_string(), DataLayoutParseErrorKind::UnknownSpecification)));
+        assert_eq!(error("e-"), Some(("".to_string(), DataLayoutParseErrorKind::MissingField)));
+        assert_eq!(error("S"), Some(("S".to_string(), DataLayoutParseErrorKind::MissingField)));
+        assert_eq!(error("S1x"), Some(("S1x".to_string(), DataLayoutParseErrorKind::IllegalNumber)));
+        assert_eq!(error("S12"), Some(("S12".to_string(), DataLayoutParseErrorKind::IllegalValue)));
+        assert_eq!(error("m:q"), Some(("m:q".to_string(), DataLayoutParseErrorKind::IllegalValue)));
+        assert_eq!(error("p:0:8"), Some(("p:0:8".to_string(), DataLayoutParseErrorKind::IllegalValue)));
+        assert_eq!(error("p:32:32:32:64"), Some(("p:32:32:32:64".to_string(), DataLayoutParseErrorKind::IllegalValue)));
+        assert_eq!(error("i32:12"), Some(("i32:12".to_string(), DataLayoutParseErrorKind::IllegalValue)));
+        assert_eq!(error("i32:64:32"), Some(("i32:64:32".to_string(), DataLayoutParseErrorKind::IllegalValue)));
+        assert_eq!(error("ni:0"), Some(("ni:0".to_string(), DataLayoutParseErrorKind::IllegalValue)));
+    }
+
+    #[test]
+    fn unsized_types_have_no_layout() {
+        let layout = x86_64();
+        assert_eq!(layout.type_size_in_bits(&crate::types::first_class::LabelType.into()), None);
+        let opaque = NamedStructType::new_opaque("opaque").unwrap();
+        assert_eq!(layout.type_alloc_size(&opaque.clone().into()), None);
+        assert_eq!(layout.struct_layout(&StructType::new(vec![IntegerType(8).into(), opaque.into()]).unwrap()), None);
+        let recursive = NamedStructType::new_opaque("recursive").unwrap();
+        recursive.set_body(StructType::new(vec![IntegerType(32).into(), recursive.clone().into()]).unwrap()).unwrap();
+        let recursive: Type = recursive.into();
+        assert_eq!((layout.type_size_in_bits(&recursive), layout.abi_alignment(&recursive), layout.preferred_alignment(&recursive)), (None, None, None));
+        // Through a pointer, a struct may contain itself.
+        let list = NamedStructType::new_opaque("list").unwrap();
+        list.set_body(StructType::new(vec![PointerType::new(list.clone()).into(), IntegerType(32).into()]).unwrap()).unwrap();
+        assert_eq!(layout.type_alloc_size(&list.into()), Some(16));
+    }
+}
@@ -2,6 +2,7 @@ use std::fmt::Display;
 
 use crate::reference::Value;
 
+#[derive(Debug)]
 pub enum Instruction<'s> {
     ExtractValue(ExtractValueInstruction<'s>),
     InsertValue(InsertValueInstruction<'s>)
@@ -16,6 +17,7 @@ impl Display for Instruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct ExtractValueInstruction<'s> {
     parent: Value<'s>,
     indecies: Vec<Value<'s>>
@@ -34,6 +36,7 @@ impl Display for ExtractValueInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct InsertValueInstruction<'s> {
     parent: Value<'s>,
     element: Value<'s>,
@@ -2,6 +2,7 @@ use std::fmt::Display;
 
 use crate::reference::Value;
 
+#[derive(Debug)]
 pub enum Instruction<'s> {
     Add(AddInstruction<'s>),
     FloatAdd(FloatAddInstruction<'s>),
@@ -36,6 +37,7 @@ impl Display for Instruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct AddInstruction<'s> {
     nuw: bool,
     nsw: bool,
@@ -56,6 +58,7 @@ impl Display for AddInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct FloatAddInstruction<'s> {
     //fast_math_flags: Vec<FastMathFlag>,
     lhs: Value<'s>,
@@ -71,6 +74,7 @@ impl Display for FloatAddInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct SubInstruction<'s> {
     nuw: bool,
     nsw: bool,
@@ -91,6 +95,7 @@ impl Display for SubInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct FloatSubInstruction<'s> {
     //fast_math_flags: Vec<FastMathFlag>,
     lhs: Value<'s>,
@@ -106,6 +111,7 @@ impl Display for FloatSubInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct MultiplyInstruction<'s> {
     nuw: bool,
     nsw: bool,
@@ -126,6 +132,7 @@ impl Display for MultiplyInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct FloatMultiplyInstruction<'s> {
     //fast_math_flags: Vec<FastMathFlag>,
     lhs: Value<'s>,
@@ -141,6 +148,7 @@ impl Display for FloatMultiplyInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct UnsignedDivisionInstruction<'s> {
     exact: bool,
     lhs: Value<'s>,
@@ -159,6 +167,7 @@ impl Display for UnsignedDivisionInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct SignedDivisionInstruction<'s> {
     exact: bool,
     lhs: Value<'s>,
@@ -177,6 +186,7 @@ impl Display for SignedDivisionInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct FloatDivisionInstruction<'s> {
     //fast_math_flags: Vec<FastMathFlag>,
     lhs: Value<'s>,
@@ -192,6 +202,7 @@ impl Display for FloatDivisionInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct UnsignedRemainderInstruction<'s> {
     lhs: Value<'s>,
     rhs: Value<'s>,
@@ -206,6 +217,7 @@ impl Display for UnsignedRemainderInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct SignedRemainderInstruction<'s> {
     lhs: Value<'s>,
     rhs: Value<'s>,
@@ -220,6 +232,7 @@ impl Display for SignedRemainderInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct FloatRemainderInstruction<'s> {
     //fast_math_flags: Vec<FastMathFlag>,
     lhs: Value<'s>,
@@ -2,6 +2,7 @@ use std::fmt::Display;
 
 use crate::reference::Value;
 
+#[derive(Debug)]
 pub enum Instruction<'s> {
     ShiftLeft(ShiftLeftInstruction<'s>),
     LogicalShiftLeft(LogicalShiftLeftInstruction<'s>),
@@ -24,6 +25,7 @@ impl Display for Instruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct ShiftLeftInstruction<'s> {
     nuw: bool,
     nsw: bool,
@@ -44,6 +46,7 @@ impl Display for ShiftLeftInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct LogicalShiftLeftInstruction<'s> {
     exact: bool,
     lhs: Value<'s>,
@@ -62,6 +65,7 @@ impl Display for LogicalShiftLeftInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct ArithmeticShiftLeftInstruction<'s> {
     exact: bool,
     lhs: Value<'s>,
@@ -80,6 +84,7 @@ impl Display for ArithmeticShiftLeftInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct AndInstruction<'s> {
     lhs: Value<'s>,
     rhs: Value<'s>
@@ -94,20 +99,37 @@ impl Display for AndInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct OrInstruction<'s> {
+    disjoint: bool,
     lhs: Value<'s>,
     rhs: Value<'s>
 }
 
+impl<'s> OrInstruction<'s> {
+    pub fn new(lhs: Value<'s>, rhs: Value<'s>) -> Self {
+        OrInstruction { disjoint: false, lhs, rhs }
+    }
+    /// Promises that no bit is set in both operands (`disjoint`), making the result poison otherwise.
+    /// Dropped for versions before LLVM 18.
+    pub fn with_disjoint(mut self) -> Self {
+        self.disjoint = true;
+        self
+    }
+}
+
 impl Display for OrInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("or {}, {}", self.lhs, match &self.rhs {
+        f.write_str("or ")?;
+        if self.disjoint && crate::printer::prints_optional(18) { f.write_str("disjoint ")?; }
+        f.write_fmt(format_args!("{}, {}", self.lhs, match &self.rhs {
             Value::Constant(it) => it as &dyn Display,
             Value::Reference(it) => it as &dyn Display
         }))
     }
 }
 
+#[derive(Debug)]
 pub struct XOrInstruction<'s> {
     lhs: Value<'s>,
     rhs: Value<'s>
@@ -0,0 +1,63 @@
+use std::fmt::Display;
+
+use crate::{constant::expression::{CastOperator, ConstantExpressionError}, reference::Value, types::{interner::TypeId, ReturnType}};
+
+#[derive(Debug)]
+pub enum Instruction<'s> {
+    Cast(CastInstruction<'s>),
+}
+
+impl Display for Instruction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (match self {
+            Self::Cast(it) => it as &dyn Display,
+        }).fmt(f)
+    }
+}
+
+/// `zext i8 %x to i32`, `ptrtoint i8* %p to i64`, ...
+#[derive(Debug)]
+pub struct CastInstruction<'s> {
+    operator: CastOperator,
+    non_negative: bool,
+    value: Value<'s>,
+    _type: TypeId
+}
+
+impl<'s> CastInstruction<'s> {
+    /// Fails for the same casts as `CastConstant::new`.
+    pub fn new(operator: CastOperator, value: Value<'s>, _type: impl Into<TypeId>) -> Result<Self, ConstantExpressionError> {
+        let _type = _type.into();
+        let from = &*value.return_type();
+        if !crate::constant::expression::is_valid_cast(operator, from, _type.get()) {
+            return Err(ConstantExpressionError::InvalidCast { operator: operator.name(), from: from.clone(), to: _type.get().clone() });
+        }
+        Ok(CastInstruction { operator, non_negative: false, value, _type })
+    }
+    /// Promises that the operand is non-negative (`nneg`), making the result poison otherwise.
+    /// Only `zext` (LLVM 18 on) and `uitofp` (LLVM 19 on) take the flag; it is dropped
+    /// for other casts and for versions that don't know it.
+    pub fn with_non_negative(mut self) -> Self {
+        self.non_negative = true;
+        self
+    }
+}
+
+impl Display for CastInstruction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} ", self.operator))?;
+        let introduced = match self.operator {
+            CastOperator::ZeroExtend => Some(18),
+            CastOperator::UnsignedToFloat => Some(19),
+            _ => None
+        };
+        if self.non_negative && introduced.is_some_and(crate::printer::prints_optional) { f.write_str("nneg ")?; }
+        f.write_fmt(format_args!("{} to {}", self.value, self._type))
+    }
+}
+
+impl ReturnType for CastInstruction<'_> {
+    fn return_type(&self) -> TypeId {
+        self._type
+    }
+}
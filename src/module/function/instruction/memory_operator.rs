@@ -1,37 +1,77 @@
+use std::borrow::Cow;
 use std::fmt::Display;
 
-use crate::{types::{first_class::Type, ReturnType}, reference::Value};
+use crate::{types::{first_class::{self, single_value::PointerType}, interner::TypeId, ReturnType}, reference::Value, identifier::QuotedString};
 
+#[derive(Debug)]
 pub enum Instruction<'s> {
     AllocA(AllocAInstruction<'s>),
+    Load(LoadInstruction<'s>),
+    Store(StoreInstruction<'s>),
+    Fence(FenceInstruction<'s>),
+    CompareExchange(CompareExchangeInstruction<'s>),
+    AtomicReadModifyWrite(AtomicReadModifyWriteInstruction<'s>),
+    GetElementPointer(GetElementPointerInstruction<'s>),
 }
 
 impl Display for Instruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         (match self {
             Instruction::AllocA(it) => it as &dyn Display,
+            Instruction::Load(it) => it as &dyn Display,
+            Instruction::Store(it) => it as &dyn Display,
+            Instruction::Fence(it) => it as &dyn Display,
+            Instruction::CompareExchange(it) => it as &dyn Display,
+            Instruction::AtomicReadModifyWrite(it) => it as &dyn Display,
+            Instruction::GetElementPointer(it) => it as &dyn Display,
         }).fmt(f)
     }
 }
 
+#[derive(Debug)]
 pub struct AllocAInstruction<'s> {
     //inalloca,
-    _type: Type,
+    _type: TypeId,
     num_elements: Option<Value<'s>>,
     align: Option<usize>,
     addrspace: Option<usize>
 }
 
+impl<'s> AllocAInstruction<'s> {
+    /// Allocates one `_type` on the stack, which has to be sized.
+    pub fn new(_type: impl Into<TypeId>) -> Result<Self, MemoryAccessError> {
+        let _type = _type.into();
+        if !_type.is_sized() { return Err(MemoryAccessError::InvalidType(_type.get().clone())); }
+        return Ok(AllocAInstruction { _type, num_elements: None, align: None, addrspace: None });
+    }
+    /// Allocates `num_elements` of the type instead of one. The count has to be an integer.
+    pub fn with_num_elements(mut self, num_elements: Value<'s>) -> Result<Self, MemoryAccessError> {
+        let _type = num_elements.return_type();
+        if !_type.is_integer() { return Err(MemoryAccessError::InvalidElementCount(_type.get().clone())); }
+        self.num_elements = Some(num_elements);
+        return Ok(self);
+    }
+    pub fn with_align(mut self, align: usize) -> Self {
+        self.align = Some(align);
+        self
+    }
+    pub fn with_address_space(mut self, address_space: usize) -> Self {
+        self.addrspace = Some(address_space);
+        self
+    }
+}
+
 impl Display for AllocAInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("alloca {}", self._type))?;
         if let Some(num_elements) = &self.num_elements { f.write_fmt(format_args!(", {}", num_elements))?; }
-        if let Some(align) = self.align { f.write_fmt(format_args!(", {}", align))?; }
-        if let Some(addrspace) = self.addrspace { f.write_fmt(format_args!(", {}", addrspace))?; }
+        if let Some(align) = self.align { f.write_fmt(format_args!(", align {}", align))?; }
+        if let Some(addrspace) = self.addrspace { f.write_fmt(format_args!(", addrspace({})", addrspace))?; }
         Ok(())
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AtomicMemoryOrderingConstraint {
     Unordered,
     Monotonic,
@@ -49,64 +89,112 @@ impl Display for AtomicMemoryOrderingConstraint {
             Self::Acquire => f.write_str("acquire"),
             Self::Release => f.write_str("release"),
             Self::AcquireRelease => f.write_str("acq_rel"),
-            Self::SequentiallyConsistent => f.write_str("swq_cst")
+            Self::SequentiallyConsistent => f.write_str("seq_cst")
         }
     }
 }
 
-pub enum LoadInstruction<'s> {
-    Normal {
-        volatile: bool,
-        value: Value<'s>,
-        alignment: usize,
-        //nontemporal: Option<Metadata>,
-        //invariant_load: Option<Metadata>,
-        //invariant_group: Option<Metadata>,
-        //nonnull: Option<Metadata>,
-        //dereferencable: Option<Metadata>,
-        //dereferencable_or_null: Option<Metadata>,
-        //align_m: Option<Metadata>,
-        //noundef: Option<Metadata>
-    },
-    Atomic {
-        volatile: bool,
-        value: Value<'s>,
-        syncscope: Option<&'s str>,
-        ordering: AtomicMemoryOrderingConstraint,
-        alignment: usize,
-        //invariant_group: Metadata,
+#[derive(Debug)]
+pub struct LoadInstruction<'s> {
+    volatile: bool,
+    _type: TypeId,
+    pointer: Value<'s>,
+    /// The sync scope and ordering of an atomic load.
+    atomic: Option<(Option<Cow<'s, str>>, AtomicMemoryOrderingConstraint)>,
+    alignment: usize,
+    //nontemporal: Option<Metadata>,
+    //invariant_load: Option<Metadata>,
+    //invariant_group: Option<Metadata>,
+    //nonnull: Option<Metadata>,
+    //dereferencable: Option<Metadata>,
+    //dereferencable_or_null: Option<Metadata>,
+    //align_m: Option<Metadata>,
+    //noundef: Option<Metadata>
+}
+
+impl<'s> LoadInstruction<'s> {
+    /// Loads a `_type` from `pointer`, which has to point to `_type` unless it is opaque.
+    /// An `alignment` of 0 is left to the target.
+    pub fn new(volatile: bool, _type: impl Into<TypeId>, pointer: Value<'s>, alignment: usize) -> Result<Self, MemoryAccessError> {
+        let _type = _type.into();
+        check_access(_type, &pointer)?;
+        return Ok(LoadInstruction { volatile, _type, pointer, atomic: None, alignment });
+    }
+    pub fn new_atomic(volatile: bool, _type: impl Into<TypeId>, pointer: Value<'s>, syncscope: Option<Cow<'s, str>>, ordering: AtomicMemoryOrderingConstraint, alignment: usize) -> Result<Self, MemoryAccessError> {
+        let _type = _type.into();
+        check_access(_type, &pointer)?;
+        return Ok(LoadInstruction { volatile, _type, pointer, atomic: Some((syncscope, ordering)), alignment });
+    }
+}
+
+impl ReturnType for LoadInstruction<'_> {
+    fn return_type(&self) -> TypeId {
+        self._type
     }
 }
 
 impl Display for LoadInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("load ")?;
-        match self {
-            Self::Normal { volatile, value, alignment } => {
-                if *volatile { f.write_str("volatile ")?; }
-                let pointer_type = match value.return_type() {
-                    Type::SingleValue(crate::types::first_class::single_value::Type::Pointer(_type)) => _type,
-                    _ => panic!()
-                };
-                f.write_fmt(format_args!("{}, {}", pointer_type._type, value))?;
-                if *alignment != 0 { f.write_fmt(format_args!(", align {}", alignment))?; }
-            }
-            Self::Atomic { volatile, value, syncscope, ordering, alignment } => {
-                f.write_str("atomic ")?;
-                if *volatile { f.write_str("volatile ")?; }
-                let pointer_type = match value.return_type() {
-                    Type::SingleValue(crate::types::first_class::single_value::Type::Pointer(_type)) => _type,
-                    _ => panic!()
-                };
-                f.write_fmt(format_args!("{}, {}", pointer_type._type, value))?;
-                if let Some(syncscope) = syncscope { f.write_fmt(format_args!(" syncscope(\"{}\")", syncscope))?; }
-                f.write_fmt(format_args!(" {}, align {}", ordering, alignment))?;
+        if self.atomic.is_some() { f.write_str("atomic ")?; }
+        if self.volatile { f.write_str("volatile ")?; }
+        f.write_fmt(format_args!("{}, {}", self._type, self.pointer))?;
+        match &self.atomic {
+            Some((syncscope, ordering)) => {
+                if let Some(syncscope) = syncscope { f.write_fmt(format_args!(" syncscope({})", QuotedString(syncscope)))?; }
+                f.write_fmt(format_args!(" {}, align {}", ordering, self.alignment))?;
             }
+            None => if self.alignment != 0 { f.write_fmt(format_args!(", align {}", self.alignment))?; }
         }
         Ok(())
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryAccessError {
+    /// An address that isn't a pointer.
+    InvalidPointer(first_class::Type),
+    /// A value type without a size, which can't be stored in memory.
+    InvalidType(first_class::Type),
+    /// A value type that can't be used by an atomic instruction or operation.
+    InvalidAtomicType(first_class::Type),
+    /// An `alloca` element count that isn't an integer.
+    InvalidElementCount(first_class::Type),
+    /// An ordering that the instruction doesn't allow.
+    InvalidOrdering(AtomicMemoryOrderingConstraint),
+    /// A typed pointer that points to another type than the one accessed, or a value of
+    /// another type than the one compared to.
+    TypeMismatch { expected: first_class::Type, found: first_class::Type }
+}
+
+impl Display for MemoryAccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPointer(_type) => f.write_fmt(format_args!("an address of type `{}` isn't a pointer", _type)),
+            Self::InvalidType(_type) => f.write_fmt(format_args!("a `{}` can't be loaded or stored", _type)),
+            Self::InvalidAtomicType(_type) => f.write_fmt(format_args!("a `{}` can't be used here atomically", _type)),
+            Self::InvalidElementCount(_type) => f.write_fmt(format_args!("an element count of type `{}` isn't an integer", _type)),
+            Self::InvalidOrdering(ordering) => f.write_fmt(format_args!("the ordering `{}` isn't allowed here", ordering)),
+            Self::TypeMismatch { expected, found } => f.write_fmt(format_args!("the operand has type `{}`, expected `{}`", found, expected))
+        }
+    }
+}
+
+impl std::error::Error for MemoryAccessError {}
+
+/// Checks that a `_type` can be accessed through `pointer`.
+fn check_access(_type: TypeId, pointer: &Value<'_>) -> Result<(), MemoryAccessError> {
+    if !_type.is_sized() { return Err(MemoryAccessError::InvalidType(_type.get().clone())); }
+    let pointer_type = pointer.return_type();
+    if !pointer_type.is_pointer() { return Err(MemoryAccessError::InvalidPointer(pointer_type.get().clone())); }
+    if pointer_type.pointee().is_some_and(|it| it.first_class() != Some(_type.get())) {
+        let expected = PointerType::new_with_address_space(_type.get().clone(), pointer_type.address_space().unwrap_or(0));
+        return Err(MemoryAccessError::TypeMismatch { expected: expected.into(), found: pointer_type.get().clone() });
+    }
+    return Ok(());
+}
+
+#[derive(Debug)]
 pub enum StoreInstruction<'s> {
     Normal {
         volatile: bool,
@@ -120,7 +208,7 @@ pub enum StoreInstruction<'s> {
         volatile: bool,
         value: Value<'s>,
         pointer: Value<'s>,
-        syncscope: Option<&'s str>,
+        syncscope: Option<Cow<'s, str>>,
         ordering: AtomicMemoryOrderingConstraint,
         alignment: usize,
         //invariant_group: Metadata,
@@ -140,7 +228,7 @@ impl Display for StoreInstruction<'_> {
                 f.write_str("atomic ")?;
                 if *volatile { f.write_str("volatile ")?; }
                 f.write_fmt(format_args!("{}, {}", value, pointer))?;
-                if let Some(syncscope) = syncscope { f.write_fmt(format_args!(" syncscope(\"{}\")", syncscope))?; }
+                if let Some(syncscope) = syncscope { f.write_fmt(format_args!(" syncscope({})", QuotedString(syncscope)))?; }
                 f.write_fmt(format_args!(" {}, align {}", ordering, alignment))?;
             }
         }
@@ -148,44 +236,89 @@ impl Display for StoreInstruction<'_> {
     }
 }
 
-struct FenceInstruction<'s> {
-    syncscope: Option<&'s str>,
+#[derive(Debug)]
+pub struct FenceInstruction<'s> {
+    syncscope: Option<Cow<'s, str>>,
     ordering: AtomicMemoryOrderingConstraint
 }
 
+impl<'s> FenceInstruction<'s> {
+    /// Fences have to acquire, release or both.
+    pub fn new(syncscope: Option<Cow<'s, str>>, ordering: AtomicMemoryOrderingConstraint) -> Result<Self, MemoryAccessError> {
+        if matches!(ordering, AtomicMemoryOrderingConstraint::Unordered | AtomicMemoryOrderingConstraint::Monotonic) { return Err(MemoryAccessError::InvalidOrdering(ordering)); }
+        return Ok(FenceInstruction { syncscope, ordering });
+    }
+}
+
 impl Display for FenceInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("fence ")?;
-        if let Some(syncscope) = self.syncscope { f.write_fmt(format_args!("syncscope(\"{}\") ", syncscope))?; }
+        if let Some(syncscope) = &self.syncscope { f.write_fmt(format_args!("syncscope({}) ", QuotedString(syncscope)))?; }
         self.ordering.fmt(f)
     }
 }
 
-struct CompareExchangeInstruction<'s> {
+#[derive(Debug)]
+pub struct CompareExchangeInstruction<'s> {
     weak: bool,
     volatile: bool,
     pointer: Value<'s>,
     compare: Value<'s>,
     new: Value<'s>,
-    syncscope: Option<&'s str>,
+    syncscope: Option<Cow<'s, str>>,
     success_ordering: AtomicMemoryOrderingConstraint,
     failure_ordering: AtomicMemoryOrderingConstraint,
     alignment: usize,
 }
 
+impl<'s> CompareExchangeInstruction<'s> {
+    /// Stores `new` at `pointer` if the value there equals `compare`. Both have to be integers or
+    /// pointers of the same type. Neither ordering can be `unordered`, and the failure ordering
+    /// can't release.
+    pub fn new(pointer: Value<'s>, compare: Value<'s>, new: Value<'s>, success_ordering: AtomicMemoryOrderingConstraint, failure_ordering: AtomicMemoryOrderingConstraint) -> Result<Self, MemoryAccessError> {
+        let _type = compare.return_type();
+        if !_type.is_integer() && !_type.is_pointer() { return Err(MemoryAccessError::InvalidAtomicType(_type.get().clone())); }
+        let new_type = new.return_type();
+        if new_type != _type { return Err(MemoryAccessError::TypeMismatch { expected: _type.get().clone(), found: new_type.get().clone() }); }
+        check_access(_type, &pointer)?;
+        if success_ordering == AtomicMemoryOrderingConstraint::Unordered { return Err(MemoryAccessError::InvalidOrdering(success_ordering)); }
+        if matches!(failure_ordering, AtomicMemoryOrderingConstraint::Unordered | AtomicMemoryOrderingConstraint::Release | AtomicMemoryOrderingConstraint::AcquireRelease) {
+            return Err(MemoryAccessError::InvalidOrdering(failure_ordering));
+        }
+        return Ok(CompareExchangeInstruction { weak: false, volatile: false, pointer, compare, new, syncscope: None, success_ordering, failure_ordering, alignment: 0 });
+    }
+    pub fn with_weak(mut self) -> Self {
+        self.weak = true;
+        self
+    }
+    pub fn with_volatile(mut self) -> Self {
+        self.volatile = true;
+        self
+    }
+    pub fn with_syncscope(mut self, syncscope: impl Into<Cow<'s, str>>) -> Self {
+        self.syncscope = Some(syncscope.into());
+        self
+    }
+    pub fn with_align(mut self, align: usize) -> Self {
+        self.alignment = align;
+        self
+    }
+}
+
 impl Display for CompareExchangeInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("cmpxchg ")?;
-        if self.weak { f.write_str(" weak")?; }
-        if self.volatile { f.write_str(" volatile")?; }
+        if self.weak { f.write_str("weak ")?; }
+        if self.volatile { f.write_str("volatile ")?; }
         f.write_fmt(format_args!("{}, {}, {}", self.pointer, self.compare, self.new))?;
-        if let Some(syncscope) = self.syncscope { f.write_fmt(format_args!(" syncscope(\"{}\")", syncscope))?; }
+        if let Some(syncscope) = &self.syncscope { f.write_fmt(format_args!(" syncscope({})", QuotedString(syncscope)))?; }
         f.write_fmt(format_args!(" {} {}", self.success_ordering, self.failure_ordering))?;
         if self.alignment != 0 { f.write_fmt(format_args!(", align {}", self.alignment))?; }
         Ok(())
     }
 }
 
+#[derive(Debug)]
 pub enum AtomicReadModifyWriteOperation {
     Exchange,
     Add,
@@ -222,42 +355,150 @@ impl Display for AtomicReadModifyWriteOperation {
     }
 }
 
-struct AtomicReadModifyWriteInstruction<'s> {
+#[derive(Debug)]
+pub struct AtomicReadModifyWriteInstruction<'s> {
     volatile: bool,
     operation: AtomicReadModifyWriteOperation,
     pointer: Value<'s>,
     value: Value<'s>,
-    syncscope: Option<&'s str>,
+    syncscope: Option<Cow<'s, str>>,
     ordering: AtomicMemoryOrderingConstraint,
     alignment: usize
 }
 
+impl<'s> AtomicReadModifyWriteInstruction<'s> {
+    /// Applies `operation` to the value at `pointer` and `value`. `xchg` takes integers, floats and
+    /// pointers, `fadd` and `fsub` floats, and the other operations integers.
+    pub fn new(operation: AtomicReadModifyWriteOperation, pointer: Value<'s>, value: Value<'s>, ordering: AtomicMemoryOrderingConstraint) -> Result<Self, MemoryAccessError> {
+        let _type = value.return_type();
+        let valid = match operation {
+            AtomicReadModifyWriteOperation::Exchange => _type.is_integer() || _type.is_float() || _type.is_pointer(),
+            AtomicReadModifyWriteOperation::FloatAdd | AtomicReadModifyWriteOperation::FloatSubtract => _type.is_float(),
+            _ => _type.is_integer()
+        };
+        if !valid { return Err(MemoryAccessError::InvalidAtomicType(_type.get().clone())); }
+        check_access(_type, &pointer)?;
+        if ordering == AtomicMemoryOrderingConstraint::Unordered { return Err(MemoryAccessError::InvalidOrdering(ordering)); }
+        return Ok(AtomicReadModifyWriteInstruction { volatile: false, operation, pointer, value, syncscope: None, ordering, alignment: 0 });
+    }
+    pub fn with_volatile(mut self) -> Self {
+        self.volatile = true;
+        self
+    }
+    pub fn with_syncscope(mut self, syncscope: impl Into<Cow<'s, str>>) -> Self {
+        self.syncscope = Some(syncscope.into());
+        self
+    }
+    pub fn with_align(mut self, align: usize) -> Self {
+        self.alignment = align;
+        self
+    }
+}
+
 impl Display for AtomicReadModifyWriteInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("atomicrmw ")?;
         if self.volatile { f.write_str("volatile ")?; }
         f.write_fmt(format_args!("{} {}, {}", self.operation, self.pointer, self.value))?;
+        if let Some(syncscope) = &self.syncscope { f.write_fmt(format_args!(" syncscope({})", QuotedString(syncscope)))?; }
+        f.write_fmt(format_args!(" {}", self.ordering))?;
+        if self.alignment != 0 { f.write_fmt(format_args!(", align {}", self.alignment))?; }
         Ok(())
     }
 }
 
-struct GetElementPointerInstruction<'s> {
+#[derive(Debug)]
+pub struct GetElementPointerInstruction<'s> {
     inbounds: bool,
+    _type: TypeId,
     pointer: Value<'s>,
-    indecies: Vec<(bool, Value<'s>)>
+    indecies: Vec<Value<'s>>
+}
+
+impl<'s> GetElementPointerInstruction<'s> {
+    /// `_type` is the type the pointer is indexed as.
+    pub fn new(inbounds: bool, _type: impl Into<TypeId>, pointer: Value<'s>, indecies: Vec<Value<'s>>) -> Self {
+        GetElementPointerInstruction { inbounds, _type: _type.into(), pointer, indecies }
+    }
 }
 
 impl Display for GetElementPointerInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("getelementptr ")?;
-        if self.inbounds { f.write_str("inbounds")?; }
-        self.pointer.fmt(f)?;
-        for (inbound, index) in &self.indecies {
-            f.write_str(", ")?;
-            if *inbound { f.write_str("inbound")?; }
-            index.fmt(f)?;
+        if self.inbounds { f.write_str("inbounds ")?; }
+        f.write_fmt(format_args!("{}, {}", self._type, self.pointer))?;
+        for index in &self.indecies {
+            f.write_fmt(format_args!(", {}", index))?;
         }
         Ok(())
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::simple::{IntegerConstant, NullPointerConstant};
+    use crate::types::first_class::single_value::{IntegerType, PointerType};
+
+    fn int(value: i64) -> Value<'static> {
+        crate::constant::Constant::from(IntegerConstant::from_i64(IntegerType(32), value).unwrap()).into()
+    }
+
+    fn null(_type: PointerType) -> Value<'static> {
+        crate::constant::Constant::from(NullPointerConstant::new(_type)).into()
+    }
+
+    #[test]
+    fn alloca_prints_alignment_and_address_space() {
+        let alloca = AllocAInstruction::new(IntegerType(32)).unwrap().with_num_elements(int(4)).unwrap().with_align(16).with_address_space(5);
+        assert_eq!(alloca.to_string(), "alloca i32, i32 4, align 16, addrspace(5)");
+        assert_eq!(AllocAInstruction::new(IntegerType(32)).unwrap().with_align(4).to_string(), "alloca i32, align 4");
+        assert_eq!(AllocAInstruction::new(first_class::LabelType).err(), Some(MemoryAccessError::InvalidType(first_class::LabelType.into())));
+        let count = AllocAInstruction::new(IntegerType(32)).unwrap().with_num_elements(null(PointerType::new(IntegerType(8))));
+        assert_eq!(count.err(), Some(MemoryAccessError::InvalidElementCount(PointerType::new(IntegerType(8)).into())));
+    }
+
+    #[test]
+    fn cmpxchg_separates_its_flags() {
+        let cmpxchg = CompareExchangeInstruction::new(null(PointerType::new(IntegerType(32))), int(0), int(1), AtomicMemoryOrderingConstraint::AcquireRelease, AtomicMemoryOrderingConstraint::Monotonic).unwrap();
+        let cmpxchg = cmpxchg.with_weak().with_volatile().with_syncscope("agent").with_align(4);
+        assert_eq!(cmpxchg.to_string(), "cmpxchg weak volatile i32* null, i32 0, i32 1 syncscope(\"agent\") acq_rel monotonic, align 4");
+        let cmpxchg = |compare, new, failure| CompareExchangeInstruction::new(null(PointerType::new_opaque()), compare, new, AtomicMemoryOrderingConstraint::SequentiallyConsistent, failure).err();
+        assert_eq!(cmpxchg(int(0), int(1), AtomicMemoryOrderingConstraint::Release), Some(MemoryAccessError::InvalidOrdering(AtomicMemoryOrderingConstraint::Release)));
+        let long = crate::constant::Constant::from(IntegerConstant::from_i64(IntegerType(64), 1).unwrap()).into();
+        assert_eq!(cmpxchg(int(0), long, AtomicMemoryOrderingConstraint::Monotonic), Some(MemoryAccessError::TypeMismatch { expected: IntegerType(32).into(), found: IntegerType(64).into() }));
+        let float = || crate::constant::Constant::from(crate::constant::simple::FloatConstant::from_f32(0.0)).into();
+        assert!(matches!(cmpxchg(float(), float(), AtomicMemoryOrderingConstraint::Monotonic), Some(MemoryAccessError::InvalidAtomicType(_))));
+    }
+
+    #[test]
+    fn atomic_operations_check_their_operands() {
+        let rmw = AtomicReadModifyWriteInstruction::new(AtomicReadModifyWriteOperation::Add, null(PointerType::new_opaque()), int(1), AtomicMemoryOrderingConstraint::Monotonic).unwrap();
+        assert_eq!(rmw.with_volatile().with_align(4).to_string(), "atomicrmw volatile add ptr null, i32 1 monotonic, align 4");
+        let rmw = |operation, pointer, ordering| AtomicReadModifyWriteInstruction::new(operation, pointer, int(1), ordering).err();
+        assert_eq!(rmw(AtomicReadModifyWriteOperation::FloatAdd, null(PointerType::new_opaque()), AtomicMemoryOrderingConstraint::Monotonic), Some(MemoryAccessError::InvalidAtomicType(IntegerType(32).into())));
+        assert_eq!(rmw(AtomicReadModifyWriteOperation::Exchange, null(PointerType::new_opaque()), AtomicMemoryOrderingConstraint::Unordered), Some(MemoryAccessError::InvalidOrdering(AtomicMemoryOrderingConstraint::Unordered)));
+        assert!(matches!(rmw(AtomicReadModifyWriteOperation::Or, null(PointerType::new(IntegerType(8))), AtomicMemoryOrderingConstraint::Monotonic), Some(MemoryAccessError::TypeMismatch { .. })));
+        assert_eq!(FenceInstruction::new(Some(Cow::Borrowed("agent")), AtomicMemoryOrderingConstraint::Acquire).unwrap().to_string(), "fence syncscope(\"agent\") acquire");
+        assert_eq!(FenceInstruction::new(None, AtomicMemoryOrderingConstraint::Monotonic).err(), Some(MemoryAccessError::InvalidOrdering(AtomicMemoryOrderingConstraint::Monotonic)));
+    }
+
+    #[test]
+    fn loads_match_the_pointee() {
+        use crate::reference::Reference;
+        use crate::identifier::LocalIdentifier;
+        let pointer = |_type: PointerType| Value::from(Reference::new(LocalIdentifier::new("p").unwrap(), _type));
+        let load = LoadInstruction::new(false, IntegerType(32), pointer(PointerType::new(IntegerType(32))), 4).unwrap();
+        assert_eq!(load.to_string(), "load i32, i32* %p, align 4");
+        let load = LoadInstruction::new(true, IntegerType(64), pointer(PointerType::new_opaque()), 0).unwrap();
+        assert_eq!(load.to_string(), "load volatile i64, ptr %p");
+        let load = LoadInstruction::new_atomic(false, IntegerType(32), pointer(PointerType::new_opaque()), Some(Cow::Borrowed("agent")), AtomicMemoryOrderingConstraint::Acquire, 4).unwrap();
+        assert_eq!(load.to_string(), "load atomic i32, ptr %p syncscope(\"agent\") acquire, align 4");
+        assert_eq!(LoadInstruction::new(false, IntegerType(64), pointer(PointerType::new_with_address_space(IntegerType(32), 1)), 8).err(), Some(MemoryAccessError::TypeMismatch {
+            expected: PointerType::new_with_address_space(IntegerType(64), 1).into(),
+            found: PointerType::new_with_address_space(IntegerType(32), 1).into()
+        }));
+        assert_eq!(LoadInstruction::new(false, IntegerType(32), int(0), 4).err(), Some(MemoryAccessError::InvalidPointer(IntegerType(32).into())));
+        assert_eq!(LoadInstruction::new(false, first_class::LabelType, pointer(PointerType::new_opaque()), 4).err(), Some(MemoryAccessError::InvalidType(first_class::LabelType.into())));
+    }
+}
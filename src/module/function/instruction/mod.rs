@@ -9,7 +9,10 @@ pub mod bitwise_binary_operator;
 pub mod vector_operator;
 pub mod aggregate_operator;
 pub mod memory_operator;
+pub mod conversion_operator;
+pub mod other_operator;
 
+#[derive(Debug)]
 pub enum Instruction<'s> {
     Terminal(terminator::Instruction<'s>),
     UnaryOperator(unary_operator::Instruction<'s>),
@@ -18,6 +21,9 @@ pub enum Instruction<'s> {
     VectorOperator(vector_operator::Instruction<'s>),
     AggregateOperator(aggregate_operator::Instruction<'s>),
     MemoryOperator(memory_operator::Instruction<'s>),
+    ConversionOperator(conversion_operator::Instruction<'s>),
+    OtherOperator(other_operator::Instruction<'s>),
+    Variable(Variable<'s>),
 }
 
 impl Display for Instruction<'_> {
@@ -30,29 +36,110 @@ impl Display for Instruction<'_> {
             Instruction::VectorOperator(it) => it as &dyn Display,
             Instruction::AggregateOperator(it) => it as &dyn Display,
             Instruction::MemoryOperator(it) => it as &dyn Display,
+            Instruction::ConversionOperator(it) => it as &dyn Display,
+            Instruction::OtherOperator(it) => it as &dyn Display,
+            Instruction::Variable(it) => it as &dyn Display,
         }).fmt(f)
     }
 }
 
+impl Instruction<'_> {
+    /// Whether the instruction produces a value without naming it, like a bare `call i32 @f()`.
+    /// LLVM numbers such a value like any other unnamed value.
+    pub(crate) fn has_unnamed_result(&self) -> bool {
+        match self {
+            Instruction::Terminal(terminator::Instruction::Invoke(it)) => !it.function_type.return_type.is_void(),
+            Instruction::Terminal(terminator::Instruction::CallBranch(it)) => !it.function_type.return_type.is_void(),
+            Instruction::Terminal(_) => false,
+            Instruction::MemoryOperator(memory_operator::Instruction::Store(_) | memory_operator::Instruction::Fence(_)) => false,
+            Instruction::OtherOperator(other_operator::Instruction::Call(it)) => !it.function_type.return_type.is_void(),
+            Instruction::Variable(_) => false,
+            _ => true
+        }
+    }
+}
+
+#[derive(Debug)]
 pub enum ReturningInstruction<'s> {
     Terminal(terminator::ReturningInstruction<'s>),
     UnaryOperator(unary_operator::ReturningInstruction<'s>),
+    BitwiseBinaryOperator(bitwise_binary_operator::Instruction<'s>),
+    ConversionOperator(conversion_operator::Instruction<'s>),
+    OtherOperator(other_operator::ReturningInstruction<'s>),
 }
 
 impl Display for ReturningInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         (match self {
             Self::Terminal(it) => it as &dyn Display,
-            Self::UnaryOperator(it) => it as &dyn Display
+            Self::UnaryOperator(it) => it as &dyn Display,
+            Self::BitwiseBinaryOperator(it) => it as &dyn Display,
+            Self::ConversionOperator(it) => it as &dyn Display,
+            Self::OtherOperator(it) => it as &dyn Display
         }).fmt(f)
     }
 }
 
+#[derive(Debug)]
 pub struct Variable<'s> {
-    identifier: LocalIdentifier<'s>,
+    pub(crate) identifier: LocalIdentifier<'s>,
     instruction: ReturningInstruction<'s>
 }
 
+impl<'s> Variable<'s> {
+    pub fn new(identifier: LocalIdentifier<'s>, instruction: ReturningInstruction<'s>) -> Self {
+        Variable { identifier, instruction }
+    }
+}
+
+impl<'s> From<Variable<'s>> for Instruction<'s> {
+    fn from(variable: Variable<'s>) -> Self {
+        Instruction::Variable(variable)
+    }
+}
+
+impl<'s> From<bitwise_binary_operator::OrInstruction<'s>> for Instruction<'s> {
+    fn from(instruction: bitwise_binary_operator::OrInstruction<'s>) -> Self {
+        Instruction::BitwiseBinaryOperator(bitwise_binary_operator::Instruction::Or(instruction))
+    }
+}
+
+impl<'s> From<bitwise_binary_operator::OrInstruction<'s>> for ReturningInstruction<'s> {
+    fn from(instruction: bitwise_binary_operator::OrInstruction<'s>) -> Self {
+        ReturningInstruction::BitwiseBinaryOperator(bitwise_binary_operator::Instruction::Or(instruction))
+    }
+}
+
+impl<'s> From<conversion_operator::CastInstruction<'s>> for Instruction<'s> {
+    fn from(instruction: conversion_operator::CastInstruction<'s>) -> Self {
+        Instruction::ConversionOperator(conversion_operator::Instruction::Cast(instruction))
+    }
+}
+
+impl<'s> From<conversion_operator::CastInstruction<'s>> for ReturningInstruction<'s> {
+    fn from(instruction: conversion_operator::CastInstruction<'s>) -> Self {
+        ReturningInstruction::ConversionOperator(conversion_operator::Instruction::Cast(instruction))
+    }
+}
+
+impl<'s> From<other_operator::CallInstruction<'s>> for Instruction<'s> {
+    fn from(instruction: other_operator::CallInstruction<'s>) -> Self {
+        Instruction::OtherOperator(other_operator::Instruction::Call(instruction))
+    }
+}
+
+impl<'s> From<other_operator::CallInstruction<'s>> for ReturningInstruction<'s> {
+    fn from(instruction: other_operator::CallInstruction<'s>) -> Self {
+        ReturningInstruction::OtherOperator(other_operator::ReturningInstruction::Call(instruction))
+    }
+}
+
+impl<'s> From<other_operator::VariableArgumentInstruction<'s>> for ReturningInstruction<'s> {
+    fn from(instruction: other_operator::VariableArgumentInstruction<'s>) -> Self {
+        ReturningInstruction::OtherOperator(other_operator::ReturningInstruction::VariableArgument(instruction))
+    }
+}
+
 impl Display for Variable<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{} = {}", self.identifier, self.instruction))
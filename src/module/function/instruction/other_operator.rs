@@ -0,0 +1,195 @@
+use std::fmt::Display;
+
+use crate::{types::{first_class, interner::TypeId, FunctionType, ReturnType}, reference::{Reference, Value}, module::{CallingConvention, function::Function}};
+
+#[derive(Debug)]
+pub enum Instruction<'s> {
+    Call(CallInstruction<'s>),
+    VariableArgument(VariableArgumentInstruction<'s>),
+}
+
+impl Display for Instruction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (match self {
+            Self::Call(it) => it as &dyn Display,
+            Self::VariableArgument(it) => it as &dyn Display,
+        }).fmt(f)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReturningInstruction<'s> {
+    Call(CallInstruction<'s>),
+    VariableArgument(VariableArgumentInstruction<'s>),
+}
+
+impl Display for ReturningInstruction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (match self {
+            Self::Call(it) => it as &dyn Display,
+            Self::VariableArgument(it) => it as &dyn Display,
+        }).fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TailCallKind {
+    Tail,
+    MustTail,
+    NoTail,
+}
+
+impl Display for TailCallKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Tail => "tail",
+            Self::MustTail => "musttail",
+            Self::NoTail => "notail",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallError {
+    /// A callee that isn't a pointer, or is a typed pointer to a function of another type.
+    InvalidCallee { function_type: FunctionType, callee: first_class::Type },
+    /// `found` arguments for a function taking `expected`, or at least `expected` if it is variadic.
+    ArgumentCount { expected: usize, variadic: bool, found: usize },
+    ArgumentType { index: usize, expected: first_class::Type, found: first_class::Type },
+    /// A destination of `invoke` or `callbr` that isn't a label.
+    InvalidDestination(first_class::Type)
+}
+
+impl Display for CallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidCallee { function_type, callee } => f.write_fmt(format_args!("a callee of type `{}` can't be called as `{}`", callee, function_type)),
+            Self::ArgumentCount { expected, variadic: false, found } => f.write_fmt(format_args!("the function takes {} arguments, found {}", expected, found)),
+            Self::ArgumentCount { expected, variadic: true, found } => f.write_fmt(format_args!("the function takes at least {} arguments, found {}", expected, found)),
+            Self::ArgumentType { index, expected, found } => f.write_fmt(format_args!("argument {} has type `{}`, expected `{}`", index, found, expected)),
+            Self::InvalidDestination(_type) => f.write_fmt(format_args!("a destination of type `{}` isn't a label", _type))
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+/// Checks `callee` and `arguments` against `function_type`. Arguments past the parameters of
+/// a variadic function can have any type.
+pub(crate) fn check_call(function_type: &FunctionType, callee: &Value<'_>, arguments: &[Value<'_>]) -> Result<(), CallError> {
+    let callee = &*callee.return_type();
+    let pointee_matches = callee.pointee().is_none_or(|pointee| matches!(pointee, crate::types::Type::Function(it) if it == function_type));
+    if !callee.is_pointer() || !pointee_matches {
+        return Err(CallError::InvalidCallee { function_type: function_type.clone(), callee: callee.clone() });
+    }
+    let expected = function_type.argument_types.len();
+    if arguments.len() < expected || (arguments.len() > expected && !function_type.variadic) {
+        return Err(CallError::ArgumentCount { expected, variadic: function_type.variadic, found: arguments.len() });
+    }
+    for (index, (argument, expected)) in arguments.iter().zip(&function_type.argument_types).enumerate() {
+        let found = argument.return_type();
+        if found != *expected && !pointers_match(&found, expected) {
+            return Err(CallError::ArgumentType { index, expected: expected.get().clone(), found: found.get().clone() });
+        }
+    }
+    return Ok(());
+}
+
+/// Pointers in the same address space are interchangeable if either of them is opaque.
+fn pointers_match(found: &crate::types::first_class::Type, expected: &crate::types::first_class::Type) -> bool {
+    found.is_pointer() && expected.is_pointer() && found.address_space() == expected.address_space() && (found.pointee().is_none() || expected.pointee().is_none())
+}
+
+/// Checks that the destination of an `invoke` or `callbr` is a label.
+pub(crate) fn check_destination(destination: &Value<'_>) -> Result<(), CallError> {
+    let _type = destination.return_type();
+    if !_type.is_label() { return Err(CallError::InvalidDestination(_type.get().clone())); }
+    return Ok(());
+}
+
+#[derive(Debug)]
+pub struct CallInstruction<'s> {
+    tail_call_kind: Option<TailCallKind>,
+    //fast_math_flags: Vec<FastMathFlag>,
+    calling_convention: CallingConvention,
+    //return_attributes: Vec<ReturnAttribute>,
+    address_space: usize,
+    pub(crate) function_type: FunctionType,
+    callee: Value<'s>,
+    arguments: Vec<Value<'s>>,
+    //function_attributes: Vec<FunctionAttribute>,
+    //operand_bundles: Vec<OperandBundle>
+}
+
+impl<'s> CallInstruction<'s> {
+    /// Calls `callee`, a pointer to a function of `function_type`.
+    /// Fails if `arguments` don't match the parameters of `function_type`.
+    pub fn new(function_type: FunctionType, callee: Value<'s>, arguments: Vec<Value<'s>>) -> Result<Self, CallError> {
+        check_call(&function_type, &callee, &arguments)?;
+        Ok(CallInstruction { tail_call_kind: None, calling_convention: CallingConvention::C, address_space: 0, function_type, callee, arguments })
+    }
+    /// Calls `function` directly, using its type, calling convention and address space.
+    pub fn new_to_function(function: &Function<'s>, arguments: Vec<Value<'s>>) -> Result<Self, CallError> {
+        let call = CallInstruction::new(FunctionType::from(function), Reference::from(function).into(), arguments)?;
+        Ok(call.with_calling_convention(function.calling_convention.clone()).with_address_space(function.address_space))
+    }
+    pub fn with_tail_call_kind(mut self, tail_call_kind: TailCallKind) -> Self {
+        self.tail_call_kind = Some(tail_call_kind);
+        self
+    }
+    pub fn with_calling_convention(mut self, calling_convention: CallingConvention) -> Self {
+        self.calling_convention = calling_convention;
+        self
+    }
+    pub fn with_address_space(mut self, address_space: usize) -> Self {
+        self.address_space = address_space;
+        self
+    }
+}
+
+impl Display for CallInstruction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(tail_call_kind) = &self.tail_call_kind { f.write_fmt(format_args!("{} ", tail_call_kind))?; }
+        f.write_str("call ")?;
+        if let CallingConvention::C = self.calling_convention {} else { f.write_fmt(format_args!("{} ", self.calling_convention))?; }
+        if self.address_space != 0 { f.write_fmt(format_args!("addrspace({}) ", self.address_space))?; }
+        // Variadic callees need the full function type, everything else is written with the return type only.
+        if self.function_type.is_variadic() {
+            f.write_fmt(format_args!("{} ", self.function_type))?;
+        } else {
+            f.write_fmt(format_args!("{} ", self.function_type.return_type))?;
+        }
+        f.write_fmt(format_args!("{}(", self.callee.untyped()))?;
+        for (i, argument) in self.arguments.iter().enumerate() {
+            argument.fmt(f)?;
+            if i < (self.arguments.len()-1) { f.write_str(", ")?; }
+        }
+        f.write_str(")")?;
+        Ok(())
+    }
+}
+
+/// Reads the next argument of type `_type` from the `va_list` that `list` points to.
+#[derive(Debug)]
+pub struct VariableArgumentInstruction<'s> {
+    list: Value<'s>,
+    _type: TypeId
+}
+
+impl<'s> VariableArgumentInstruction<'s> {
+    pub fn new(list: Value<'s>, _type: impl Into<TypeId>) -> Self {
+        VariableArgumentInstruction { list, _type: _type.into() }
+    }
+}
+
+impl ReturnType for VariableArgumentInstruction<'_> {
+    fn return_type(&self) -> TypeId {
+        self._type
+    }
+}
+
+impl Display for VariableArgumentInstruction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("va_arg {}, {}", self.list, self._type))
+    }
+}
@@ -1,7 +1,10 @@
 use std::fmt::Display;
 
-use crate::{reference::Value, module::function::Function, types::FunctionType};
+use crate::{reference::{Reference, Value}, module::{CallingConvention, function::Function}, types::FunctionType};
 
+use super::other_operator::{check_call, check_destination, CallError};
+
+#[derive(Debug)]
 pub enum Instruction<'s> {
     Return(ReturnInstruction<'s>),
     Branch(BranchInstruction<'s>),
@@ -26,6 +29,7 @@ impl<'s> Display for Instruction<'s> {
     }
 }
 
+#[derive(Debug)]
 pub enum ReturningInstruction<'s> {
     Invoke(InvokeInstruction<'s>),
     CallBranch(CallBranchInstruction<'s>),
@@ -42,6 +46,7 @@ impl<'s> Display for ReturningInstruction<'s> {
     }
 }
 
+#[derive(Debug)]
 pub enum ReturnInstruction<'s> {
     Void,
     NonVoid(Value<'s>)
@@ -56,6 +61,7 @@ impl<'s> Display for ReturnInstruction<'s> {
     }
 }
 
+#[derive(Debug)]
 pub enum BranchInstruction<'s> {
     Unconditional { label: Value<'s> },
     Conditional { condition: Value<'s>, iflabel: Value<'s>, elselabel: Value<'s> }
@@ -70,6 +76,7 @@ impl Display for BranchInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct SwitchInstruction<'s> {
     input: Value<'s>,
     defaultdest: Value<'s>,
@@ -88,6 +95,7 @@ impl Display for SwitchInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct IndirectBranchInstruction<'s> {
     pointer: Value<'s>,
     possible_labels: Vec<Value<'s>>
@@ -105,23 +113,51 @@ impl Display for IndirectBranchInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct InvokeInstruction<'s> {
-    function_ref: &'s Function<'s>,
+    calling_convention: CallingConvention,
+    address_space: usize,
+    pub(crate) function_type: FunctionType,
+    callee: Value<'s>,
     arguments: Vec<Value<'s>>,
     normal: Value<'s>,
     exception: Value<'s>
 }
 
+impl<'s> InvokeInstruction<'s> {
+    /// Calls `callee`, a pointer to a function of `function_type`, continuing at the label `normal`
+    /// when it returns and at the label `exception` when it unwinds.
+    pub fn new(function_type: FunctionType, callee: Value<'s>, arguments: Vec<Value<'s>>, normal: Value<'s>, exception: Value<'s>) -> Result<Self, CallError> {
+        check_call(&function_type, &callee, &arguments)?;
+        check_destination(&normal)?;
+        check_destination(&exception)?;
+        Ok(InvokeInstruction { calling_convention: CallingConvention::C, address_space: 0, function_type, callee, arguments, normal, exception })
+    }
+    /// Invokes `function` directly, using its type, calling convention and address space.
+    pub fn new_to_function(function: &Function<'s>, arguments: Vec<Value<'s>>, normal: Value<'s>, exception: Value<'s>) -> Result<Self, CallError> {
+        let invoke = InvokeInstruction::new(FunctionType::from(function), Reference::from(function).into(), arguments, normal, exception)?;
+        Ok(invoke.with_calling_convention(function.calling_convention.clone()).with_address_space(function.address_space))
+    }
+    pub fn with_calling_convention(mut self, calling_convention: CallingConvention) -> Self {
+        self.calling_convention = calling_convention;
+        self
+    }
+    pub fn with_address_space(mut self, address_space: usize) -> Self {
+        self.address_space = address_space;
+        self
+    }
+}
+
 impl Display for InvokeInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("invoke ")?;
-        if let crate::module::CallingConvention::C = self.function_ref.calling_convention {} else {
-            f.write_fmt(format_args!("{} ", self.function_ref.calling_convention))?;
+        if let CallingConvention::C = self.calling_convention {} else {
+            f.write_fmt(format_args!("{} ", self.calling_convention))?;
         }
-        if self.function_ref.address_space == 0 {
-            f.write_fmt(format_args!("addrspace({}) ", self.function_ref.address_space))?;
+        if self.address_space != 0 {
+            f.write_fmt(format_args!("addrspace({}) ", self.address_space))?;
         }
-        f.write_fmt(format_args!("{} {}( ", FunctionType::from(self.function_ref), self.function_ref.identifier))?;
+        f.write_fmt(format_args!("{} {}( ", self.function_type, self.callee.untyped()))?;
         for (i, argument) in self.arguments.iter().enumerate() {
             argument.fmt(f)?;
             if i < (self.arguments.len()-1) { f.write_str(", ")?; }
@@ -131,23 +167,52 @@ impl Display for InvokeInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct CallBranchInstruction<'s> {
-    function_ref: &'s Function<'s>,
+    calling_convention: CallingConvention,
+    address_space: usize,
+    pub(crate) function_type: FunctionType,
+    callee: Value<'s>,
     arguments: Vec<Value<'s>>,
     fallthrough: Value<'s>,
     indirect: Vec<Value<'s>>,
 }
 
+impl<'s> CallBranchInstruction<'s> {
+    /// Calls `callee`, a pointer to a function of `function_type` (in practice inline assembly),
+    /// continuing at the label `fallthrough` or at one of the labels in `indirect`.
+    pub fn new(function_type: FunctionType, callee: Value<'s>, arguments: Vec<Value<'s>>, fallthrough: Value<'s>, indirect: Vec<Value<'s>>) -> Result<Self, CallError> {
+        check_call(&function_type, &callee, &arguments)?;
+        check_destination(&fallthrough)?;
+        for destination in &indirect { check_destination(destination)?; }
+        Ok(CallBranchInstruction { calling_convention: CallingConvention::C, address_space: 0, function_type, callee, arguments, fallthrough, indirect })
+    }
+    /// Calls `function` directly, using its type, calling convention and address space.
+    pub fn new_to_function(function: &Function<'s>, arguments: Vec<Value<'s>>, fallthrough: Value<'s>, indirect: Vec<Value<'s>>) -> Result<Self, CallError> {
+        let call = CallBranchInstruction::new(FunctionType::from(function), Reference::from(function).into(), arguments, fallthrough, indirect)?;
+        Ok(call.with_calling_convention(function.calling_convention.clone()).with_address_space(function.address_space))
+    }
+    pub fn with_calling_convention(mut self, calling_convention: CallingConvention) -> Self {
+        self.calling_convention = calling_convention;
+        self
+    }
+    pub fn with_address_space(mut self, address_space: usize) -> Self {
+        self.address_space = address_space;
+        self
+    }
+}
+
 impl Display for CallBranchInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::printer::supports("callbr", Some(9), None);
         f.write_str("callbr ")?;
-        if let crate::module::CallingConvention::C = self.function_ref.calling_convention {} else {
-            f.write_fmt(format_args!("{} ", self.function_ref.calling_convention))?;
+        if let CallingConvention::C = self.calling_convention {} else {
+            f.write_fmt(format_args!("{} ", self.calling_convention))?;
         }
-        if self.function_ref.address_space == 0 {
-            f.write_fmt(format_args!("addrspace({}) ", self.function_ref.address_space))?;
+        if self.address_space != 0 {
+            f.write_fmt(format_args!("addrspace({}) ", self.address_space))?;
         }
-        f.write_fmt(format_args!("{} {}( ", FunctionType::from(self.function_ref), self.function_ref.identifier))?;
+        f.write_fmt(format_args!("{} {}( ", self.function_type, self.callee.untyped()))?;
         for (i, argument) in self.arguments.iter().enumerate() {
             argument.fmt(f)?;
             if i < (self.arguments.len()-1) { f.write_str(", ")?; }
@@ -162,6 +227,7 @@ impl Display for CallBranchInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct ResumeInstruction<'s>(Value<'s>);
 
 impl Display for ResumeInstruction<'_> {
@@ -170,6 +236,7 @@ impl Display for ResumeInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct CatchSwitchInstruction<'s> {
     parent: Value<'s>,
     handlers: Vec<Value<'s>>,
@@ -178,13 +245,8 @@ pub struct CatchSwitchInstruction<'s> {
 
 impl Display for CatchSwitchInstruction<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("catchswitch within ")?;
-        match &self.parent {
-            Value::Constant(crate::constant::Constant::Simple(crate::constant::simple::Constant::Token(crate::constant::simple::TokenConstant))) => 
-                crate::constant::simple::TokenConstant.fmt(f)?,
-            Value::Reference(it) => it.fmt(f)?,
-            _ => unreachable!()
-        }
+        // The parent is either `none` or a reference to a pad, both written without a type.
+        f.write_fmt(format_args!("catchswitch within {}", self.parent.untyped()))?;
         f.write_str(" [ ")?;
         for (i, handler) in self.handlers.iter().enumerate() { 
             handler.fmt(f)?;
@@ -196,6 +258,7 @@ impl Display for CatchSwitchInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct CatchReturnInstruction<'s> {
     token: Value<'s>,
     label: Value<'s>
@@ -207,6 +270,7 @@ impl Display for CatchReturnInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct CleanUpReturnInstruction<'s> {
     value: Value<'s>,
     label: Option<Value<'s>>
@@ -220,6 +284,7 @@ impl Display for CleanUpReturnInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct UnreachableInstruction;
 
 impl Display for UnreachableInstruction {
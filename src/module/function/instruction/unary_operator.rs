@@ -2,6 +2,7 @@ use std::fmt::Display;
 
 use crate::reference::Value;
 
+#[derive(Debug)]
 pub enum Instruction<'s> {
     FloatNegation(FloatNegationInstruction<'s>),
 }
@@ -14,6 +15,7 @@ impl Display for Instruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub enum ReturningInstruction<'s> {
     FloatNegation(FloatNegationInstruction<'s>),
 }
@@ -26,6 +28,7 @@ impl Display for ReturningInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct FloatNegationInstruction<'s> {
     //fast_math_flags: Vec<FastMathFlag>,
     value: Value<'s>
@@ -2,6 +2,7 @@ use std::fmt::Display;
 
 use crate::reference::Value;
 
+#[derive(Debug)]
 pub enum Instruction<'s> {
     ExtractElement(ExtractElementInstruction<'s>),
     InsertElement(InsertElementInstruction<'s>),
@@ -18,6 +19,7 @@ impl Display for Instruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct ExtractElementInstruction<'s> {
     vector: Value<'s>,
     index: Value<'s>
@@ -29,6 +31,7 @@ impl Display for ExtractElementInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct InsertElementInstruction<'s> {
     vector: Value<'s>,
     element: Value<'s>,
@@ -41,6 +44,7 @@ impl Display for InsertElementInstruction<'_> {
     }
 }
 
+#[derive(Debug)]
 pub struct ShuffleVectorInstruction<'s> {
     vector1: Value<'s>,
     vector2: Value<'s>,
@@ -0,0 +1,47 @@
+//! Declarations of and calls to LLVM intrinsics.
+
+use std::borrow::Cow;
+
+use crate::{identifier::{GlobalIdentifier, LocalIdentifier, LocalName}, types::{first_class::single_value::{IntegerType, PointerType}, Type}, reference::Value};
+
+use super::{Argument, Function, instruction::other_operator::{CallError, CallInstruction}};
+
+fn byte_pointer() -> crate::types::first_class::Type {
+    PointerType::new(IntegerType(8)).into()
+}
+
+/// `declare void @llvm.va_start(i8* %list)`
+pub fn va_start<'s>() -> Function<'s> {
+    Function::new(GlobalIdentifier(Cow::Borrowed(b"llvm.va_start")), Type::Void, vec![Argument::new(byte_pointer(), LocalIdentifier(LocalName::Named(Cow::Borrowed(b"list"))))]).expect("intrinsic arguments are named")
+}
+
+/// `declare void @llvm.va_end(i8* %list)`
+pub fn va_end<'s>() -> Function<'s> {
+    Function::new(GlobalIdentifier(Cow::Borrowed(b"llvm.va_end")), Type::Void, vec![Argument::new(byte_pointer(), LocalIdentifier(LocalName::Named(Cow::Borrowed(b"list"))))]).expect("intrinsic arguments are named")
+}
+
+/// `declare void @llvm.va_copy(i8* %destination, i8* %source)`
+pub fn va_copy<'s>() -> Function<'s> {
+    Function::new(GlobalIdentifier(Cow::Borrowed(b"llvm.va_copy")), Type::Void, vec![
+        Argument::new(byte_pointer(), LocalIdentifier(LocalName::Named(Cow::Borrowed(b"destination")))),
+        Argument::new(byte_pointer(), LocalIdentifier(LocalName::Named(Cow::Borrowed(b"source"))))
+    ]).expect("intrinsic arguments are named")
+}
+
+/// Initializes the `va_list` that `list` points to. The module has to declare [`va_start`].
+/// Fails if `list` isn't an `i8*` or a `ptr`.
+pub fn call_va_start(list: Value<'_>) -> Result<CallInstruction<'_>, CallError> {
+    CallInstruction::new_to_function(&va_start(), vec![list])
+}
+
+/// Releases the `va_list` that `list` points to. The module has to declare [`va_end`].
+/// Fails if `list` isn't an `i8*` or a `ptr`.
+pub fn call_va_end(list: Value<'_>) -> Result<CallInstruction<'_>, CallError> {
+    CallInstruction::new_to_function(&va_end(), vec![list])
+}
+
+/// Copies the `va_list` at `source` to `destination`. The module has to declare [`va_copy`].
+/// Fails unless both pointers are `i8*` or `ptr`.
+pub fn call_va_copy<'s>(destination: Value<'s>, source: Value<'s>) -> Result<CallInstruction<'s>, CallError> {
+    CallInstruction::new_to_function(&va_copy(), vec![destination, source])
+}
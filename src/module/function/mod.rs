@@ -1,11 +1,76 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 
-use crate::{identifier::{Identifier, Identifiable, GlobalIdentifier}, types::{first_class::Type, ReturnType}, constant::Constant};
+use crate::{identifier::{Identifier, Identifiable, GlobalIdentifier, LocalIdentifier, LocalName, NameSupply, ParseError}, types::{interner::TypeId, ReturnType}, constant::Constant, metadata::MetadataAttachment};
 
 use super::{LinkageType, DLLStorageClass, UnnamedAddress, CallingConvention, VisibilityStyle, RuntimePreemptionSpecifier};
 
 pub mod instruction;
+pub mod intrinsic;
 
+/// A local value that breaks the sequential numbering of unnamed values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumberingError {
+    /// A value named with a number other than the one LLVM gives it.
+    OutOfSequence { name: String, expected: usize },
+    /// An unnamed local defined more than once.
+    Redefinition,
+    /// An argument, block or value with the name of one defined before.
+    DuplicateName(String)
+}
+
+impl Display for NumberingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfSequence { name, expected } => f.write_fmt(format_args!("value named %{} is expected to be numbered %{}", name, expected)),
+            Self::Redefinition => f.write_str("unnamed value is defined more than once"),
+            Self::DuplicateName(name) => f.write_fmt(format_args!("%{} is defined more than once", crate::identifier::Name(name.as_bytes())))
+        }
+    }
+}
+
+impl std::error::Error for NumberingError {}
+
+/// The first of `names` that is defined in `supply` already or comes up twice.
+fn duplicate_name<'a>(supply: &NameSupply, mut names: impl Iterator<Item = &'a [u8]>) -> Option<&'a [u8]> {
+    let mut seen = HashSet::new();
+    names.find(|it| supply.is_defined(it) || !seen.insert(*it))
+}
+
+/// Numbers of the unnamed values of a function, in definition order.
+#[derive(Debug, Default)]
+struct Numbering {
+    numbers: HashMap<usize, usize>,
+    next: usize
+}
+
+impl Numbering {
+    /// Numbers `definitions`, which are defined in this order. `None` is a value without an identifier,
+    /// which takes a number all the same. Nothing is numbered if one of them is invalid.
+    fn define<'a, 's: 'a>(&mut self, definitions: impl Iterator<Item = Option<&'a LocalIdentifier<'s>>>) -> Result<(), NumberingError> {
+        let mut numbers = HashMap::new();
+        let mut next = self.next;
+        for definition in definitions {
+            let Some(identifier) = definition else { next += 1; continue; };
+            match &identifier.0 {
+                LocalName::Named(name) => {
+                    if !name.iter().all(u8::is_ascii_digit) { continue; }
+                    if **name != *next.to_string().as_bytes() { return Err(NumberingError::OutOfSequence { name: String::from_utf8_lossy(name).into_owned(), expected: next }); }
+                }
+                LocalName::Unnamed(key) => {
+                    if self.numbers.contains_key(key) || numbers.insert(*key, next).is_some() { return Err(NumberingError::Redefinition); }
+                }
+            }
+            next += 1;
+        }
+        self.numbers.extend(numbers);
+        self.next = next;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
 pub struct Function<'s> {
     identifier: GlobalIdentifier<'s>,
     linkage: Option<LinkageType>,
@@ -14,47 +79,239 @@ pub struct Function<'s> {
     dll_storage_class: Option<DLLStorageClass>,
     calling_convention: CallingConvention,
     //return_attributes: Vec<ReturnAttribute>,
-    pub(crate) return_type: Type,
+    pub(crate) return_type: crate::types::Type,
     pub(crate) arguments: Vec<Argument<'s>>,
+    pub(crate) variadic: bool,
     unnamed_address: Option<UnnamedAddress>,
     address_space: usize,
     //function_attributes: Vec<FunctionAttribute>,
-    section_name: Option<&'s str>,
-    partition_name: Option<&'s str>,
+    section_name: Option<Cow<'s, str>>,
+    partition_name: Option<Cow<'s, str>>,
     //comdat: Option<Comdat>,
     align: usize,
     //garbage_collector: Option<&'s str>,
     prefix_constant: Option<Constant<'s>>,
     prologue_constant: Option<Constant<'s>>,
     personality_constant: Option<Constant<'s>>,
-    //metadata: Vec<Metadata>
-    instruction_blocks: Vec<InstructionBlock<'s>>
+    metadata: Vec<MetadataAttachment<'s>>,
+    instruction_blocks: Vec<InstructionBlock<'s>>,
+    numbering: Numbering,
+    local_names: NameSupply,
+    pointer_type: TypeId
+}
+
+/// The type of a pointer to a function, kept in the function so that references to it share it.
+fn pointer_type(function_type: crate::types::FunctionType, address_space: usize) -> TypeId {
+    crate::types::first_class::single_value::PointerType::new_with_address_space(function_type, address_space).into()
+}
+
+impl<'s> Function<'s> {
+    /// Fails if an argument has a numeric name other than its position among the unnamed arguments,
+    /// or if two arguments have the same name.
+    pub fn new(identifier: GlobalIdentifier<'s>, return_type: crate::types::Type, arguments: Vec<Argument<'s>>) -> Result<Self, NumberingError> {
+        let mut numbering = Numbering::default();
+        numbering.define(arguments.iter().map(|it| Some(&it.identifier)))?;
+        let mut local_names = NameSupply::new();
+        let names = arguments.iter().filter_map(|it| it.identifier.name_bytes());
+        if let Some(name) = duplicate_name(&local_names, names.clone()) { return Err(NumberingError::DuplicateName(String::from_utf8_lossy(name).into_owned())); }
+        names.for_each(|it| { local_names.define(it); });
+        let function_type = crate::types::FunctionType::new(return_type.clone(), arguments.iter().map(|it| it.return_type).collect());
+        Ok(Function {
+            identifier,
+            linkage: None,
+            preemtion_specifier: RuntimePreemptionSpecifier::Preemptable,
+            visibility: VisibilityStyle::Default,
+            dll_storage_class: None,
+            calling_convention: CallingConvention::C,
+            return_type,
+            arguments,
+            variadic: false,
+            unnamed_address: None,
+            address_space: 0,
+            section_name: None,
+            partition_name: None,
+            align: 0,
+            prefix_constant: None,
+            prologue_constant: None,
+            personality_constant: None,
+            metadata: Vec::new(),
+            instruction_blocks: Vec::new(),
+            numbering,
+            local_names,
+            pointer_type: pointer_type(function_type, 0)
+        })
+    }
+    /// A function that accepts further arguments after `arguments`, like `printf`.
+    pub fn new_variadic(identifier: GlobalIdentifier<'s>, return_type: crate::types::Type, arguments: Vec<Argument<'s>>) -> Result<Self, NumberingError> {
+        let mut function = Function::new(identifier, return_type, arguments)?;
+        function.variadic = true;
+        function.pointer_type = pointer_type(crate::types::FunctionType::from(&function), function.address_space);
+        Ok(function)
+    }
+    pub fn with_linkage(mut self, linkage: LinkageType) -> Self {
+        self.linkage = Some(linkage);
+        self
+    }
+    pub fn with_preemption_specifier(mut self, preemption_specifier: RuntimePreemptionSpecifier) -> Self {
+        self.preemtion_specifier = preemption_specifier;
+        self
+    }
+    pub fn with_visibility(mut self, visibility: VisibilityStyle) -> Self {
+        self.visibility = visibility;
+        self
+    }
+    pub fn with_dll_storage_class(mut self, dll_storage_class: DLLStorageClass) -> Self {
+        self.dll_storage_class = Some(dll_storage_class);
+        self
+    }
+    pub fn with_calling_convention(mut self, calling_convention: CallingConvention) -> Self {
+        self.calling_convention = calling_convention;
+        self
+    }
+    pub fn with_unnamed_address(mut self, unnamed_address: UnnamedAddress) -> Self {
+        self.unnamed_address = Some(unnamed_address);
+        self
+    }
+    pub fn with_address_space(mut self, address_space: usize) -> Self {
+        self.address_space = address_space;
+        self.pointer_type = pointer_type(crate::types::FunctionType::from(&self), address_space);
+        self
+    }
+    pub fn identifier(&self) -> &GlobalIdentifier<'s> {
+        &self.identifier
+    }
+    pub fn address_space(&self) -> usize {
+        self.address_space
+    }
+    pub fn with_section_name(mut self, section_name: impl Into<Cow<'s, str>>) -> Self {
+        self.section_name = Some(section_name.into());
+        self
+    }
+    pub fn with_partition_name(mut self, partition_name: impl Into<Cow<'s, str>>) -> Self {
+        self.partition_name = Some(partition_name.into());
+        self
+    }
+    pub fn with_align(mut self, align: usize) -> Self {
+        self.align = align;
+        self
+    }
+    pub fn with_prefix_constant(mut self, prefix_constant: Constant<'s>) -> Self {
+        self.prefix_constant = Some(prefix_constant);
+        self
+    }
+    pub fn with_prologue_constant(mut self, prologue_constant: Constant<'s>) -> Self {
+        self.prologue_constant = Some(prologue_constant);
+        self
+    }
+    pub fn with_personality_constant(mut self, personality_constant: Constant<'s>) -> Self {
+        self.personality_constant = Some(personality_constant);
+        self
+    }
+    pub fn with_metadata(mut self, attachment: MetadataAttachment<'s>) -> Self {
+        self.metadata.push(attachment);
+        self
+    }
+    pub fn metadata(&self) -> &[MetadataAttachment<'s>] {
+        &self.metadata
+    }
+    /// The number an unnamed value or block of this function is printed with, once it is defined.
+    pub(crate) fn local_number(&self, identifier: &LocalIdentifier<'s>) -> Option<usize> {
+        match &identifier.0 {
+            LocalName::Unnamed(key) => self.numbering.numbers.get(key).copied(),
+            LocalName::Named(_) => None
+        }
+    }
+    pub(crate) fn is_entry_block(&self, identifier: &LocalIdentifier<'s>) -> bool {
+        self.instruction_blocks.first().is_some_and(|it| it.identifier == *identifier)
+    }
+    /// Appends `instruction_block`, numbering its unnamed label and values after the ones before it.
+    /// Results of instructions that aren't assigned to a local, like a bare `call i32`, take a number too.
+    /// Fails if the block defines a numeric name out of sequence, an unnamed value defined before
+    /// or a name that an argument, block or value has already.
+    pub fn add_instruction_block(&mut self, instruction_block: InstructionBlock<'s>) -> Result<&mut Self, NumberingError> {
+        let results = instruction_block.instructions.iter().filter_map(|it| match it {
+            instruction::Instruction::Variable(variable) => Some(Some(&variable.identifier)),
+            _ if it.has_unnamed_result() => Some(None),
+            _ => None
+        });
+        let definitions = std::iter::once(Some(&instruction_block.identifier)).chain(results);
+        let names = definitions.clone().flatten().filter_map(|it| it.name_bytes());
+        if let Some(name) = duplicate_name(&self.local_names, names.clone()) { return Err(NumberingError::DuplicateName(String::from_utf8_lossy(name).into_owned())); }
+        self.numbering.define(definitions)?;
+        names.for_each(|it| { self.local_names.define(it); });
+        self.instruction_blocks.push(instruction_block);
+        Ok(self)
+    }
+    /// A local name based on `base` that no argument, block or value of this function has.
+    /// Names of blocks that haven't been added yet have to be reserved with `reserve_local`.
+    pub fn fresh_local(&mut self, base: &str) -> Result<LocalIdentifier<'s>, ParseError> {
+        LocalIdentifier::new(self.local_names.fresh(base))
+    }
+    /// Keeps `fresh_local` from handing out `name`. Returns whether it was free before.
+    pub fn reserve_local(&mut self, name: &str) -> bool {
+        self.local_names.reserve(name)
+    }
+    /// The attachments of the function and of its instructions.
+    pub(crate) fn all_metadata(&self) -> impl Iterator<Item = &MetadataAttachment<'s>> {
+        self.metadata.iter().chain(self.instruction_blocks.iter().flat_map(InstructionBlock::metadata))
+    }
+    pub fn instruction_blocks(&self) -> &[InstructionBlock<'s>] {
+        &self.instruction_blocks
+    }
+    pub fn is_variadic(&self) -> bool {
+        self.variadic
+    }
+    pub fn is_declaration(&self) -> bool {
+        self.instruction_blocks.is_empty()
+    }
 }
 
 impl<'s> Display for Function<'s> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("define ")?;
+        crate::identifier::with_numbering(self.numbering.numbers.clone(), || self.fmt_numbered(f))
+    }
+}
+
+impl<'s> Function<'s> {
+    fn fmt_numbered(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_declaration() {
+            f.write_str("declare ")?;
+            // Declarations take their attachments before the signature.
+            for attachment in &self.metadata {
+                f.write_fmt(format_args!("{} ", attachment))?;
+            }
+        } else {
+            f.write_str("define ")?;
+        }
         if let Some(linkage) = &self.linkage { f.write_fmt(format_args!("{} ", linkage))?; }
         if let RuntimePreemptionSpecifier::Local = self.preemtion_specifier { f.write_fmt(format_args!("{} ", self.preemtion_specifier))?; }
         if let VisibilityStyle::Default = self.visibility {} else { f.write_fmt(format_args!("{} ", self.visibility))?; }
         if let Some(dll_storage_class) = &self.dll_storage_class { f.write_fmt(format_args!("{} ", dll_storage_class))?; }
         if let CallingConvention::C = self.calling_convention {} else { f.write_fmt(format_args!("{} ", self.calling_convention))?; }
-        f.write_fmt(format_args!("{} {} ( ", self.return_type, self.identifier))?;
+        f.write_fmt(format_args!("{} {}(", self.return_type, self.identifier))?;
         for (i, argument) in self.arguments.iter().enumerate() {
             argument.fmt(f)?;
             if i < (self.arguments.len()-1) { f.write_str(", ")?; }
         }
+        if self.variadic {
+            if !self.arguments.is_empty() { f.write_str(", ")?; }
+            f.write_str("...")?;
+        }
         f.write_str(")")?;
         if let Some(unnamed_address) = &self.unnamed_address { f.write_fmt(format_args!(" {}", unnamed_address))?; }
         if self.address_space != 0 { f.write_fmt(format_args!(" addrspace({})", self.address_space))?; }
-        if let Some(section_name) = self.section_name { f.write_fmt(format_args!(" section \"{}\"", section_name))?; }
-        if let Some(partition_name) = self.partition_name { f.write_fmt(format_args!(" partition \"{}\"", partition_name))?; }
+        if let Some(section_name) = &self.section_name { f.write_fmt(format_args!(" section {}", crate::identifier::QuotedString(section_name)))?; }
+        if let Some(partition_name) = self.partition_name.as_deref().filter(|_| crate::printer::supports("partition", Some(9), None)) { f.write_fmt(format_args!(" partition {}", crate::identifier::QuotedString(partition_name)))?; }
         //comdat
         if self.align != 0 { f.write_fmt(format_args!(" align {}", self.align))?; }
         //gc
-        if let Some(prefix_constant) = &self.prefix_constant { f.write_fmt(format_args!(" prefix {}", prefix_constant))?; }
-        if let Some(prologue_constant) = &self.prologue_constant { f.write_fmt(format_args!(" prologue {}", prologue_constant))?; }
-        if let Some(personality_constant) = &self.personality_constant { f.write_fmt(format_args!(" personality {}", personality_constant))?; }
+        if let Some(prefix_constant) = &self.prefix_constant { f.write_fmt(format_args!(" prefix {} {}", prefix_constant.return_type(), prefix_constant))?; }
+        if let Some(prologue_constant) = &self.prologue_constant { f.write_fmt(format_args!(" prologue {} {}", prologue_constant.return_type(), prologue_constant))?; }
+        if let Some(personality_constant) = &self.personality_constant { f.write_fmt(format_args!(" personality {} {}", personality_constant.return_type(), personality_constant))?; }
+        if self.is_declaration() { return f.write_str("\n"); }
+        for attachment in &self.metadata {
+            f.write_fmt(format_args!(" {}", attachment))?;
+        }
         f.write_str(" {\n")?;
         for instruction_block in &self.instruction_blocks {
             instruction_block.fmt(f)?;
@@ -64,9 +321,26 @@ impl<'s> Display for Function<'s> {
     }
 }
 
+impl<'s> Identifiable<'s> for Function<'s> {
+    fn id(&self) -> Identifier<'s> { self.identifier.clone().into() }
+}
+
+impl<'s> ReturnType for Function<'s> {
+    fn return_type(&self) -> TypeId {
+        self.pointer_type
+    }
+}
+
+#[derive(Debug)]
 pub struct Argument<'s> {
-    pub(crate) return_type: Type,
-    pub(crate) identifier: Identifier<'s>
+    pub(crate) return_type: TypeId,
+    pub(crate) identifier: LocalIdentifier<'s>
+}
+
+impl<'s> Argument<'s> {
+    pub fn new(return_type: impl Into<TypeId>, identifier: LocalIdentifier<'s>) -> Self {
+        Argument { return_type: return_type.into(), identifier }
+    }
 }
 
 impl<'s> Display for Argument<'s> {
@@ -75,32 +349,170 @@ impl<'s> Display for Argument<'s> {
     }
 }
 
-impl<'s> Identifiable for Argument<'s> {
+impl<'s> Identifiable<'s> for Argument<'s> {
     fn id(&self) -> Identifier<'s> { self.identifier.clone().into() }
 }
 
 impl<'s> ReturnType for Argument<'s> {
-    fn return_type(&self) -> crate::types::first_class::Type { self.return_type.clone() }
+    fn return_type(&self) -> TypeId { self.return_type }
 }
 
+#[derive(Debug)]
 pub struct InstructionBlock<'s> {
-    identifier: GlobalIdentifier<'s>,
-    instructions: Vec<instruction::Instruction<'s>>
+    identifier: LocalIdentifier<'s>,
+    instructions: Vec<instruction::Instruction<'s>>,
+    /// The attachments of each instruction, by position.
+    metadata: Vec<Vec<MetadataAttachment<'s>>>
+}
+
+impl<'s> InstructionBlock<'s> {
+    pub fn new(identifier: LocalIdentifier<'s>, instructions: Vec<instruction::Instruction<'s>>) -> Self {
+        let metadata = instructions.iter().map(|_| Vec::new()).collect();
+        InstructionBlock { identifier, instructions, metadata }
+    }
+    pub fn add_instruction(&mut self, instruction: instruction::Instruction<'s>) -> &mut Self {
+        self.add_instruction_with_metadata(instruction, Vec::new())
+    }
+    /// Appends `instruction` with `metadata` attached, e.g. `!tbaa` on a load.
+    pub fn add_instruction_with_metadata(&mut self, instruction: instruction::Instruction<'s>, metadata: Vec<MetadataAttachment<'s>>) -> &mut Self {
+        self.instructions.push(instruction);
+        self.metadata.push(metadata);
+        self
+    }
+    pub(crate) fn metadata(&self) -> impl Iterator<Item = &MetadataAttachment<'s>> {
+        self.metadata.iter().flatten()
+    }
 }
 
 impl<'s> Display for InstructionBlock<'s> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{}:\n", self.identifier.0))?;
-        for instruction in &self.instructions {
-            instruction.fmt(f)?;
+        for (instruction, metadata) in self.instructions.iter().zip(&self.metadata) {
+            f.write_fmt(format_args!("  {}", instruction))?;
+            for attachment in metadata {
+                f.write_fmt(format_args!(", {}", attachment))?;
+            }
+            f.write_str("\n")?;
         }
         Ok(())
     }
 }
 
-impl<'s> Identifiable for InstructionBlock<'s> {
+impl<'s> ReturnType for InstructionBlock<'s> {
+    fn return_type(&self) -> TypeId { crate::types::first_class::LabelType.into() }
+}
+
+impl<'s> Identifiable<'s> for InstructionBlock<'s> {
     fn id(&self) -> Identifier<'s> {
         self.identifier.clone().into()
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::instruction::{Instruction, Variable};
+    use super::instruction::other_operator::{CallError, CallInstruction};
+    use super::instruction::terminator::{self, CallBranchInstruction, InvokeInstruction, ReturnInstruction};
+    use crate::reference::{Reference, Value};
+    use crate::types::{FunctionType, first_class::single_value::IntegerType};
+
+    fn callee() -> Function<'static> {
+        Function::new(GlobalIdentifier::new("g").unwrap(), IntegerType(32).into(), vec![]).unwrap()
+    }
+
+    fn block(identifier: LocalIdentifier<'static>, instructions: Vec<Instruction<'static>>) -> InstructionBlock<'static> {
+        let mut instructions = instructions;
+        instructions.push(Instruction::Terminal(terminator::Instruction::Return(ReturnInstruction::Void)));
+        InstructionBlock::new(identifier, instructions)
+    }
+
+    #[test]
+    fn unnamed_values_are_numbered_in_definition_order() {
+        let argument = Argument::new(IntegerType(32), LocalIdentifier::unnamed());
+        let mut function = Function::new(GlobalIdentifier::new("f").unwrap(), crate::types::Type::Void, vec![argument]).unwrap();
+        let result = LocalIdentifier::unnamed();
+        function.add_instruction_block(block(LocalIdentifier::unnamed(), vec![
+            CallInstruction::new_to_function(&callee(), vec![]).unwrap().into(),
+            Variable::new(result.clone(), CallInstruction::new_to_function(&callee(), vec![]).unwrap().into()).into()
+        ])).unwrap();
+        assert_eq!(function.local_number(&result), Some(3));
+        assert_eq!(function.to_string(), "define void @f(i32 %0) {\n1:\n  call i32 @g()\n  %3 = call i32 @g()\n  ret void\n}\n");
+    }
+
+    #[test]
+    fn numeric_names_have_to_follow_the_sequence() {
+        let mut function = Function::new(GlobalIdentifier::new("f").unwrap(), crate::types::Type::Void, vec![]).unwrap();
+        // The bare call takes %1, so the next value has to be %2.
+        let misnumbered = block(LocalIdentifier::new("0").unwrap(), vec![
+            CallInstruction::new_to_function(&callee(), vec![]).unwrap().into(),
+            Variable::new(LocalIdentifier::new("1").unwrap(), CallInstruction::new_to_function(&callee(), vec![]).unwrap().into()).into()
+        ]);
+        assert_eq!(function.add_instruction_block(misnumbered).err(), Some(NumberingError::OutOfSequence { name: "1".to_string(), expected: 2 }));
+        let numbered = block(LocalIdentifier::new("0").unwrap(), vec![
+            CallInstruction::new_to_function(&callee(), vec![]).unwrap().into(),
+            Variable::new(LocalIdentifier::new("2").unwrap(), CallInstruction::new_to_function(&callee(), vec![]).unwrap().into()).into()
+        ]);
+        assert!(function.add_instruction_block(numbered).is_ok());
+    }
+
+    #[test]
+    fn arguments_are_numbered_first() {
+        let arguments = vec![Argument::new(IntegerType(8), LocalIdentifier::new("1").unwrap())];
+        assert_eq!(Function::new(GlobalIdentifier::new("f").unwrap(), crate::types::Type::Void, arguments).err(), Some(NumberingError::OutOfSequence { name: "1".to_string(), expected: 0 }));
+    }
+
+    #[test]
+    fn names_are_defined_once() {
+        let arguments = vec![Argument::new(IntegerType(8), LocalIdentifier::new("x").unwrap()), Argument::new(IntegerType(8), LocalIdentifier::new("x").unwrap())];
+        assert_eq!(Function::new(GlobalIdentifier::new("f").unwrap(), crate::types::Type::Void, arguments).err(), Some(NumberingError::DuplicateName("x".to_string())));
+        let arguments = vec![Argument::new(IntegerType(8), LocalIdentifier::new("x").unwrap())];
+        let mut function = Function::new(GlobalIdentifier::new("f").unwrap(), crate::types::Type::Void, arguments).unwrap();
+        // Reserving a block's name ahead of time doesn't count as defining it.
+        assert!(function.reserve_local("entry"));
+        let clash = block(LocalIdentifier::new("entry").unwrap(), vec![
+            Variable::new(LocalIdentifier::new("x").unwrap(), CallInstruction::new_to_function(&callee(), vec![]).unwrap().into()).into()
+        ]);
+        assert_eq!(function.add_instruction_block(clash).err(), Some(NumberingError::DuplicateName("x".to_string())));
+        function.add_instruction_block(block(LocalIdentifier::new("entry").unwrap(), vec![])).unwrap();
+        assert_eq!(function.add_instruction_block(block(LocalIdentifier::new("entry").unwrap(), vec![])).err(), Some(NumberingError::DuplicateName("entry".to_string())));
+    }
+
+    #[test]
+    fn calls_are_checked_against_the_function_type() {
+        let int = |name| Value::from(Reference::new(LocalIdentifier::new(name).unwrap(), IntegerType(32)));
+        let byte = || Value::from(Reference::new(LocalIdentifier::new("b").unwrap(), IntegerType(8)));
+        let printf = Function::new_variadic(GlobalIdentifier::new("printf").unwrap(), IntegerType(32).into(), vec![Argument::new(IntegerType(32), LocalIdentifier::new("x").unwrap())]).unwrap();
+        assert_eq!(CallInstruction::new_to_function(&printf, vec![]).err(), Some(CallError::ArgumentCount { expected: 1, variadic: true, found: 0 }));
+        assert_eq!(CallInstruction::new_to_function(&printf, vec![byte()]).err(), Some(CallError::ArgumentType { index: 0, expected: IntegerType(32).into(), found: IntegerType(8).into() }));
+        // Arguments past the parameters of a variadic function can have any type.
+        assert_eq!(CallInstruction::new_to_function(&printf, vec![int("a"), byte()]).unwrap().to_string(), "call i32 (i32, ...) @printf(i32 %a, i8 %b)");
+        assert_eq!(CallInstruction::new_to_function(&callee(), vec![int("a")]).err(), Some(CallError::ArgumentCount { expected: 0, variadic: false, found: 1 }));
+        assert!(matches!(CallInstruction::new(FunctionType::from(&callee()), int("a"), vec![]), Err(CallError::InvalidCallee { .. })));
+        let other = Function::new(GlobalIdentifier::new("h").unwrap(), crate::types::Type::Void, vec![]).unwrap();
+        assert!(matches!(CallInstruction::new(FunctionType::from(&callee()), Reference::from(&other).into(), vec![]), Err(CallError::InvalidCallee { .. })));
+    }
+
+    #[test]
+    fn opaque_pointers_match_typed_pointers() {
+        use crate::types::first_class::single_value::PointerType;
+        let list = |_type: PointerType| Value::from(Reference::new(LocalIdentifier::new("list").unwrap(), _type));
+        assert_eq!(super::intrinsic::call_va_start(list(PointerType::new_opaque())).unwrap().to_string(), "call void @llvm.va_start(ptr %list)");
+        assert!(super::intrinsic::call_va_end(list(PointerType::new(IntegerType(8)))).is_ok());
+        assert!(matches!(super::intrinsic::call_va_start(list(PointerType::new_opaque_with_address_space(1))), Err(CallError::ArgumentType { index: 0, .. })));
+        assert!(matches!(super::intrinsic::call_va_start(list(PointerType::new(IntegerType(32)))), Err(CallError::ArgumentType { index: 0, .. })));
+    }
+
+    #[test]
+    fn invokes_continue_at_labels() {
+        let int = || Value::from(Reference::new(LocalIdentifier::new("a").unwrap(), IntegerType(32)));
+        let normal = InstructionBlock::new(LocalIdentifier::new("normal").unwrap(), vec![]);
+        let label = || Value::from(Reference::from(&normal));
+        let id = Function::new(GlobalIdentifier::new("id").unwrap(), IntegerType(32).into(), vec![Argument::new(IntegerType(32), LocalIdentifier::new("x").unwrap())]).unwrap();
+        let invoke = InvokeInstruction::new_to_function(&id, vec![int()], label(), label()).unwrap();
+        assert_eq!(invoke.to_string(), "invoke i32 (i32) @id( i32 %a ) to label %normal unwind label %normal");
+        assert_eq!(InvokeInstruction::new_to_function(&callee(), vec![], label(), int()).err(), Some(CallError::InvalidDestination(IntegerType(32).into())));
+        assert_eq!(CallBranchInstruction::new_to_function(&callee(), vec![], label(), vec![label(), int()]).err(), Some(CallError::InvalidDestination(IntegerType(32).into())));
+        assert!(matches!(InvokeInstruction::new_to_function(&callee(), vec![int()], label(), label()), Err(CallError::ArgumentCount { .. })));
+    }
+}
@@ -1,43 +1,125 @@
+use std::borrow::Cow;
 use std::fmt::Display;
 
-use crate::identifier::{GlobalIdentifier, Identifiable};
+use crate::identifier::{GlobalIdentifier, Identifiable, QuotedString};
 use crate::constant::Constant;
+use crate::metadata::MetadataAttachment;
 use crate::module::{LinkageType, RuntimePreemptionSpecifier, VisibilityStyle, DLLStorageClass, UnnamedAddress};
 use crate::types::ReturnType;
-use crate::types::first_class::Type;
+use crate::types::interner::TypeId;
 
 use super::ThreadLocalStorageModel;
 
+#[derive(Debug)]
 pub struct GlobalVariable<'s> {
     identifier: GlobalIdentifier<'s>,
     linkage: Option<LinkageType>,
     preemtion_specifier: RuntimePreemptionSpecifier,
     visibility: VisibilityStyle,
     dll_storage_class: Option<DLLStorageClass>,
-    thread_local: ThreadLocalStorageModel,
+    thread_local: Option<ThreadLocalStorageModel>,
     unnamed_address: Option<UnnamedAddress>,
     address_space: usize,
     externally_initialized: bool,
     global_variable_type: GlobalVariableType,
-    return_type: Type,
+    return_type: TypeId,
     initializer_constant: Option<Constant<'s>>,
-    section_name: Option<&'s str>,
-    partition_name: Option<&'s str>,
+    section_name: Option<Cow<'s, str>>,
+    partition_name: Option<Cow<'s, str>>,
     //comdats: Vec<Comdat>,
     allign: usize,
-    //metadata: Vec<Metadata>
+    metadata: Vec<MetadataAttachment<'s>>
+}
+
+impl<'s> GlobalVariable<'s> {
+    pub fn new(identifier: GlobalIdentifier<'s>, global_variable_type: GlobalVariableType, return_type: impl Into<TypeId>, initializer_constant: Option<Constant<'s>>) -> Self {
+        GlobalVariable {
+            identifier,
+            linkage: None,
+            preemtion_specifier: RuntimePreemptionSpecifier::Preemptable,
+            visibility: VisibilityStyle::Default,
+            dll_storage_class: None,
+            thread_local: None,
+            unnamed_address: None,
+            address_space: 0,
+            externally_initialized: false,
+            global_variable_type,
+            return_type: return_type.into(),
+            initializer_constant,
+            section_name: None,
+            partition_name: None,
+            allign: 0,
+            metadata: Vec::new()
+        }
+    }
+    pub fn with_linkage(mut self, linkage: LinkageType) -> Self {
+        self.linkage = Some(linkage);
+        self
+    }
+    pub fn with_preemption_specifier(mut self, preemption_specifier: RuntimePreemptionSpecifier) -> Self {
+        self.preemtion_specifier = preemption_specifier;
+        self
+    }
+    pub fn with_visibility(mut self, visibility: VisibilityStyle) -> Self {
+        self.visibility = visibility;
+        self
+    }
+    pub fn with_dll_storage_class(mut self, dll_storage_class: DLLStorageClass) -> Self {
+        self.dll_storage_class = Some(dll_storage_class);
+        self
+    }
+    pub fn with_thread_local(mut self, thread_local: ThreadLocalStorageModel) -> Self {
+        self.thread_local = Some(thread_local);
+        self
+    }
+    pub fn with_unnamed_address(mut self, unnamed_address: UnnamedAddress) -> Self {
+        self.unnamed_address = Some(unnamed_address);
+        self
+    }
+    pub fn with_address_space(mut self, address_space: usize) -> Self {
+        self.address_space = address_space;
+        self
+    }
+    pub fn identifier(&self) -> &GlobalIdentifier<'s> {
+        &self.identifier
+    }
+    pub fn address_space(&self) -> usize {
+        self.address_space
+    }
+    pub fn with_externally_initialized(mut self) -> Self {
+        self.externally_initialized = true;
+        self
+    }
+    pub fn with_section_name(mut self, section_name: impl Into<Cow<'s, str>>) -> Self {
+        self.section_name = Some(section_name.into());
+        self
+    }
+    pub fn with_partition_name(mut self, partition_name: impl Into<Cow<'s, str>>) -> Self {
+        self.partition_name = Some(partition_name.into());
+        self
+    }
+    pub fn with_align(mut self, align: usize) -> Self {
+        self.allign = align;
+        self
+    }
+    pub fn with_metadata(mut self, attachment: MetadataAttachment<'s>) -> Self {
+        self.metadata.push(attachment);
+        self
+    }
+    pub fn metadata(&self) -> &[MetadataAttachment<'s>] {
+        &self.metadata
+    }
 }
 
 impl<'s> Display for GlobalVariable<'s> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{} = ", self.identifier))?;
         if let Some(linkage) = &self.linkage { f.write_fmt(format_args!("{} ", linkage))?; }
+        else if self.initializer_constant.is_none() { f.write_fmt(format_args!("{} ", LinkageType::External))?; }
         if let RuntimePreemptionSpecifier::Local = self.preemtion_specifier { f.write_fmt(format_args!("{} ", self.preemtion_specifier))?; }
-        if let VisibilityStyle::Default = self.visibility { f.write_fmt(format_args!("{} ", self.visibility))?; }
+        if let VisibilityStyle::Default = self.visibility {} else { f.write_fmt(format_args!("{} ", self.visibility))?; }
         if let Some(dll_storage_class) = &self.dll_storage_class { f.write_fmt(format_args!("{} ", dll_storage_class))?; }
-        if let ThreadLocalStorageModel::GeneralDynamic = self.thread_local {} else {
-            f.write_fmt(format_args!("{} ", self.thread_local))?;
-        }
+        if let Some(thread_local) = &self.thread_local { f.write_fmt(format_args!("{} ", thread_local))?; }
         if let Some(unnamed_address) = &self.unnamed_address { f.write_fmt(format_args!("{} ", unnamed_address))?; }
         if self.address_space != 0 { f.write_fmt(format_args!("addrspace({}) ", self.address_space))?; }
         if self.externally_initialized { f.write_str("externally_initialized ")?; }
@@ -46,21 +128,23 @@ impl<'s> Display for GlobalVariable<'s> {
         if let Some(initializer_constant) = &self.initializer_constant {
             f.write_fmt(format_args!(" {}", initializer_constant))?;
         }
-        if let Some(section_name) = &self.section_name { f.write_fmt(format_args!(", section \"{}\"", section_name))?; }
-        if let Some(partition_name) = &self.partition_name { f.write_fmt(format_args!(", partition \"{}\"", partition_name))?; }
+        if let Some(section_name) = &self.section_name { f.write_fmt(format_args!(", section {}", QuotedString(section_name)))?; }
+        if let Some(partition_name) = self.partition_name.as_deref().filter(|_| crate::printer::supports("partition", Some(9), None)) { f.write_fmt(format_args!(", partition {}", QuotedString(partition_name)))?; }
         //comdats
         if self.allign != 0 { f.write_fmt(format_args!(", align {}", self.allign))?; }
-        //metadata
+        for attachment in &self.metadata {
+            f.write_fmt(format_args!(", {}", attachment))?;
+        }
         Ok(())
     }
 }
 
-impl<'s> Identifiable for GlobalVariable<'s> {
-    fn id(&self) -> crate::identifier::Identifier { self.identifier.clone().into() }
+impl<'s> Identifiable<'s> for GlobalVariable<'s> {
+    fn id(&self) -> crate::identifier::Identifier<'s> { self.identifier.clone().into() }
 }
 
 impl<'s> ReturnType for GlobalVariable<'s> {
-    fn return_type(&self) -> Type { self.return_type.clone() }
+    fn return_type(&self) -> TypeId { self.return_type }
 }
 
 #[derive(Debug)]
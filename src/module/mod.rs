@@ -1,23 +1,359 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use core::fmt::{Display, Debug};
 use crate::IRElement;
+use crate::identifier::{GlobalIdentifier, Identifiable, NameSupply, ParseError};
+use crate::types::ReturnType;
+use crate::types::first_class::aggregate::NamedStructType;
 
 pub mod global_variable;
+pub mod alias;
 pub mod function;
+pub mod data_layout;
+pub mod target_triple;
 
-#[repr(transparent)]
-pub struct Module<'s>(Vec<ModuleElement<'s>>);
+/// An LLVM module, printed as IR through `Display`.
+///
+/// # Panics
+///
+/// `to_string` panics if the module can't be printed as valid IR, e.g. when an unnamed local is
+/// used outside of the function defining it or a distinct metadata node never gets its operands,
+/// as `Display` has no way to tell what went wrong. `printer().print()` returns a `PrintError` instead.
+#[derive(Debug, Default)]
+pub struct Module<'s> {
+    source_filename: Option<Cow<'s, str>>,
+    target_triple: Option<target_triple::TargetTriple<'s>>,
+    data_layout: Option<data_layout::DataLayout>,
+    elements: Vec<ModuleElement<'s>>,
+    global_names: NameSupply,
+    type_names: NameSupply,
+    /// The globals made by `intern_string`, by their bytes.
+    strings: HashMap<Vec<u8>, crate::constant::address::GlobalAddressConstant<'s>>
+}
 
-#[derive(Debug, Clone, PartialEq)]
+impl<'s> Module<'s> {
+    pub fn new() -> Self {
+        Module { source_filename: None, target_triple: None, data_layout: None, elements: Vec::new(), global_names: NameSupply::new(), type_names: NameSupply::new(), strings: HashMap::new() }
+    }
+    /// Creates a module for `target_triple`, using its preset data layout if there is one.
+    pub fn new_for_target(target_triple: target_triple::TargetTriple<'s>) -> Self {
+        let data_layout = target_triple.data_layout();
+        Module { source_filename: None, target_triple: Some(target_triple), data_layout, elements: Vec::new(), global_names: NameSupply::new(), type_names: NameSupply::new(), strings: HashMap::new() }
+    }
+    pub fn set_source_filename(&mut self, source_filename: impl Into<Cow<'s, str>>) -> &mut Self {
+        self.source_filename = Some(source_filename.into());
+        self
+    }
+    pub fn set_target_triple(&mut self, target_triple: target_triple::TargetTriple<'s>) -> &mut Self {
+        self.target_triple = Some(target_triple);
+        self
+    }
+    pub fn set_data_layout(&mut self, data_layout: data_layout::DataLayout) -> &mut Self {
+        self.data_layout = Some(data_layout);
+        self
+    }
+    pub fn source_filename(&self) -> Option<&str> {
+        self.source_filename.as_deref()
+    }
+    pub fn target_triple(&self) -> Option<&target_triple::TargetTriple<'s>> {
+        self.target_triple.as_ref()
+    }
+    pub fn data_layout(&self) -> Option<&data_layout::DataLayout> {
+        self.data_layout.as_ref()
+    }
+    /// Fails if a global variable, alias or function of this module has the name of `element` already,
+    /// or a named struct of this module has the name of the named struct `element` defines.
+    pub fn add(&mut self, element: impl Into<ModuleElement<'s>>) -> Result<&mut Self, DuplicateNameError> {
+        let element = element.into();
+        if let ModuleElement::TypeDefinition(it) = &element {
+            let named_struct = it.named_struct();
+            if !self.type_names.define(named_struct.name_bytes()) { return Err(DuplicateNameError::Type(named_struct.name().into_owned())); }
+        }
+        let name = match &element {
+            ModuleElement::TypeDefinition(_) => None,
+            ModuleElement::GlobalVariable(it) => it.id().name_bytes().map(<[u8]>::to_vec),
+            ModuleElement::Alias(it) => it.id().name_bytes().map(<[u8]>::to_vec),
+            ModuleElement::Function(it) => it.id().name_bytes().map(<[u8]>::to_vec),
+            ModuleElement::NamedMetadata(_) => None
+        };
+        if let Some(name) = name {
+            if !self.global_names.define(&name) { return Err(DuplicateNameError::Global(String::from_utf8_lossy(&name).into_owned())); }
+        }
+        self.elements.push(element);
+        Ok(self)
+    }
+    /// A global name based on `base` that no global variable or function of this module has.
+    pub fn fresh_global(&mut self, base: &str) -> Result<GlobalIdentifier<'s>, ParseError> {
+        GlobalIdentifier::new(self.global_names.fresh(base))
+    }
+    /// Keeps `fresh_global` from handing out `name`, e.g. for a global added later.
+    /// Returns whether it was free before.
+    pub fn reserve_global(&mut self, name: &str) -> bool {
+        self.global_names.reserve(name)
+    }
+    pub fn add_type_definition(&mut self, type_definition: TypeDefinition) -> Result<&mut Self, DuplicateNameError> {
+        self.add(type_definition)
+    }
+    /// Registers a named struct so that its definition is printed with the module.
+    pub fn add_named_struct(&mut self, named_struct: NamedStructType) -> Result<&mut Self, DuplicateNameError> {
+        self.add(named_struct)
+    }
+    /// Declares an opaque named struct whose body can be set later through the returned handle.
+    /// Fails if `name` is empty or contains a NUL byte, or a named struct of this module has `name` already.
+    pub fn declare_named_struct(&mut self, name: &str) -> Result<NamedStructType, crate::Error> {
+        let named_struct = NamedStructType::new_opaque(name)?;
+        self.add(named_struct.clone())?;
+        Ok(named_struct)
+    }
+    pub fn named_struct(&self, name: impl AsRef<[u8]>) -> Option<&NamedStructType> {
+        self.type_definitions().map(|it| it.named_struct()).find(|it| it.name_bytes() == name.as_ref())
+    }
+    /// Parses a type, resolving `%name` references against the named structs of this module.
+    pub fn parse_type(&self, s: &str) -> Result<crate::types::Type, crate::types::parse::TypeParseError> {
+        let resolver = |name: &[u8]| self.named_struct(name).cloned();
+        crate::types::parse::TypeParser::new(s).with_resolver(&resolver).parse()
+    }
+    pub fn add_global_variable(&mut self, global_variable: global_variable::GlobalVariable<'s>) -> Result<&mut Self, DuplicateNameError> {
+        self.add(global_variable)
+    }
+    pub fn add_alias(&mut self, alias: alias::Alias<'s>) -> Result<&mut Self, DuplicateNameError> {
+        self.add(alias)
+    }
+    pub fn add_function(&mut self, function: function::Function<'s>) -> Result<&mut Self, DuplicateNameError> {
+        self.add(function)
+    }
+    pub fn add_named_metadata(&mut self, named_metadata: crate::metadata::NamedMetadata<'s>) -> Result<&mut Self, DuplicateNameError> {
+        self.add(named_metadata)
+    }
+    /// Adds `string` as a `private unnamed_addr constant` global, or finds the one added before
+    /// for the same bytes, and returns the address of that global, an `i8` array pointer.
+    pub fn intern_string(&mut self, string: crate::constant::complex::ByteStringConstant<'s>) -> crate::constant::address::GlobalAddressConstant<'s> {
+        if let Some(address) = self.strings.get(string.bytes()) { return address.clone(); }
+        let identifier = self.fresh_global(".str").expect("`.str` is a valid global name");
+        let bytes = string.bytes().to_vec();
+        let global = global_variable::GlobalVariable::new(identifier, global_variable::GlobalVariableType::Constant, string.return_type(), Some(string.into()))
+            .with_linkage(LinkageType::Private)
+            .with_unnamed_address(UnnamedAddress::NonLocal)
+            .with_align(1);
+        let address = crate::constant::address::GlobalAddressConstant::from(&global);
+        self.add(global).expect("fresh global names are free");
+        self.strings.insert(bytes, address.clone());
+        return address;
+    }
+    /// A printer that can target a specific LLVM version and reports what can't be printed.
+    /// `Display` prints without a version and fails on anything that can't be printed,
+    /// so `to_string` panics where `print` returns an error.
+    pub fn printer(&self) -> crate::printer::ModulePrinter<'_, 's> {
+        crate::printer::ModulePrinter::new(self)
+    }
+    pub fn elements(&self) -> &[ModuleElement<'s>] {
+        &self.elements
+    }
+    pub fn type_definitions(&self) -> impl Iterator<Item = &TypeDefinition> {
+        self.elements.iter().filter_map(|it| match it {
+            ModuleElement::TypeDefinition(it) => Some(it),
+            _ => None
+        })
+    }
+    pub fn global_variables(&self) -> impl Iterator<Item = &global_variable::GlobalVariable<'s>> {
+        self.elements.iter().filter_map(|it| match it {
+            ModuleElement::GlobalVariable(it) => Some(it),
+            _ => None
+        })
+    }
+    pub fn aliases(&self) -> impl Iterator<Item = &alias::Alias<'s>> {
+        self.elements.iter().filter_map(|it| match it {
+            ModuleElement::Alias(it) => Some(it),
+            _ => None
+        })
+    }
+    pub fn functions(&self) -> impl Iterator<Item = &function::Function<'s>> {
+        self.elements.iter().filter_map(|it| match it {
+            ModuleElement::Function(it) => Some(it),
+            _ => None
+        })
+    }
+    pub fn named_metadata(&self) -> impl Iterator<Item = &crate::metadata::NamedMetadata<'s>> {
+        self.elements.iter().filter_map(|it| match it {
+            ModuleElement::NamedMetadata(it) => Some(it),
+            _ => None
+        })
+    }
+}
+
+/// A definition whose name is taken in the module already.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DuplicateNameError {
+    /// Global variables, aliases and functions share one namespace.
+    Global(String),
+    /// Named structs have a namespace of their own.
+    Type(String)
+}
+
+impl Display for DuplicateNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Global(name) => f.write_fmt(format_args!("@{} is defined more than once", crate::identifier::Name(name.as_bytes()))),
+            Self::Type(name) => f.write_fmt(format_args!("%{} is defined more than once", crate::identifier::Name(name.as_bytes())))
+        }
+    }
+}
+
+impl std::error::Error for DuplicateNameError {}
+
+impl<'s> IRElement for Module<'s> {}
+
+// Modules with owned names can be built on one thread and printed on another.
+const _: fn() = || {
+    fn assert_send<T: Send + 'static>() {}
+    assert_send::<Module<'static>>();
+};
+
+impl<'s> Display for Module<'s> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let attachments = self.global_variables().flat_map(global_variable::GlobalVariable::metadata)
+            .chain(self.functions().flat_map(function::Function::all_metadata))
+            .map(crate::metadata::MetadataAttachment::node);
+        let nodes = crate::metadata::number_nodes(attachments.chain(self.named_metadata().flat_map(|it| it.nodes())));
+        crate::metadata::with_node_numbering(&nodes, || self.fmt_numbered(f, &nodes))
+    }
+}
+
+impl<'s> Module<'s> {
+    fn fmt_numbered(&self, f: &mut std::fmt::Formatter<'_>, nodes: &[crate::metadata::MetadataNode]) -> std::fmt::Result {
+        let mut separate = false;
+        if let Some(source_filename) = &self.source_filename {
+            f.write_fmt(format_args!("source_filename = {}\n", crate::identifier::QuotedString(source_filename)))?;
+            separate = true;
+        }
+        if let Some(data_layout) = &self.data_layout {
+            f.write_fmt(format_args!("target datalayout = \"{}\"\n", data_layout))?;
+            separate = true;
+        }
+        if let Some(target_triple) = &self.target_triple {
+            f.write_fmt(format_args!("target triple = \"{}\"\n", target_triple))?;
+            separate = true;
+        }
+        if separate { f.write_str("\n")?; separate = false; }
+        for type_definition in self.type_definitions() {
+            f.write_fmt(format_args!("{}\n", type_definition))?;
+            separate = true;
+        }
+        if separate { f.write_str("\n")?; separate = false; }
+        for global_variable in self.global_variables() {
+            f.write_fmt(format_args!("{}\n", global_variable))?;
+            separate = true;
+        }
+        for alias in self.aliases() {
+            f.write_fmt(format_args!("{}\n", alias))?;
+            separate = true;
+        }
+        for function in self.functions() {
+            if separate { f.write_str("\n")?; }
+            Display::fmt(function, f)?;
+            separate = true;
+        }
+        if separate && self.named_metadata().next().is_some() { f.write_str("\n")?; }
+        for named_metadata in self.named_metadata() {
+            f.write_fmt(format_args!("{}\n", named_metadata))?;
+            separate = true;
+        }
+        if separate && !nodes.is_empty() { f.write_str("\n")?; }
+        for (number, node) in nodes.iter().enumerate() {
+            f.write_fmt(format_args!("!{} = {}\n", number, node.definition()))?;
+        }
+        Ok(())
+    }
+}
+
+// Functions carry their numbering and are much larger than the other elements.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
 pub enum ModuleElement<'s> {
-    GlobalVariable(global_variable::GlobalVariable<'s>)
+    TypeDefinition(TypeDefinition),
+    GlobalVariable(global_variable::GlobalVariable<'s>),
+    Alias(alias::Alias<'s>),
+    Function(function::Function<'s>),
+    NamedMetadata(crate::metadata::NamedMetadata<'s>)
+}
+
+impl<'s> From<TypeDefinition> for ModuleElement<'s> {
+    fn from(element: TypeDefinition) -> Self {
+        ModuleElement::TypeDefinition(element)
+    }
+}
+
+impl<'s> From<global_variable::GlobalVariable<'s>> for ModuleElement<'s> {
+    fn from(element: global_variable::GlobalVariable<'s>) -> Self {
+        ModuleElement::GlobalVariable(element)
+    }
+}
+
+impl<'s> From<alias::Alias<'s>> for ModuleElement<'s> {
+    fn from(element: alias::Alias<'s>) -> Self {
+        ModuleElement::Alias(element)
+    }
+}
+
+impl<'s> From<function::Function<'s>> for ModuleElement<'s> {
+    fn from(element: function::Function<'s>) -> Self {
+        ModuleElement::Function(element)
+    }
+}
+
+impl<'s> From<crate::metadata::NamedMetadata<'s>> for ModuleElement<'s> {
+    fn from(element: crate::metadata::NamedMetadata<'s>) -> Self {
+        ModuleElement::NamedMetadata(element)
+    }
 }
 
 impl<'s> IRElement for ModuleElement<'s> {}
 
 impl<'s> Display for ModuleElement<'s> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::GlobalVariable(it) => it.fmt(f)
+        (match self {
+            Self::TypeDefinition(it) => it as &dyn Display,
+            Self::GlobalVariable(it) => it as &dyn Display,
+            Self::Alias(it) => it as &dyn Display,
+            Self::Function(it) => it as &dyn Display,
+            Self::NamedMetadata(it) => it as &dyn Display
+        }).fmt(f)
+    }
+}
+
+/// The `%name = type ...` definition of a named struct.
+#[derive(Debug, Clone, PartialEq)]
+#[repr(transparent)]
+pub struct TypeDefinition(NamedStructType);
+
+impl TypeDefinition {
+    pub fn new(named_struct: NamedStructType) -> Self {
+        TypeDefinition(named_struct)
+    }
+    pub fn named_struct(&self) -> &NamedStructType {
+        &self.0
+    }
+}
+
+impl From<NamedStructType> for TypeDefinition {
+    fn from(named_struct: NamedStructType) -> Self {
+        TypeDefinition(named_struct)
+    }
+}
+
+impl From<NamedStructType> for ModuleElement<'_> {
+    fn from(named_struct: NamedStructType) -> Self {
+        ModuleElement::TypeDefinition(named_struct.into())
+    }
+}
+
+impl IRElement for TypeDefinition {}
+
+impl Display for TypeDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.body() {
+            Some(body) => f.write_fmt(format_args!("{} = type {}", self.0, body)),
+            None => f.write_fmt(format_args!("{} = type opaque", self.0))
         }
     }
 }
@@ -84,6 +420,8 @@ impl Display for CallingConvention {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             CallingConvention::Numbered(n) => f.write_fmt(format_args!("cc {}", n)),
+            CallingConvention::Tail if !crate::printer::supports("tailcc", Some(9), None) => f.write_str("tailcc"),
+            CallingConvention::SwiftTail if !crate::printer::supports("swifttailcc", Some(13), None) => f.write_str("swifttailcc"),
             _ => f.write_str(match self {
                 CallingConvention::C => "ccc",
                 CallingConvention::Fast => "fastcc",
@@ -157,10 +495,10 @@ impl IRElement for ThreadLocalStorageModel {}
 impl Display for ThreadLocalStorageModel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
-            ThreadLocalStorageModel::LocalDynamic => "localdynamic",
-            ThreadLocalStorageModel::InitialExecution => "initialexec",
-            ThreadLocalStorageModel::LocalExecution => "localexec",
-            ThreadLocalStorageModel::GeneralDynamic => ""
+            ThreadLocalStorageModel::LocalDynamic => "thread_local(localdynamic)",
+            ThreadLocalStorageModel::InitialExecution => "thread_local(initialexec)",
+            ThreadLocalStorageModel::LocalExecution => "thread_local(localexec)",
+            ThreadLocalStorageModel::GeneralDynamic => "thread_local"
         })
     }
 }
@@ -195,9 +533,54 @@ impl IRElement for UnnamedAddress {}
 impl Display for UnnamedAddress {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
-            UnnamedAddress::Local => "local_unnamed_address",
-            UnnamedAddress::NonLocal => "unnamed_address"
+            UnnamedAddress::Local => "local_unnamed_addr",
+            UnnamedAddress::NonLocal => "unnamed_addr"
         })
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::first_class::single_value::IntegerType;
+
+    #[test]
+    fn globals_are_defined_once() {
+        let mut module = Module::new();
+        let global = || global_variable::GlobalVariable::new(GlobalIdentifier::new("x").unwrap(), global_variable::GlobalVariableType::Global, IntegerType(32), None);
+        assert!(module.reserve_global("x"));
+        module.add_global_variable(global()).unwrap();
+        assert_eq!(module.add_global_variable(global()).err(), Some(DuplicateNameError::Global("x".to_string())));
+        let function = function::Function::new(GlobalIdentifier::new("x").unwrap(), crate::types::Type::Void, vec![]).unwrap();
+        assert_eq!(module.add_function(function).err(), Some(DuplicateNameError::Global("x".to_string())));
+        assert_eq!(module.global_variables().count(), 1);
+    }
+
+    #[test]
+    fn named_structs_are_defined_once() {
+        let mut module = Module::new();
+        let node = module.declare_named_struct("node").unwrap();
+        assert_eq!(module.declare_named_struct("node").err(), Some(DuplicateNameError::Type("node".to_string()).into()));
+        assert_eq!(module.add_named_struct(node).err(), Some(DuplicateNameError::Type("node".to_string())));
+        assert!(matches!(module.declare_named_struct(""), Err(crate::Error::Identifier(_))));
+        // Structs and globals don't share names.
+        let global = global_variable::GlobalVariable::new(GlobalIdentifier::new("node").unwrap(), global_variable::GlobalVariableType::Global, IntegerType(32), None);
+        module.add_global_variable(global).unwrap();
+        assert_eq!(module.type_definitions().count(), 1);
+    }
+
+    #[test]
+    fn strings_are_interned_once() {
+        use crate::constant::complex::ByteStringConstant;
+        let mut module = Module::new();
+        let hello = module.intern_string(ByteStringConstant::from_text("hello", true));
+        assert_eq!(format!("{} {}", hello.return_type(), hello), "[ 6 x i8 ]* @.str");
+        let again = module.intern_string(ByteStringConstant::new(&b"hello\0"[..], false));
+        assert_eq!(again.to_string(), "@.str");
+        let unterminated = module.intern_string(ByteStringConstant::from_text("hello", false));
+        assert_eq!(format!("{} {}", unterminated.return_type(), unterminated), "[ 5 x i8 ]* @.str.1");
+        assert_eq!(module.global_variables().count(), 2);
+        assert!(module.to_string().contains("@.str = private unnamed_addr constant [ 6 x i8 ] c\"hello\\00\", align 1\n"));
+        assert!(module.printer().with_opaque_pointers(true).print().is_ok());
+    }
+}
@@ -0,0 +1,91 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+
+use crate::IRElement;
+
+use super::data_layout::DataLayout;
+
+/// A target triple of the form `<arch>-<vendor>-<os>[-<environment>]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct TargetTriple<'s>(Cow<'s, str>);
+
+impl TargetTriple<'static> {
+    pub const X86_64_LINUX_GNU: TargetTriple<'static> = TargetTriple(Cow::Borrowed("x86_64-unknown-linux-gnu"));
+    pub const X86_64_APPLE_MACOS: TargetTriple<'static> = TargetTriple(Cow::Borrowed("x86_64-apple-macosx"));
+    pub const X86_64_WINDOWS_MSVC: TargetTriple<'static> = TargetTriple(Cow::Borrowed("x86_64-pc-windows-msvc"));
+    pub const I686_LINUX_GNU: TargetTriple<'static> = TargetTriple(Cow::Borrowed("i686-unknown-linux-gnu"));
+    pub const AARCH64_LINUX_GNU: TargetTriple<'static> = TargetTriple(Cow::Borrowed("aarch64-unknown-linux-gnu"));
+    pub const AARCH64_APPLE_MACOS: TargetTriple<'static> = TargetTriple(Cow::Borrowed("arm64-apple-macosx"));
+    pub const AARCH64_WINDOWS_MSVC: TargetTriple<'static> = TargetTriple(Cow::Borrowed("aarch64-pc-windows-msvc"));
+    pub const WASM32_UNKNOWN_UNKNOWN: TargetTriple<'static> = TargetTriple(Cow::Borrowed("wasm32-unknown-unknown"));
+    pub const WASM32_WASI: TargetTriple<'static> = TargetTriple(Cow::Borrowed("wasm32-unknown-wasi"));
+}
+
+impl<'s> TargetTriple<'s> {
+    pub fn new(triple: impl Into<Cow<'s, str>>) -> Self {
+        TargetTriple(triple.into())
+    }
+    fn component(&self, index: usize) -> Option<&str> {
+        self.0.split('-').nth(index)
+    }
+    pub fn architecture(&self) -> &str {
+        self.component(0).unwrap_or("")
+    }
+    pub fn vendor(&self) -> Option<&str> {
+        self.component(1)
+    }
+    pub fn operating_system(&self) -> Option<&str> {
+        self.component(2)
+    }
+    pub fn environment(&self) -> Option<&str> {
+        self.component(3)
+    }
+    /// The data layout clang uses for this triple, if it is one of the known presets.
+    pub fn data_layout(&self) -> Option<DataLayout> {
+        let os = self.operating_system().unwrap_or("");
+        let apple = self.vendor() == Some("apple") || os.starts_with("darwin") || os.starts_with("macos") || os.starts_with("ios");
+        let windows = os.starts_with("windows") || os.starts_with("win32");
+        let layout = match self.architecture() {
+            "x86_64" | "amd64" => {
+                if apple { "e-m:o-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128" }
+                else if windows { "e-m:w-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128" }
+                else { "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128" }
+            }
+            "i386" | "i486" | "i586" | "i686" => {
+                if apple { "e-m:o-p:32:32-p270:32:32-p271:32:32-p272:64:64-i128:128-f64:32:64-f80:128-n8:16:32-S128" }
+                else if windows { "e-m:x-p:32:32-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:32-n8:16:32-a:0:32-S32" }
+                else { "e-m:e-p:32:32-p270:32:32-p271:32:32-p272:64:64-i128:128-f64:32:64-f80:32-n8:16:32-S128" }
+            }
+            "aarch64" | "arm64" => {
+                if apple { "e-m:o-i64:64-i128:128-n32:64-S128" }
+                else if windows { "e-m:w-p:64:64-i32:32-i64:64-i128:128-n32:64-S128" }
+                else { "e-m:e-i8:8:32-i16:16:32-i64:64-i128:128-n32:64-S128" }
+            }
+            "wasm32" => "e-m:e-p:32:32-p10:8:8-p20:8:8-i64:64-n32:64-S128",
+            "wasm64" => "e-m:e-p:64:64-p10:8:8-p20:8:8-i64:64-n32:64-S128",
+            _ => return None
+        };
+        Some(layout.parse().expect("preset layouts parse"))
+    }
+}
+
+impl<'s> From<&'s str> for TargetTriple<'s> {
+    fn from(triple: &'s str) -> Self {
+        TargetTriple(Cow::Borrowed(triple))
+    }
+}
+
+impl From<String> for TargetTriple<'static> {
+    fn from(triple: String) -> Self {
+        TargetTriple(Cow::Owned(triple))
+    }
+}
+
+impl<'s> IRElement for TargetTriple<'s> {}
+
+impl<'s> Display for TargetTriple<'s> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,261 @@
+use std::cell::RefCell;
+use std::fmt::Display;
+
+use crate::module::Module;
+
+/// A major LLVM release that printed IR should be accepted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct LLVMVersion(pub u32);
+
+impl LLVMVersion {
+    pub const LLVM_12: LLVMVersion = LLVMVersion(12);
+    pub const LLVM_15: LLVMVersion = LLVMVersion(15);
+    pub const LLVM_17: LLVMVersion = LLVMVersion(17);
+    pub const LLVM_18: LLVMVersion = LLVMVersion(18);
+
+    /// LLVM 15 switched the textual IR to opaque pointers; 17 removed typed pointers.
+    pub fn uses_opaque_pointers(&self) -> bool {
+        self.0 >= 15
+    }
+}
+
+impl Display for LLVMVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("LLVM {}", self.0))
+    }
+}
+
+/// A construct used in a module that the targeted LLVM version cannot express.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedConstructError {
+    pub construct: &'static str,
+    pub version: LLVMVersion,
+    pub introduced: Option<LLVMVersion>,
+    pub removed: Option<LLVMVersion>
+}
+
+impl Display for UnsupportedConstructError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("`{}` cannot be expressed in {}", self.construct, self.version))?;
+        match (self.introduced, self.removed) {
+            (Some(introduced), _) if self.version < introduced => f.write_fmt(format_args!(" (introduced in {})", introduced)),
+            (_, Some(removed)) => f.write_fmt(format_args!(" (removed in {})", removed)),
+            _ => Ok(())
+        }
+    }
+}
+
+impl std::error::Error for UnsupportedConstructError {}
+
+/// Something in a module that can't be printed as valid IR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrintError {
+    Unsupported(UnsupportedConstructError),
+    /// An unnamed local used outside of the function defining it, which has no number there.
+    UnnumberedLocal,
+    /// A metadata node within its own operands, written in place outside of a module.
+    MetadataCycle,
+    /// A node from `MetadataNode::declare_distinct` whose operands were never set.
+    UndefinedMetadataNode
+}
+
+impl From<UnsupportedConstructError> for PrintError {
+    fn from(error: UnsupportedConstructError) -> Self {
+        PrintError::Unsupported(error)
+    }
+}
+
+impl Display for PrintError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported(it) => Display::fmt(it, f),
+            Self::UnnumberedLocal => f.write_str("unnamed local is used outside of the function defining it"),
+            Self::MetadataCycle => f.write_str("metadata node refers to itself and can only be printed within a module"),
+            Self::UndefinedMetadataNode => f.write_str("distinct metadata node is declared but its operands are never set")
+        }
+    }
+}
+
+impl std::error::Error for PrintError {}
+
+struct PrintContext {
+    version: Option<LLVMVersion>,
+    opaque_pointers: Option<bool>,
+    errors: Vec<PrintError>
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<PrintContext>> = const { RefCell::new(None) };
+}
+
+/// The version targeted by the printer currently running on this thread, if any.
+pub(crate) fn target_version() -> Option<LLVMVersion> {
+    CONTEXT.with(|context| context.borrow().as_ref().and_then(|it| it.version))
+}
+
+/// Whether the printer running on this thread writes every pointer type as `ptr`. Unless set
+/// on the printer this follows the targeted version. Outside of a printer only pointers
+/// without a pointee are written as `ptr`.
+pub(crate) fn opaque_pointers() -> bool {
+    CONTEXT.with(|context| context.borrow().as_ref().is_some_and(|it| {
+        it.opaque_pointers.or(it.version.map(|version| version.uses_opaque_pointers())).unwrap_or(false)
+    }))
+}
+
+/// Whether `construct` exists in the targeted version. Constructs that don't are recorded
+/// as errors of the running printer. Without a targeted version everything is supported.
+pub(crate) fn supports(construct: &'static str, introduced: Option<u32>, removed: Option<u32>) -> bool {
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        let Some(context) = context.as_mut() else { return true; };
+        let Some(version) = context.version else { return true; };
+        let introduced = introduced.map(LLVMVersion);
+        let removed = removed.map(LLVMVersion);
+        if introduced.is_some_and(|it| version < it) || removed.is_some_and(|it| version >= it) {
+            let error = PrintError::from(UnsupportedConstructError { construct, version, introduced, removed });
+            if !context.errors.contains(&error) { context.errors.push(error); }
+            return false;
+        }
+        true
+    })
+}
+
+/// Records `error` for the running printer, which then fails, and lets printing go on so that
+/// further errors are found too. Outside of a printer there is nowhere to report `error` to,
+/// so formatting fails instead.
+pub(crate) fn report(error: PrintError) -> std::fmt::Result {
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        let Some(context) = context.as_mut() else { return Err(std::fmt::Error); };
+        if !context.errors.contains(&error) { context.errors.push(error); }
+        Ok(())
+    })
+}
+
+/// Whether an optional construct, like a flag that only enables optimizations,
+/// should be printed. Such constructs are dropped for versions that don't know them.
+pub(crate) fn prints_optional(introduced: u32) -> bool {
+    target_version().is_none_or(|it| it.0 >= introduced)
+}
+
+/// Prints a module for a specific LLVM version.
+///
+/// Debug info is out of scope: the crate can't express its specialized metadata like
+/// `DILocalVariable`, so neither `#dbg_value` records (LLVM 19) nor `llvm.dbg.value` calls are emitted.
+pub struct ModulePrinter<'m, 's> {
+    module: &'m Module<'s>,
+    llvm_version: Option<LLVMVersion>,
+    opaque_pointers: Option<bool>
+}
+
+impl<'m, 's> ModulePrinter<'m, 's> {
+    pub fn new(module: &'m Module<'s>) -> Self {
+        ModulePrinter { module, llvm_version: None, opaque_pointers: None }
+    }
+    /// Adapts the output to `llvm_version`, e.g. typed pointers before LLVM 15.
+    pub fn with_llvm_version(mut self, llvm_version: LLVMVersion) -> Self {
+        self.llvm_version = Some(llvm_version);
+        self
+    }
+    /// Writes every pointer type as `ptr` or `ptr addrspace(N)` if `enabled`, and pointers with
+    /// a pointee as typed pointers otherwise, whatever the targeted version defaults to.
+    /// LLVM 15 and 16 read both; 17 removed typed pointers, which are then reported.
+    pub fn with_opaque_pointers(mut self, enabled: bool) -> Self {
+        self.opaque_pointers = Some(enabled);
+        self
+    }
+    /// Prints the module, failing on the first construct that can't be printed,
+    /// e.g. one the targeted version can't express.
+    pub fn print(&self) -> Result<String, PrintError> {
+        self.print_all().map_err(|mut errors| errors.remove(0))
+    }
+    /// Like `print`, but reports every construct that can't be printed.
+    pub fn print_all(&self) -> Result<String, Vec<PrintError>> {
+        let previous = CONTEXT.with(|context| context.replace(Some(PrintContext { version: self.llvm_version, opaque_pointers: self.opaque_pointers, errors: Vec::new() })));
+        let output = self.module.to_string();
+        let context = CONTEXT.with(|context| context.replace(previous)).unwrap();
+        if context.errors.is_empty() { Ok(output) } else { Err(context.errors) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constant::simple::PoisonConstant;
+    use crate::identifier::{GlobalIdentifier, LocalIdentifier};
+    use crate::module::function::{Function, InstructionBlock};
+    use crate::module::function::instruction::{Instruction, terminator};
+    use crate::reference::{Reference, Value};
+    use crate::types::first_class::single_value::IntegerType;
+
+    fn returning(value: Value<'static>) -> Module<'static> {
+        let mut function = Function::new(GlobalIdentifier::new("f").unwrap(), IntegerType(32).into(), vec![]).unwrap();
+        let ret = Instruction::Terminal(terminator::Instruction::Return(terminator::ReturnInstruction::NonVoid(value)));
+        function.add_instruction_block(InstructionBlock::new(LocalIdentifier::new("entry").unwrap(), vec![ret])).unwrap();
+        let mut module = Module::new();
+        module.add_function(function).unwrap();
+        return module;
+    }
+
+    #[test]
+    fn unsupported_constructs_are_reported() {
+        let module = returning(crate::constant::Constant::from(PoisonConstant::new(IntegerType(32)).unwrap()).into());
+        assert!(module.printer().with_llvm_version(LLVMVersion(12)).print().is_ok());
+        let error = UnsupportedConstructError { construct: "poison", version: LLVMVersion(11), introduced: Some(LLVMVersion(12)), removed: None };
+        assert_eq!(module.printer().with_llvm_version(LLVMVersion(11)).print(), Err(error.clone().into()));
+        // The crate-wide error has one variant for everything the printer reports.
+        assert_eq!(crate::Error::from(error.clone()), crate::Error::Print(PrintError::Unsupported(error)));
+    }
+
+    #[test]
+    fn optional_flags_are_dropped_for_older_versions() {
+        use crate::constant::expression::CastOperator;
+        use crate::module::function::{Argument, instruction::{Variable, bitwise_binary_operator::OrInstruction, conversion_operator::CastInstruction}};
+        use crate::types::first_class::single_value::FloatType;
+        let x = Argument::new(IntegerType(8), LocalIdentifier::new("x").unwrap());
+        let reference = Reference::from(&x);
+        let value = || Value::from(reference.clone());
+        let instructions = vec![
+            Variable::new(LocalIdentifier::new("a").unwrap(), CastInstruction::new(CastOperator::ZeroExtend, value(), IntegerType(32)).unwrap().with_non_negative().into()).into(),
+            Variable::new(LocalIdentifier::new("b").unwrap(), CastInstruction::new(CastOperator::UnsignedToFloat, value(), FloatType::Double).unwrap().with_non_negative().into()).into(),
+            Variable::new(LocalIdentifier::new("c").unwrap(), OrInstruction::new(value(), value()).with_disjoint().into()).into(),
+            Instruction::Terminal(terminator::Instruction::Return(terminator::ReturnInstruction::Void))
+        ];
+        let mut function = Function::new(GlobalIdentifier::new("f").unwrap(), crate::types::Type::Void, vec![x]).unwrap();
+        function.add_instruction_block(InstructionBlock::new(LocalIdentifier::new("entry").unwrap(), instructions)).unwrap();
+        let mut module = Module::new();
+        module.add_function(function).unwrap();
+        let printed = |version| module.printer().with_llvm_version(LLVMVersion(version)).print().unwrap();
+        assert!(printed(19).contains("%a = zext nneg i8 %x to i32\n  %b = uitofp nneg i8 %x to double\n  %c = or disjoint i8 %x, %x\n"));
+        assert!(printed(18).contains("%a = zext nneg i8 %x to i32\n  %b = uitofp i8 %x to double\n  %c = or disjoint i8 %x, %x\n"));
+        assert!(printed(17).contains("%a = zext i8 %x to i32\n  %b = uitofp i8 %x to double\n  %c = or i8 %x, %x\n"));
+        assert!(CastInstruction::new(CastOperator::ZeroExtend, value(), IntegerType(8)).is_err());
+    }
+
+    #[test]
+    fn unnumbered_locals_are_reported() {
+        let module = returning(Reference::new(LocalIdentifier::unnamed(), IntegerType(32)).into());
+        assert_eq!(module.printer().print(), Err(PrintError::UnnumberedLocal));
+        assert!(std::fmt::Write::write_fmt(&mut String::new(), format_args!("{}", LocalIdentifier::unnamed())).is_err());
+        // Without a printer to report to, formatting the module fails, so `to_string` would panic.
+        assert!(std::fmt::Write::write_fmt(&mut String::new(), format_args!("{}", module)).is_err());
+    }
+
+    #[test]
+    fn opaque_pointers_are_a_printer_option() {
+        use crate::module::function::Argument;
+        use crate::types::first_class::single_value::PointerType;
+        let p = Argument::new(PointerType::new(IntegerType(32)), LocalIdentifier::new("p").unwrap());
+        let mut module = Module::new();
+        module.add_function(Function::new(GlobalIdentifier::new("f").unwrap(), crate::types::Type::Void, vec![p]).unwrap()).unwrap();
+        assert!(module.printer().print().unwrap().contains("@f(i32* %p)"));
+        assert!(module.printer().with_opaque_pointers(true).print().unwrap().contains("@f(ptr %p)"));
+        assert!(module.printer().with_llvm_version(LLVMVersion::LLVM_15).print().unwrap().contains("@f(ptr %p)"));
+        assert!(module.printer().with_llvm_version(LLVMVersion::LLVM_15).with_opaque_pointers(false).print().unwrap().contains("@f(i32* %p)"));
+        assert!(module.printer().with_llvm_version(LLVMVersion::LLVM_17).with_opaque_pointers(false).print().is_err());
+        // The option only holds while its printer runs.
+        let _ = module.printer().with_opaque_pointers(true).print();
+        assert!(module.to_string().contains("@f(i32* %p)"));
+    }
+}
@@ -2,7 +2,9 @@ use std::fmt::Display;
 
 use crate::identifier::Identifiable;
 use crate::types::ReturnType;
+use crate::types::interner::TypeId;
 
+#[derive(Debug)]
 pub enum Referencable<'s> {
     FunctionArgument(crate::module::function::Argument<'s>),
     Label(crate::module::function::InstructionBlock<'s>),
@@ -10,36 +12,50 @@ pub enum Referencable<'s> {
 }
 
 impl<'s> ReturnType for Referencable<'s> {
-    fn return_type(&self) -> crate::types::first_class::Type {
-        match self {
-            Self::Label(it) => crate::types::first_class::LabelType.into(),
-            _ => (match self {
-                Self::FunctionArgument(it) => it as &dyn ReturnType,
-                Self::GlobalVariabel(it) => it as &dyn ReturnType,
-                Self::Label(_) => unreachable!()
-            }).return_type()
-        }
+    fn return_type(&self) -> TypeId {
+        (match self {
+            Self::FunctionArgument(it) => it as &dyn ReturnType,
+            Self::Label(it) => it as &dyn ReturnType,
+            Self::GlobalVariabel(it) => it as &dyn ReturnType,
+        }).return_type()
     }
 }
 
-impl<'s> Identifiable for Referencable<'s> {
-    fn id(&self) -> crate::identifier::Identifier {
+impl<'s> Identifiable<'s> for Referencable<'s> {
+    fn id(&self) -> crate::identifier::Identifier<'s> {
         (match self {
-            Self::FunctionArgument(it) => it as &dyn Identifiable,
-            Self::Label(it) => it as &dyn Identifiable,
-            Self::GlobalVariabel(it) => it as &dyn Identifiable,
+            Self::FunctionArgument(it) => it as &dyn Identifiable<'s>,
+            Self::Label(it) => it as &dyn Identifiable<'s>,
+            Self::GlobalVariabel(it) => it as &dyn Identifiable<'s>,
         }).id()
     }
 }
 
-pub struct Reference<'s>(&'s Referencable<'s>);
+/// A use of a named value, e.g. an argument, a block or a function.
+#[derive(Debug, Clone)]
+pub struct Reference<'s> {
+    identifier: crate::identifier::Identifier<'s>,
+    _type: TypeId
+}
+
+impl<'s> Reference<'s> {
+    pub fn new(identifier: impl Into<crate::identifier::Identifier<'s>>, _type: impl Into<TypeId>) -> Self {
+        Reference { identifier: identifier.into(), _type: _type.into() }
+    }
+}
+
+impl<'s, T: Identifiable<'s> + ReturnType> From<&T> for Reference<'s> {
+    fn from(referencable: &T) -> Self {
+        Reference { identifier: referencable.id(), _type: referencable.return_type() }
+    }
+}
 
 impl<'s> ReturnType for Reference<'s> {
-    fn return_type(&self) -> crate::types::first_class::Type { self.return_type() }
+    fn return_type(&self) -> TypeId { self._type }
 }
 
-impl<'s> Identifiable for Reference<'s> {
-    fn id(&self) -> crate::identifier::Identifier { self.id() }
+impl<'s> Identifiable<'s> for Reference<'s> {
+    fn id(&self) -> crate::identifier::Identifier<'s> { self.identifier.clone() }
 }
 
 impl<'s> Display for Reference<'s> {
@@ -48,13 +64,48 @@ impl<'s> Display for Reference<'s> {
     }
 }
 
+#[derive(Debug)]
 pub enum Value<'s> {
     Constant(crate::constant::Constant<'s>),
     Reference(Reference<'s>),
 }
 
+impl<'s> From<crate::constant::Constant<'s>> for Value<'s> {
+    fn from(constant: crate::constant::Constant<'s>) -> Self {
+        Value::Constant(constant)
+    }
+}
+
+impl<'s> From<crate::constant::simple::UndefinedConstant> for Value<'s> {
+    fn from(constant: crate::constant::simple::UndefinedConstant) -> Self {
+        Value::Constant(constant.into())
+    }
+}
+
+impl<'s> From<crate::constant::simple::PoisonConstant> for Value<'s> {
+    fn from(constant: crate::constant::simple::PoisonConstant) -> Self {
+        Value::Constant(constant.into())
+    }
+}
+
+impl<'s> From<Reference<'s>> for Value<'s> {
+    fn from(reference: Reference<'s>) -> Self {
+        Value::Reference(reference)
+    }
+}
+
+impl<'s> Value<'s> {
+    /// The value without its leading type, as used for callees.
+    pub(crate) fn untyped(&self) -> &dyn Display {
+        match self {
+            Self::Constant(it) => it as &dyn Display,
+            Self::Reference(it) => it as &dyn Display
+        }
+    }
+}
+
 impl<'s> ReturnType for Value<'s> {
-    fn return_type(&self) -> crate::types::first_class::Type {
+    fn return_type(&self) -> TypeId {
         (match self {
             Self::Constant(it) => it as &dyn ReturnType,
             Self::Reference(it) => it as &dyn ReturnType
@@ -64,10 +115,7 @@ impl<'s> ReturnType for Value<'s> {
 
 impl<'s> Display for Value<'s> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("{} {}", self.return_type(), match self {
-            Self::Constant(it) => it as &dyn Display,
-            Self::Reference(it) => it as &dyn Display
-        }))
+        f.write_fmt(format_args!("{} {}", self.return_type(), self.untyped()))
     }
 }
 
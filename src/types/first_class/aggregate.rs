@@ -1,12 +1,19 @@
+use std::borrow::Cow;
 use std::fmt::{Display, Debug};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, OnceLock};
 
 use crate::IRElement;
+use crate::identifier::ParseError;
+use crate::types::interner::TypeId;
 
-#[derive(Debug, Clone, PartialEq)]
+use super::TypeCreationError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
     Array(ArrayType),
     Struct(StructType),
-    OpaqueStruct(OpaqueStructType)
+    NamedStruct(NamedStructType)
 }
 
 impl From<ArrayType> for Type {
@@ -21,9 +28,9 @@ impl From<StructType> for Type {
     }
 }
 
-impl From<OpaqueStructType> for Type {
-    fn from(_type: OpaqueStructType) -> Self {
-        return Type::OpaqueStruct(_type);
+impl From<NamedStructType> for Type {
+    fn from(_type: NamedStructType) -> Self {
+        return Type::NamedStruct(_type);
     }
 }
 
@@ -34,21 +41,33 @@ impl Display for Type {
         let displayable: &dyn Display = match self {
             Self::Array(_type) => _type as &dyn Display,
             Self::Struct(_type) => _type as &dyn Display,
-            Self::OpaqueStruct(_type) => _type as &dyn Display
+            Self::NamedStruct(_type) => _type as &dyn Display
         };
         Display::fmt(displayable, f)
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ArrayType {
-    size: usize,
-    _type: Box<crate::types::first_class::Type>,
+    pub(crate) size: usize,
+    pub(crate) _type: TypeId,
 }
 
 impl ArrayType {
-    pub fn new(size: usize, _type: Box<crate::types::first_class::Type>) -> Self {
-        ArrayType { size, _type }
+    /// An array of `size` elements, which have to be sized and can't be scalable vectors.
+    pub fn new(size: usize, _type: impl Into<TypeId>) -> Result<Self, TypeCreationError> {
+        let _type = _type.into();
+        if !_type.get().is_valid_element(true) { return Err(TypeCreationError::InvalidArrayElement(_type.get().clone())); }
+        Ok(ArrayType { size, _type })
+    }
+    pub fn element_type(&self) -> &crate::types::first_class::Type {
+        self._type.get()
+    }
+    pub fn len(&self) -> usize {
+        self.size
+    }
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
     }
 }
 
@@ -60,18 +79,32 @@ impl Display for ArrayType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StructType {
-    packed: bool,
-    types: Vec<crate::types::first_class::Type>
+    pub(crate) packed: bool,
+    pub(crate) types: Vec<TypeId>
 }
 
 impl StructType {
-    pub fn new(types: Vec<crate::types::first_class::Type>) -> Self {
-        StructType { packed: false, types }
+    /// A struct of `types`, which have to be sized and can't be scalable vectors.
+    pub fn new(types: Vec<TypeId>) -> Result<Self, TypeCreationError> {
+        Self::checked(types, false)
+    }
+    /// A struct without padding between its fields.
+    pub fn new_packed(types: Vec<TypeId>) -> Result<Self, TypeCreationError> {
+        Self::checked(types, true)
     }
-    pub fn new_packed(types: Vec<crate::types::first_class::Type>) -> Self {
-        StructType { packed: true, types }
+    fn checked(types: Vec<TypeId>, packed: bool) -> Result<Self, TypeCreationError> {
+        if let Some(index) = types.iter().position(|it| !it.get().is_valid_element(false)) {
+            return Err(TypeCreationError::InvalidStructField { index, field: types[index].get().clone() });
+        }
+        Ok(StructType { packed, types })
+    }
+    pub fn fields(&self) -> &[TypeId] {
+        &self.types
+    }
+    pub fn is_packed(&self) -> bool {
+        self.packed
     }
 }
 
@@ -79,12 +112,11 @@ impl IRElement for StructType {}
 
 impl Display for StructType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("type ")?;
         if self.packed { f.write_str("<")?; }
         f.write_str("{ ")?;
         for (i, _type) in self.types.iter().enumerate() {
             Display::fmt(_type, f)?;
-            if i < self.types.len() { f.write_str(", ")?; }
+            if i < (self.types.len() - 1) { f.write_str(", ")?; }
         }
         f.write_str(" }")?;
         if self.packed { f.write_str(">")?; }
@@ -92,14 +124,119 @@ impl Display for StructType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct OpaqueStructType;
+/// An identified struct type, printed as `%name`.
+///
+/// Clones share the same body, so a named struct can be declared opaque,
+/// used (e.g. behind a pointer in its own body) and given a body later.
+/// Two named structs are equal if they are clones of the same declaration.
+#[derive(Clone)]
+pub struct NamedStructType {
+    name: Arc<[u8]>,
+    body: Arc<OnceLock<StructType>>
+}
+
+impl NamedStructType {
+    pub fn new(name: impl AsRef<[u8]>, body: StructType) -> Result<Self, ParseError> {
+        let named = Self::new_opaque(name)?;
+        let _ = named.body.set(body);
+        Ok(named)
+    }
+    /// `name` can be any bytes except an empty name or one containing a NUL byte, like the name
+    /// of a global. It is escaped when printed if it isn't a plain name.
+    pub fn new_opaque(name: impl AsRef<[u8]>) -> Result<Self, ParseError> {
+        let name = crate::identifier::verify_bytes(Cow::Borrowed(name.as_ref()))?;
+        Ok(NamedStructType { name: Arc::from(name.as_ref()), body: Arc::new(OnceLock::new()) })
+    }
+    /// The name, with bytes that aren't UTF-8 replaced.
+    pub fn name(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.name)
+    }
+    pub fn name_bytes(&self) -> &[u8] {
+        &self.name
+    }
+    pub fn body(&self) -> Option<&StructType> {
+        self.body.get()
+    }
+    pub fn is_opaque(&self) -> bool {
+        self.body.get().is_none()
+    }
+    /// Sets the body of an opaque struct. Gives the body back if one was already set.
+    pub fn set_body(&self, body: StructType) -> Result<(), StructType> {
+        self.body.set(body)
+    }
+}
+
+impl PartialEq for NamedStructType {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.body, &other.body)
+    }
+}
+
+impl Eq for NamedStructType {}
 
-impl IRElement for OpaqueStructType {}
+impl Hash for NamedStructType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.body).hash(state)
+    }
+}
 
-impl Display for OpaqueStructType {
+impl Debug for NamedStructType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("type opaque")
+        f.debug_tuple("NamedStructType").field(&self.name()).finish()
     }
 }
 
+impl IRElement for NamedStructType {}
+
+impl Display for NamedStructType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("%{}", crate::identifier::Name(&self.name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::first_class::single_value::IntegerType;
+
+    fn t(s: &str) -> super::super::Type {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn array_elements_have_a_fixed_size() {
+        for element in ["i8", "ptr", "<4 x i32>", "x86_mmx", "[ 2 x i8 ]", "{ i8 }", "%opaque"] {
+            assert!(ArrayType::new(2, t(element)).is_ok(), "{}", element);
+        }
+        assert!(ArrayType::new(0, IntegerType(8)).is_ok());
+        for element in ["<vscale x 4 x i32>", "x86_amx", "label", "token", "metadata"] {
+            assert_eq!(ArrayType::new(2, t(element)), Err(TypeCreationError::InvalidArrayElement(t(element))));
+        }
+    }
+
+    #[test]
+    fn struct_fields_have_a_fixed_size() {
+        assert!(StructType::new(vec![]).is_ok());
+        assert!(StructType::new(vec![t("x86_amx").into(), t("%opaque").into()]).is_ok());
+        for field in ["<vscale x 4 x i32>", "label", "token", "metadata"] {
+            let fields = vec![t("i8").into(), t(field).into()];
+            assert_eq!(StructType::new(fields.clone()), Err(TypeCreationError::InvalidStructField { index: 1, field: t(field) }));
+            assert_eq!(StructType::new_packed(fields), Err(TypeCreationError::InvalidStructField { index: 1, field: t(field) }));
+        }
+        // Void and function types aren't first class, so they can't even be passed as fields or elements.
+        for s in ["{ void }", "{ i8, i32 (i8) }", "[ 2 x void ]", "[ 2 x void () ]"] {
+            assert!(s.parse::<crate::types::Type>().is_err(), "{}", s);
+        }
+        // The first invalid field is reported.
+        assert_eq!(StructType::new(vec![t("label").into(), t("token").into()]), Err(TypeCreationError::InvalidStructField { index: 0, field: t("label") }));
+    }
+
+    #[test]
+    fn named_struct_names_are_not_empty() {
+        use crate::identifier::ParseErrorKind;
+        assert_eq!(NamedStructType::new_opaque("my t").unwrap().to_string(), "%\"my t\"");
+        assert_eq!(NamedStructType::new_opaque(b"\xff").unwrap().to_string(), "%\"\\FF\"");
+        assert_eq!(NamedStructType::new_opaque("").err().map(|it| it.kind), Some(ParseErrorKind::Empty));
+        assert_eq!(NamedStructType::new("a\0b", StructType::new(vec![]).unwrap()).err().map(|it| (it.position, it.kind)), Some((1, ParseErrorKind::NulByte)));
+    }
+}
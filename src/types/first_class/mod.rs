@@ -1,10 +1,11 @@
 use std::fmt::Display;
 use crate::IRElement;
+use crate::types::interner::TypeId;
 
 pub mod single_value;
 pub mod aggregate;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
     SingleValue(single_value::Type),
     Label(LabelType),
@@ -91,9 +92,9 @@ impl From<aggregate::StructType> for Type {
     }
 }
 
-impl From<aggregate::OpaqueStructType> for Type {
-    fn from(_type: aggregate::OpaqueStructType) -> Self {
-        return Type::from(aggregate::Type::OpaqueStruct(_type));
+impl From<aggregate::NamedStructType> for Type {
+    fn from(_type: aggregate::NamedStructType) -> Self {
+        return Type::from(aggregate::Type::NamedStruct(_type));
     }
 }
 
@@ -112,9 +113,158 @@ impl Display for Type {
     }
 }
 
+/// A vector or aggregate type that LLVM does not allow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeCreationError {
+    InvalidVectorElement(Type),
+    EmptyVector,
+    InvalidArrayElement(Type),
+    InvalidStructField { index: usize, field: Type }
+}
+
+impl Display for TypeCreationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidVectorElement(element) => f.write_fmt(format_args!("`{}` is not a valid vector element type, expected an integer, floating-point or pointer type", element)),
+            Self::EmptyVector => f.write_str("vector types need at least one element"),
+            Self::InvalidArrayElement(element) => f.write_fmt(format_args!("`{}` is not a valid array element type, elements need a fixed size in memory", element)),
+            Self::InvalidStructField { index, field } => f.write_fmt(format_args!("`{}` is not a valid type for struct field {}, fields need a fixed size in memory", field, index))
+        }
+    }
+}
+
+impl std::error::Error for TypeCreationError {}
+
+impl Type {
+    /// Whether this type can be an array element or struct field. Named structs are accepted even
+    /// while opaque, as their body may be set later.
+    pub(crate) fn is_valid_element(&self, array: bool) -> bool {
+        match self {
+            Self::Label(_) | Self::Token(_) | Self::Metadata(_) => false,
+            Self::SingleValue(single_value::Type::X86AMX(_)) => !array,
+            _ => !self.is_scalable()
+        }
+    }
+    fn single_value(&self) -> Option<&single_value::Type> {
+        if let Self::SingleValue(_type) = self { Some(_type) } else { None }
+    }
+    fn vector(&self) -> Option<&single_value::VectorType> {
+        if let Some(single_value::Type::Vector(_type)) = self.single_value() { Some(_type) } else { None }
+    }
+    fn pointer(&self) -> Option<&single_value::PointerType> {
+        if let Some(single_value::Type::Pointer(_type)) = self.single_value() { Some(_type) } else { None }
+    }
+    pub fn is_integer(&self) -> bool {
+        matches!(self.single_value(), Some(single_value::Type::Integer(_)))
+    }
+    /// The bit width of an integer type.
+    pub fn integer_width(&self) -> Option<usize> {
+        if let Some(single_value::Type::Integer(_type)) = self.single_value() { Some(_type.0) } else { None }
+    }
+    pub fn is_float(&self) -> bool {
+        matches!(self.single_value(), Some(single_value::Type::Float(_)))
+    }
+    pub fn float_type(&self) -> Option<&single_value::FloatType> {
+        if let Some(single_value::Type::Float(_type)) = self.single_value() { Some(_type) } else { None }
+    }
+    pub fn is_pointer(&self) -> bool {
+        self.pointer().is_some()
+    }
+    /// The pointee of a typed pointer. Opaque pointers and non-pointers have none.
+    pub fn pointee(&self) -> Option<&crate::types::Type> {
+        self.pointer().and_then(single_value::PointerType::pointee)
+    }
+    /// The address space of a pointer or of the elements of a vector of pointers.
+    pub fn address_space(&self) -> Option<usize> {
+        self.scalar_type().pointer().map(single_value::PointerType::address_space)
+    }
+    pub fn is_vector(&self) -> bool {
+        self.vector().is_some()
+    }
+    pub fn is_scalable(&self) -> bool {
+        self.vector().is_some_and(|it| it.scalable)
+    }
+    pub fn is_array(&self) -> bool {
+        matches!(self, Self::Aggregate(aggregate::Type::Array(_)))
+    }
+    /// Whether this is a literal, named or opaque struct.
+    pub fn is_struct(&self) -> bool {
+        matches!(self, Self::Aggregate(aggregate::Type::Struct(_) | aggregate::Type::NamedStruct(_)))
+    }
+    pub fn is_aggregate(&self) -> bool {
+        matches!(self, Self::Aggregate(_))
+    }
+    pub fn is_label(&self) -> bool {
+        matches!(self, Self::Label(_))
+    }
+    pub fn is_token(&self) -> bool {
+        matches!(self, Self::Token(_))
+    }
+    pub fn is_metadata(&self) -> bool {
+        matches!(self, Self::Metadata(_))
+    }
+    /// The element type of an array or vector.
+    pub fn element_type(&self) -> Option<&Type> {
+        match self {
+            Self::Aggregate(aggregate::Type::Array(_type)) => Some(_type.element_type()),
+            _ => self.vector().map(|it| it._type.get())
+        }
+    }
+    /// The number of elements of an array or vector. Scalable vectors report their minimum.
+    pub fn element_count(&self) -> Option<usize> {
+        match self {
+            Self::Aggregate(aggregate::Type::Array(_type)) => Some(_type.len()),
+            _ => self.vector().map(|it| it.factor)
+        }
+    }
+    /// The fields of a literal struct or of a named struct with a body.
+    pub fn struct_fields(&self) -> Option<&[TypeId]> {
+        match self {
+            Self::Aggregate(aggregate::Type::Struct(_type)) => Some(_type.fields()),
+            Self::Aggregate(aggregate::Type::NamedStruct(_type)) => _type.body().map(aggregate::StructType::fields),
+            _ => None
+        }
+    }
+    /// Whether values of this type have a size in memory. Opaque structs, labels, tokens and
+    /// metadata don't, and neither do aggregates containing them. Scalable vectors are sized.
+    pub fn is_sized(&self) -> bool {
+        self.is_sized_visiting(&mut Vec::new())
+    }
+    fn is_sized_visiting<'t>(&'t self, visiting: &mut Vec<&'t aggregate::NamedStructType>) -> bool {
+        match self {
+            Self::SingleValue(_) => true,
+            Self::Label(_) | Self::Token(_) | Self::Metadata(_) => false,
+            Self::Aggregate(aggregate::Type::Array(_type)) => _type.element_type().is_sized_visiting(visiting),
+            Self::Aggregate(aggregate::Type::Struct(_type)) => _type.fields().iter().all(|it| it.get().is_sized_visiting(visiting)),
+            Self::Aggregate(aggregate::Type::NamedStruct(_type)) => {
+                // A struct that contains itself other than through a pointer has no size.
+                if visiting.contains(&_type) { return false; }
+                let Some(body) = _type.body() else { return false; };
+                visiting.push(_type);
+                let sized = body.fields().iter().all(|it| it.get().is_sized_visiting(visiting));
+                visiting.pop();
+                sized
+            }
+        }
+    }
+    /// The element type of a vector, or the type itself.
+    pub fn scalar_type(&self) -> &Type {
+        self.vector().map(|it| it._type.get()).unwrap_or(self)
+    }
+    pub fn is_integer_or_integer_vector(&self) -> bool {
+        self.scalar_type().is_integer()
+    }
+    pub fn is_float_or_float_vector(&self) -> bool {
+        self.scalar_type().is_float()
+    }
+    pub fn is_pointer_or_pointer_vector(&self) -> bool {
+        self.scalar_type().is_pointer()
+    }
+}
+
 impl IRElement for LabelType {}
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LabelType;
 
 impl Display for LabelType {
@@ -123,7 +273,7 @@ impl Display for LabelType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TokenType;
 
 impl IRElement for TokenType {}
@@ -134,7 +284,7 @@ impl Display for TokenType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MetadataType;
 
 impl IRElement for MetadataType {}
@@ -145,3 +295,128 @@ impl Display for MetadataType {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aggregate::{NamedStructType, StructType};
+
+    /// The first-class type written as `s`. Unknown `%name`s are opaque structs.
+    fn t(s: &str) -> Type {
+        s.parse().unwrap()
+    }
+
+    fn named_pair() -> Type {
+        NamedStructType::new("pair", StructType::new(vec![t("i32").into(), t("ptr").into()]).unwrap()).unwrap().into()
+    }
+
+    #[test]
+    fn kinds() {
+        let kinds = |_type: &Type| [
+            _type.is_integer(), _type.is_float(), _type.is_pointer(), _type.is_vector(), _type.is_scalable(),
+            _type.is_array(), _type.is_struct(), _type.is_aggregate(), _type.is_label(), _type.is_token(), _type.is_metadata()
+        ];
+        for (s, expected) in [
+            ("i32", [true, false, false, false, false, false, false, false, false, false, false]),
+            ("bfloat", [false, true, false, false, false, false, false, false, false, false, false]),
+            ("i8 addrspace(2)*", [false, false, true, false, false, false, false, false, false, false, false]),
+            ("<4 x float>", [false, false, false, true, false, false, false, false, false, false, false]),
+            ("<vscale x 4 x ptr>", [false, false, false, true, true, false, false, false, false, false, false]),
+            ("[ 0 x i8 ]", [false, false, false, false, false, true, false, true, false, false, false]),
+            ("<{ i8, i32 }>", [false, false, false, false, false, false, true, true, false, false, false]),
+            ("%opaque", [false, false, false, false, false, false, true, true, false, false, false]),
+            ("label", [false, false, false, false, false, false, false, false, true, false, false]),
+            ("token", [false, false, false, false, false, false, false, false, false, true, false]),
+            ("metadata", [false, false, false, false, false, false, false, false, false, false, true]),
+            ("x86_amx", [false; 11])
+        ] {
+            assert_eq!(kinds(&t(s)), expected, "{}", s);
+        }
+    }
+
+    #[test]
+    fn kinds_of_vector_elements() {
+        for (s, integer, float, pointer) in [
+            ("i1", true, false, false), ("<2 x i1>", true, false, false), ("<vscale x 2 x double>", false, true, false), ("half", false, true, false),
+            ("<8 x ptr addrspace(1)>", false, false, true), ("ptr", false, false, true), ("[ 2 x i8 ]", false, false, false), ("{ float }", false, false, false)
+        ] {
+            let _type = t(s);
+            assert_eq!((_type.is_integer_or_integer_vector(), _type.is_float_or_float_vector(), _type.is_pointer_or_pointer_vector()), (integer, float, pointer), "{}", s);
+        }
+    }
+
+    #[test]
+    fn integer_width_and_float_type() {
+        for (s, width, float) in [
+            ("i17", Some(17), None), ("i8388608", Some(8388608), None), ("x86_fp80", None, Some(single_value::FloatType::X86FP80)),
+            ("ppc_fp128", None, Some(single_value::FloatType::PPCFP128)), ("<2 x i8>", None, None), ("<2 x float>", None, None), ("ptr", None, None)
+        ] {
+            let _type = t(s);
+            assert_eq!((_type.integer_width(), _type.float_type().cloned()), (width, float), "{}", s);
+        }
+    }
+
+    #[test]
+    fn pointee_and_address_space() {
+        for (s, pointee, address_space) in [
+            ("i32*", Some("i32"), Some(0)), ("void (i8, ...) addrspace(4)*", Some("void (i8, ...)"), Some(4)), ("%node**", Some("%node*"), Some(0)),
+            ("ptr", None, Some(0)), ("ptr addrspace(3)", None, Some(3)), ("<2 x i32 addrspace(5)*>", None, Some(5)),
+            ("<vscale x 2 x ptr addrspace(1)>", None, Some(1)), ("i64", None, None), ("[ 2 x ptr ]", None, None)
+        ] {
+            let _type = t(s);
+            assert_eq!(_type.pointee().map(ToString::to_string).as_deref(), pointee, "{}", s);
+            assert_eq!(_type.address_space(), address_space, "{}", s);
+        }
+    }
+
+    #[test]
+    fn elements() {
+        for (s, element, count, scalar) in [
+            ("[ 4 x i8 ]", Some("i8"), Some(4), "[ 4 x i8 ]"), ("[ 0 x { i8 } ]", Some("{ i8 }"), Some(0), "[ 0 x { i8 } ]"),
+            ("<2 x float>", Some("float"), Some(2), "float"), ("<vscale x 8 x i1>", Some("i1"), Some(8), "i1"), ("<1 x ptr>", Some("ptr"), Some(1), "ptr"),
+            ("i32", None, None, "i32"), ("{ i8, i8 }", None, None, "{ i8, i8 }"), ("ptr", None, None, "ptr")
+        ] {
+            let _type = t(s);
+            assert_eq!(_type.element_type(), element.map(t).as_ref(), "{}", s);
+            assert_eq!(_type.element_count(), count, "{}", s);
+            assert_eq!(_type.scalar_type(), &t(scalar), "{}", s);
+        }
+    }
+
+    #[test]
+    fn struct_fields() {
+        let fields = |_type: &Type| _type.struct_fields().map(|it| it.iter().map(ToString::to_string).collect::<Vec<_>>());
+        assert_eq!(fields(&t("{ i32, [ 2 x i8 ] }")), Some(vec!["i32".to_string(), "[ 2 x i8 ]".to_string()]));
+        assert_eq!(fields(&t("<{ i8 }>")), Some(vec!["i8".to_string()]));
+        assert_eq!(fields(&t("{  }")), Some(vec![]));
+        assert_eq!(fields(&named_pair()), Some(vec!["i32".to_string(), "ptr".to_string()]));
+        assert_eq!(fields(&t("%opaque")), None);
+        assert_eq!(fields(&t("[ 2 x { i8 } ]")), None);
+        assert_eq!(fields(&t("<2 x i8>")), None);
+    }
+
+    #[test]
+    fn is_sized() {
+        for (s, sized) in [
+            ("i1", true), ("ptr", true), ("<vscale x 2 x i64>", true), ("x86_amx", true), ("[ 0 x i8 ]", true), ("{  }", true), ("%opaque*", true),
+            ("label", false), ("token", false), ("metadata", false), ("%opaque", false), ("{ i32, %opaque }", false), ("[ 2 x [ 2 x %opaque ] ]", false)
+        ] {
+            assert_eq!(t(s).is_sized(), sized, "{}", s);
+        }
+        assert!(named_pair().is_sized());
+        let recursive = NamedStructType::new_opaque("recursive").unwrap();
+        recursive.set_body(StructType::new(vec![t("i8").into(), recursive.clone().into()]).unwrap()).unwrap();
+        assert!(!Type::from(recursive).is_sized());
+    }
+
+    #[test]
+    fn valid_elements() {
+        for (s, array, field) in [
+            ("i8", true, true), ("<4 x i32>", true, true), ("%opaque", true, true), ("x86_amx", false, true),
+            ("<vscale x 4 x i32>", false, false), ("label", false, false), ("token", false, false), ("metadata", false, false)
+        ] {
+            let _type = t(s);
+            assert_eq!((_type.is_valid_element(true), _type.is_valid_element(false)), (array, field), "{}", s);
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use std::fmt::{Display, Debug};
 
 use crate::IRElement;
+use crate::types::interner::TypeId;
 
-#[derive(Debug, Clone, PartialEq)]
+use super::TypeCreationError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
     Integer(IntegerType),
     Float(FloatType),
@@ -64,7 +67,7 @@ impl Display for Type {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct IntegerType(pub usize);
 
@@ -76,7 +79,7 @@ impl Display for IntegerType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FloatType {
     Half,
     BFloat,
@@ -87,13 +90,28 @@ pub enum FloatType {
     PPCFP128
 }
 
+impl FloatType {
+    pub fn bit_width(&self) -> usize {
+        match self {
+            Self::Half | Self::BFloat => 16,
+            Self::Float => 32,
+            Self::Double => 64,
+            Self::X86FP80 => 80,
+            Self::FP128 | Self::PPCFP128 => 128
+        }
+    }
+}
+
 impl IRElement for FloatType {}
 
 impl Display for FloatType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
             Self::Half => "half",
-            Self::BFloat => "bfloat",
+            Self::BFloat => {
+                crate::printer::supports("bfloat", Some(11), None);
+                "bfloat"
+            }
             Self::Float => "float",
             Self::Double => "double",
             Self::FP128 => "fp128",
@@ -103,76 +121,116 @@ impl Display for FloatType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct X86AMXType;
 
 impl IRElement for X86AMXType {}
 
 impl Display for X86AMXType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::printer::supports("x86_amx", Some(12), None);
         f.write_str("x86_amx")
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct X86MMXType;
 
 impl IRElement for X86MMXType {}
 
 impl Display for X86MMXType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        crate::printer::supports("x86_mmx", None, Some(20));
         f.write_str("x86_mmx")
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// A pointer to `_type`, an interned type of any kind. Opaque pointers (`ptr`) have no pointee.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PointerType {
-    pub(crate) _type: Box<crate::types::Type>,
-    address_space: usize
+    pub(crate) _type: Option<TypeId<crate::types::Type>>,
+    pub(crate) address_space: usize
 }
 
 impl IRElement for PointerType {}
 
 impl PointerType {
-    pub fn new(_type: Box<crate::types::Type>) -> PointerType {
-        PointerType { _type, address_space: 0 }
+    pub fn new(_type: impl Into<crate::types::Type>) -> PointerType {
+        Self::new_with_address_space(_type, 0)
     }
-    pub fn new_with_address_space(_type: Box<crate::types::Type>, address_space: usize) -> PointerType {
-        PointerType { _type, address_space }
+    pub fn new_with_address_space(_type: impl Into<crate::types::Type>, address_space: usize) -> PointerType {
+        PointerType { _type: Some(TypeId::new_any(_type)), address_space }
+    }
+    pub fn new_opaque() -> PointerType {
+        Self::new_opaque_with_address_space(0)
+    }
+    pub fn new_opaque_with_address_space(address_space: usize) -> PointerType {
+        PointerType { _type: None, address_space }
+    }
+    /// The pointee of a typed pointer, `None` for `ptr`.
+    pub fn pointee(&self) -> Option<&crate::types::Type> {
+        self._type.map(|it| it.get())
+    }
+    pub fn address_space(&self) -> usize {
+        self.address_space
+    }
+    pub fn is_opaque(&self) -> bool {
+        self._type.is_none()
     }
 }
 
 impl Display for PointerType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let crate::types::Type::FirstClass(crate::types::first_class::Type::Aggregate(crate::types::first_class::aggregate::Type::OpaqueStruct(_))) = self._type.as_ref() {
+        if crate::printer::opaque_pointers() || (self.is_opaque() && crate::printer::target_version().is_none()) {
             if self.address_space == 0 {
                 f.write_str("ptr")
             } else {
                 f.write_fmt(format_args!("ptr addrspace({})", self.address_space))
             }
         } else {
+            crate::printer::supports("typed pointers", None, Some(17));
+            let pointee: &dyn Display = match self.pointee() { Some(pointee) => pointee, None => &IntegerType(8) };
             if self.address_space == 0 {
-                f.write_fmt(format_args!("{}*", self._type))
+                f.write_fmt(format_args!("{}*", pointee))
             } else {
-                f.write_fmt(format_args!("{} addrspace({})*", self._type, self.address_space))
+                f.write_fmt(format_args!("{} addrspace({})*", pointee, self.address_space))
             }
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct VectorType {
-    pub factor: usize,
-    pub _type: Box<crate::types::first_class::Type>,
-    pub scalable: bool
+    pub(crate) factor: usize,
+    pub(crate) _type: TypeId,
+    pub(crate) scalable: bool
 }
 
 impl VectorType {
-    fn new(factor: usize, _type: Box<crate::types::first_class::Type>) -> Self {
-        VectorType { factor, _type, scalable: false }
+    /// A vector of `factor` integers, floats or pointers.
+    pub fn new(factor: usize, _type: impl Into<TypeId>) -> Result<Self, TypeCreationError> {
+        Self::checked(factor, _type.into(), false)
+    }
+    /// A vector of `vscale * factor` integers, floats or pointers.
+    pub fn new_scalable(factor: usize, _type: impl Into<TypeId>) -> Result<Self, TypeCreationError> {
+        Self::checked(factor, _type.into(), true)
     }
-    fn new_scalable(factor: usize, _type: Box<crate::types::first_class::Type>) -> Self {
-        VectorType { factor, _type, scalable: true }
+    fn checked(factor: usize, _type: TypeId, scalable: bool) -> Result<Self, TypeCreationError> {
+        if !matches!(_type.get(), crate::types::first_class::Type::SingleValue(Type::Integer(_) | Type::Float(_) | Type::Pointer(_))) {
+            return Err(TypeCreationError::InvalidVectorElement(_type.get().clone()));
+        }
+        if factor == 0 { return Err(TypeCreationError::EmptyVector); }
+        Ok(VectorType { factor, _type, scalable })
+    }
+    pub fn element_type(&self) -> &crate::types::first_class::Type {
+        self._type.get()
+    }
+    /// The number of elements, or the minimum number for scalable vectors.
+    pub fn element_count(&self) -> usize {
+        self.factor
+    }
+    pub fn is_scalable(&self) -> bool {
+        self.scalable
     }
 }
 
@@ -186,3 +244,36 @@ impl Display for VectorType {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::first_class::aggregate::NamedStructType;
+
+    #[test]
+    fn opaque_pointers_have_no_pointee() {
+        let opaque = PointerType::new_opaque_with_address_space(1);
+        assert!(opaque.is_opaque() && opaque.pointee().is_none());
+        assert_eq!(opaque.to_string(), "ptr addrspace(1)");
+        let to_struct = PointerType::new(NamedStructType::new_opaque("T").unwrap());
+        assert!(!to_struct.is_opaque());
+        assert_eq!(to_struct.to_string(), "%T*");
+    }
+
+    #[test]
+    fn vectors_take_integers_floats_and_pointers() {
+        for element in ["i1", "half", "ptr", "i8 addrspace(1)*"] {
+            let element: crate::types::first_class::Type = element.parse().unwrap();
+            assert!(VectorType::new(4, element.clone()).is_ok() && VectorType::new_scalable(1, element.clone()).is_ok(), "{}", element);
+        }
+        for element in ["x86_amx", "x86_mmx", "<2 x i32>", "[ 2 x i32 ]", "{ i32 }", "%opaque", "label", "token", "metadata"] {
+            let element: crate::types::first_class::Type = element.parse().unwrap();
+            assert_eq!(VectorType::new(4, element.clone()), Err(TypeCreationError::InvalidVectorElement(element.clone())));
+            assert_eq!(VectorType::new_scalable(4, element.clone()), Err(TypeCreationError::InvalidVectorElement(element)));
+        }
+        assert_eq!(VectorType::new(0, IntegerType(32)), Err(TypeCreationError::EmptyVector));
+        assert_eq!(VectorType::new_scalable(0, IntegerType(32)), Err(TypeCreationError::EmptyVector));
+        // Void and function types aren't first class, so they can't even be passed as elements.
+        assert!("<2 x void>".parse::<crate::types::Type>().is_err() && "<2 x i32 ()>".parse::<crate::types::Type>().is_err());
+    }
+}
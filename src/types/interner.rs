@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{LazyLock, Mutex};
+
+use crate::IRElement;
+
+use super::first_class::Type;
+
+/// A handle to an interned type, a first-class type unless `T` says otherwise.
+///
+/// All handles come from one interner for the whole program, which stores each distinct type
+/// once and keeps it until the program ends. Equal types therefore have the same handle:
+/// handles are `Copy`, and comparing and hashing them is O(1). Composite types hold handles
+/// to their parts, so interning a type only looks at its outermost level.
+pub struct TypeId<T: 'static = Type>(&'static Interned<T>);
+
+struct Interned<T> {
+    hash: u64,
+    _type: T
+}
+
+/// Interned types by their hash.
+type Table<T> = Mutex<HashMap<u64, Vec<&'static Interned<T>>>>;
+
+static FIRST_CLASS_TYPES: LazyLock<Table<Type>> = LazyLock::new(Default::default);
+static TYPES: LazyLock<Table<crate::types::Type>> = LazyLock::new(Default::default);
+
+fn intern<T: Eq + Hash + Sync>(table: &Table<T>, _type: T) -> &'static Interned<T> {
+    let mut hasher = DefaultHasher::new();
+    _type.hash(&mut hasher);
+    let hash = hasher.finish();
+    let mut table = table.lock().unwrap_or_else(|it| it.into_inner());
+    let candidates = table.entry(hash).or_default();
+    if let Some(interned) = candidates.iter().find(|it| it._type == _type) { return interned; }
+    let interned: &'static Interned<T> = Box::leak(Box::new(Interned { hash, _type }));
+    candidates.push(interned);
+    return interned;
+}
+
+impl TypeId {
+    pub fn new(_type: impl Into<Type>) -> TypeId {
+        return TypeId(intern(&FIRST_CLASS_TYPES, _type.into()));
+    }
+}
+
+impl TypeId<crate::types::Type> {
+    /// A handle to any type, e.g. a function type that a pointer points to.
+    pub fn new_any(_type: impl Into<crate::types::Type>) -> TypeId<crate::types::Type> {
+        return TypeId(intern(&TYPES, _type.into()));
+    }
+}
+
+impl<T> TypeId<T> {
+    pub fn get(&self) -> &'static T {
+        return &self.0._type;
+    }
+}
+
+impl<T> Clone for TypeId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TypeId<T> {}
+
+impl<T> Deref for TypeId<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        return self.get();
+    }
+}
+
+impl<T: Into<Type>> From<T> for TypeId {
+    fn from(_type: T) -> Self {
+        return TypeId::new(_type);
+    }
+}
+
+// Equal types are interned once, so the handles are equal exactly if they are the same.
+impl<T> PartialEq for TypeId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl<T> Eq for TypeId<T> {}
+
+impl<T> Hash for TypeId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0.hash);
+    }
+}
+
+impl<T: PartialEq> PartialEq<T> for TypeId<T> {
+    fn eq(&self, other: &T) -> bool {
+        self.get() == other
+    }
+}
+
+impl<T: Debug> Debug for TypeId<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.get(), f)
+    }
+}
+
+impl IRElement for TypeId {}
+
+impl<T: Display> Display for TypeId<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.get(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::first_class::aggregate::{ArrayType, StructType};
+    use crate::types::first_class::single_value::{IntegerType, PointerType};
+
+    #[test]
+    fn equal_types_are_interned_once() {
+        let a = TypeId::new(ArrayType::new(4, IntegerType(32)).unwrap());
+        let b = TypeId::from(ArrayType::new(4, IntegerType(32)).unwrap());
+        assert!(std::ptr::eq(a.0, b.0));
+        let copy = a;
+        assert_eq!(copy, a);
+        assert_ne!(a, TypeId::new(ArrayType::new(4, IntegerType(64)).unwrap()));
+    }
+
+    #[test]
+    fn composite_types_share_their_parts() {
+        let field = TypeId::new(ArrayType::new(4, IntegerType(32)).unwrap());
+        let pair = StructType::new(vec![field, field]).unwrap();
+        assert!(pair.fields().iter().all(|it| std::ptr::eq(it.0, field.0)));
+        assert_eq!(pair.to_string(), "{ [ 4 x i32 ], [ 4 x i32 ] }");
+    }
+
+    #[test]
+    fn pointees_are_interned_too() {
+        let function = crate::types::FunctionType::new(crate::types::Type::Void, vec![]);
+        let a = PointerType::new(function.clone());
+        let b = PointerType::new(function);
+        assert!(std::ptr::eq(a.pointee().unwrap(), b.pointee().unwrap()));
+        assert_eq!(TypeId::new(a), TypeId::new(b));
+    }
+}
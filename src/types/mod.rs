@@ -4,12 +4,15 @@ use crate::IRElement;
 use crate::module::function::Function;
 
 pub mod first_class;
+pub mod interner;
+pub mod parse;
+pub mod rust_type;
 
 pub trait ReturnType {
-    fn return_type(&self) -> first_class::Type;
+    fn return_type(&self) -> interner::TypeId;
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
     Void,
     Function(FunctionType),
@@ -112,9 +115,21 @@ impl From<first_class::aggregate::StructType> for Type {
     }
 }
 
-impl From<first_class::aggregate::OpaqueStructType> for Type {
-    fn from(_type: first_class::aggregate::OpaqueStructType) -> Self {
-        return Type::from(first_class::aggregate::Type::OpaqueStruct(_type));
+impl From<first_class::aggregate::NamedStructType> for Type {
+    fn from(_type: first_class::aggregate::NamedStructType) -> Self {
+        return Type::from(first_class::aggregate::Type::NamedStruct(_type));
+    }
+}
+
+impl Type {
+    pub fn is_void(&self) -> bool {
+        matches!(self, Self::Void)
+    }
+    pub fn is_function(&self) -> bool {
+        matches!(self, Self::Function(_))
+    }
+    pub fn first_class(&self) -> Option<&first_class::Type> {
+        if let Self::FirstClass(_type) = self { Some(_type) } else { None }
     }
 }
 
@@ -131,15 +146,29 @@ impl Display for Type {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct FunctionType { 
-    return_type: first_class::Type,
-    argument_types: Vec<first_class::Type>
+    pub(crate) return_type: Box<Type>,
+    pub(crate) argument_types: Vec<interner::TypeId>,
+    pub(crate) variadic: bool
 }
 
 impl FunctionType {
-    pub fn new(return_type: first_class::Type, argument_types: Vec<first_class::Type>) -> Self {
-        FunctionType { return_type, argument_types }
+    pub fn new(return_type: Type, argument_types: Vec<interner::TypeId>) -> Self {
+        FunctionType { return_type: Box::new(return_type), argument_types, variadic: false }
+    }
+    /// A function type taking `argument_types` followed by any number of further arguments (`...`).
+    pub fn new_variadic(return_type: Type, argument_types: Vec<interner::TypeId>) -> Self {
+        FunctionType { return_type: Box::new(return_type), argument_types, variadic: true }
+    }
+    pub fn return_type(&self) -> &Type {
+        &self.return_type
+    }
+    pub fn argument_types(&self) -> &[interner::TypeId] {
+        &self.argument_types
+    }
+    pub fn is_variadic(&self) -> bool {
+        self.variadic
     }
 }
 
@@ -148,19 +177,23 @@ impl IRElement for FunctionType {}
 impl Display for FunctionType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         Display::fmt(&self.return_type, f)?;
-        f.write_str(" ( ")?;
+        f.write_str(" (")?;
         for (i, argument_type) in self.argument_types.iter().enumerate() {
             Display::fmt(argument_type, f)?;
             if i < (self.argument_types.len() - 1) { f.write_str(", ")?; }
         }
-        f.write_str(" )")?;
+        if self.variadic {
+            if !self.argument_types.is_empty() { f.write_str(", ")?; }
+            f.write_str("...")?;
+        }
+        f.write_str(")")?;
         Ok(())
     }
 }
 
 impl From<&Function<'_>> for FunctionType {
     fn from(function: &Function) -> Self {
-        FunctionType { return_type: function.return_type.clone(), argument_types: function.arguments.iter().map(|it| it.return_type.clone()).collect() }
+        FunctionType { return_type: Box::new(function.return_type.clone()), argument_types: function.arguments.iter().map(|it| it.return_type).collect(), variadic: function.variadic }
     }
 }
 
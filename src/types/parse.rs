@@ -0,0 +1,476 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::{Type, FunctionType, first_class};
+use super::interner::TypeId;
+use super::first_class::{single_value, aggregate};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeParseErrorKind {
+    UnexpectedEnd,
+    UnexpectedCharacter(char),
+    Expected(&'static str),
+    UnknownType(String),
+    IllegalNumber,
+    NotFirstClass,
+    WrongType(&'static str),
+    InvalidType(first_class::TypeCreationError),
+    /// A pointer to `void`, `label`, `token`, `metadata` or `ptr`.
+    InvalidPointee(Type),
+    InvalidName(crate::identifier::ParseErrorKind)
+}
+
+/// A type that could not be parsed, with the byte offset at which parsing failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeParseError {
+    pub position: usize,
+    pub kind: TypeParseErrorKind
+}
+
+impl Display for TypeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            TypeParseErrorKind::UnexpectedEnd => f.write_str("unexpected end of type")?,
+            TypeParseErrorKind::UnexpectedCharacter(c) => f.write_fmt(format_args!("unexpected character `{}`", c))?,
+            TypeParseErrorKind::Expected(token) => f.write_fmt(format_args!("expected {}", token))?,
+            TypeParseErrorKind::UnknownType(name) => f.write_fmt(format_args!("unknown type `{}`", name))?,
+            TypeParseErrorKind::IllegalNumber => f.write_str("illegal number")?,
+            TypeParseErrorKind::NotFirstClass => f.write_str("expected a first class type")?,
+            TypeParseErrorKind::WrongType(expected) => f.write_fmt(format_args!("expected {}", expected))?,
+            TypeParseErrorKind::InvalidType(error) => Display::fmt(error, f)?,
+            TypeParseErrorKind::InvalidPointee(pointee) => f.write_fmt(format_args!("`{}` can't be pointed to", pointee))?,
+            TypeParseErrorKind::InvalidName(kind) => f.write_fmt(format_args!("invalid type name: {}", kind))?
+        }
+        f.write_fmt(format_args!(" at position {}", self.position))
+    }
+}
+
+impl std::error::Error for TypeParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            TypeParseErrorKind::InvalidType(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+/// The widest integer type LLVM supports, `i8388608`.
+const MAX_INTEGER_WIDTH: usize = 1 << 23;
+
+/// Whether `_type*` is a valid pointer type. Opaque pointers are written as `ptr` instead.
+fn is_valid_pointee(_type: &Type) -> bool {
+    match _type {
+        Type::Void => false,
+        Type::FirstClass(first_class::Type::Label(_) | first_class::Type::Token(_) | first_class::Type::Metadata(_)) => false,
+        Type::FirstClass(first_class::Type::SingleValue(single_value::Type::Pointer(pointer))) => !pointer.is_opaque(),
+        _ => true
+    }
+}
+
+/// Looks up the named struct a `%name` reference refers to.
+pub type NamedStructResolver<'a> = dyn Fn(&[u8]) -> Option<aggregate::NamedStructType> + 'a;
+
+/// Parses the textual form of a type, e.g. `{ i32, %Node* }` or `<vscale x 4 x float>`.
+///
+/// Named structs are looked up with the resolver if one is given. Otherwise, or if the
+/// resolver doesn't know a name, an opaque `NamedStructType` is created for it.
+pub struct TypeParser<'a> {
+    input: &'a str,
+    position: usize,
+    resolver: Option<&'a NamedStructResolver<'a>>,
+    named_structs: HashMap<Vec<u8>, aggregate::NamedStructType>
+}
+
+impl<'a> TypeParser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        TypeParser { input, position: 0, resolver: None, named_structs: HashMap::new() }
+    }
+    pub fn with_resolver(mut self, resolver: &'a NamedStructResolver<'a>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+    /// Parses the whole input as a single type.
+    pub fn parse(mut self) -> Result<Type, TypeParseError> {
+        let _type = self.parse_type()?;
+        self.skip_whitespace();
+        if let Some(c) = self.peek() { return Err(self.error(TypeParseErrorKind::UnexpectedCharacter(c))); }
+        Ok(_type)
+    }
+
+    fn error(&self, kind: TypeParseErrorKind) -> TypeParseError {
+        TypeParseError { position: self.position, kind }
+    }
+    fn invalid_type(&self, start: usize, error: first_class::TypeCreationError) -> TypeParseError {
+        TypeParseError { position: start, kind: TypeParseErrorKind::InvalidType(error) }
+    }
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.position = self.input.len() - trimmed.len();
+    }
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            return true;
+        }
+        return false;
+    }
+    fn expect(&mut self, token: &'static str, description: &'static str) -> Result<(), TypeParseError> {
+        if self.eat(token) { return Ok(()); }
+        if self.peek().is_none() { return Err(self.error(TypeParseErrorKind::UnexpectedEnd)); }
+        Err(self.error(TypeParseErrorKind::Expected(description)))
+    }
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if rest.starts_with(keyword) && !rest[keyword.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            self.position += keyword.len();
+            return true;
+        }
+        return false;
+    }
+    fn parse_number(&mut self) -> Result<usize, TypeParseError> {
+        self.skip_whitespace();
+        let digits = self.rest().len() - self.rest().trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 {
+            if self.peek().is_none() { return Err(self.error(TypeParseErrorKind::UnexpectedEnd)); }
+            return Err(self.error(TypeParseErrorKind::Expected("a number")));
+        }
+        let number = self.rest()[..digits].parse().map_err(|_| self.error(TypeParseErrorKind::IllegalNumber))?;
+        self.position += digits;
+        Ok(number)
+    }
+    fn parse_address_space(&mut self) -> Result<usize, TypeParseError> {
+        self.expect("(", "`(`")?;
+        let address_space = self.parse_number()?;
+        self.expect(")", "`)`")?;
+        Ok(address_space)
+    }
+    fn parse_first_class(&mut self) -> Result<first_class::Type, TypeParseError> {
+        self.skip_whitespace();
+        let position = self.position;
+        match self.parse_type()? {
+            Type::FirstClass(_type) => Ok(_type),
+            _ => Err(TypeParseError { position, kind: TypeParseErrorKind::NotFirstClass })
+        }
+    }
+    /// Parses a name after `%` the way identifiers are parsed, so quoted names can hold any bytes.
+    fn parse_name(&mut self) -> Result<Vec<u8>, TypeParseError> {
+        let rest = self.rest();
+        let length = match rest.strip_prefix('"') {
+            // Quotes in a name are escaped, so the next one ends it.
+            Some(quoted) => quoted.find('"').ok_or(TypeParseError { position: self.input.len(), kind: TypeParseErrorKind::UnexpectedEnd })? + 2,
+            None => rest.len() - rest.trim_start_matches(crate::identifier::is_char_valid).len()
+        };
+        if length == 0 { return Err(self.error(TypeParseErrorKind::Expected("a type name"))); }
+        let name = crate::identifier::parse_name(&rest[..length])
+            .map_err(|(offset, kind)| TypeParseError { position: self.position + offset, kind: TypeParseErrorKind::InvalidName(kind) })?;
+        self.position += length;
+        Ok(name.into_owned())
+    }
+    fn named_struct(&mut self, name: Vec<u8>) -> aggregate::NamedStructType {
+        if let Some(named_struct) = self.named_structs.get(&name) { return named_struct.clone(); }
+        let named_struct = self.resolver.and_then(|resolver| resolver(&name))
+            .unwrap_or_else(|| aggregate::NamedStructType::new_opaque(&name).expect("parsed names are valid"));
+        self.named_structs.insert(name, named_struct.clone());
+        named_struct
+    }
+    fn parse_struct_body(&mut self, close: &'static str, description: &'static str) -> Result<Vec<TypeId>, TypeParseError> {
+        let mut types = Vec::new();
+        if self.eat(close) { return Ok(types); }
+        loop {
+            types.push(self.parse_first_class()?.into());
+            if self.eat(close) { return Ok(types); }
+            self.expect(",", description)?;
+        }
+    }
+    fn parse_type(&mut self) -> Result<Type, TypeParseError> {
+        let mut _type = self.parse_base_type()?;
+        loop {
+            self.skip_whitespace();
+            let position = self.position;
+            let invalid_pointee = |_type: Type| TypeParseError { position, kind: TypeParseErrorKind::InvalidPointee(_type) };
+            if self.eat("*") {
+                if !is_valid_pointee(&_type) { return Err(invalid_pointee(_type)); }
+                _type = single_value::PointerType::new(_type).into();
+            } else if self.eat_keyword("addrspace") {
+                let address_space = self.parse_address_space()?;
+                self.expect("*", "`*` after the address space of a pointer")?;
+                if !is_valid_pointee(&_type) { return Err(invalid_pointee(_type)); }
+                _type = single_value::PointerType::new_with_address_space(_type, address_space).into();
+            } else if self.eat("(") {
+                let mut argument_types = Vec::new();
+                let mut variadic = false;
+                if !self.eat(")") {
+                    loop {
+                        if self.eat("...") {
+                            variadic = true;
+                            self.expect(")", "`)` after `...`")?;
+                            break;
+                        }
+                        argument_types.push(self.parse_first_class()?.into());
+                        if self.eat(")") { break; }
+                        self.expect(",", "`,` or `)` in the argument list")?;
+                    }
+                }
+                _type = if variadic { FunctionType::new_variadic(_type, argument_types) } else { FunctionType::new(_type, argument_types) }.into();
+            } else {
+                return Ok(_type);
+            }
+        }
+    }
+    fn parse_base_type(&mut self) -> Result<Type, TypeParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let c = self.peek().ok_or_else(|| self.error(TypeParseErrorKind::UnexpectedEnd))?;
+        match c {
+            '%' => {
+                self.position += 1;
+                let name = self.parse_name()?;
+                return Ok(self.named_struct(name).into());
+            }
+            '[' => {
+                self.position += 1;
+                let size = self.parse_number()?;
+                if !self.eat_keyword("x") { return Err(self.error(TypeParseErrorKind::Expected("`x`"))); }
+                let element = self.parse_first_class()?;
+                self.expect("]", "`]`")?;
+                return aggregate::ArrayType::new(size, element).map(Type::from).map_err(|it| self.invalid_type(start, it));
+            }
+            '{' => {
+                self.position += 1;
+                let types = self.parse_struct_body("}", "`,` or `}` in the struct body")?;
+                return aggregate::StructType::new(types).map(Type::from).map_err(|it| self.invalid_type(start, it));
+            }
+            '<' => {
+                self.position += 1;
+                if self.eat("{") {
+                    let types = self.parse_struct_body("}", "`,` or `}` in the struct body")?;
+                    self.expect(">", "`>` after a packed struct")?;
+                    return aggregate::StructType::new_packed(types).map(Type::from).map_err(|it| self.invalid_type(start, it));
+                }
+                let scalable = self.eat_keyword("vscale");
+                if scalable && !self.eat_keyword("x") { return Err(self.error(TypeParseErrorKind::Expected("`x`"))); }
+                let factor = self.parse_number()?;
+                if !self.eat_keyword("x") { return Err(self.error(TypeParseErrorKind::Expected("`x`"))); }
+                let element = self.parse_first_class()?;
+                self.expect(">", "`>`")?;
+                return if scalable {
+                    single_value::VectorType::new_scalable(factor, element)
+                } else {
+                    single_value::VectorType::new(factor, element)
+                }.map(Type::from).map_err(|it| self.invalid_type(start, it));
+            }
+            _ => {}
+        }
+        let length = self.rest().len() - self.rest().trim_start_matches(|c: char| c.is_alphanumeric() || c == '_').len();
+        let keyword = &self.rest()[..length];
+        if length == 0 { return Err(self.error(TypeParseErrorKind::UnexpectedCharacter(c))); }
+        self.position += length;
+        Ok(match keyword {
+            "void" => Type::Void,
+            "label" => first_class::LabelType.into(),
+            "token" => first_class::TokenType.into(),
+            "metadata" => first_class::MetadataType.into(),
+            "x86_mmx" => single_value::X86MMXType.into(),
+            "x86_amx" => single_value::X86AMXType.into(),
+            "half" => single_value::FloatType::Half.into(),
+            "bfloat" => single_value::FloatType::BFloat.into(),
+            "float" => single_value::FloatType::Float.into(),
+            "double" => single_value::FloatType::Double.into(),
+            "fp128" => single_value::FloatType::FP128.into(),
+            "x86_fp80" => single_value::FloatType::X86FP80.into(),
+            "ppc_fp128" => single_value::FloatType::PPCFP128.into(),
+            "ptr" => {
+                let address_space = if self.eat_keyword("addrspace") { self.parse_address_space()? } else { 0 };
+                single_value::PointerType::new_opaque_with_address_space(address_space).into()
+            }
+            _ if keyword.starts_with('i') && keyword.len() > 1 && keyword[1..].chars().all(|c| c.is_ascii_digit()) => {
+                let width = keyword[1..].parse().map_err(|_| TypeParseError { position: start + 1, kind: TypeParseErrorKind::IllegalNumber })?;
+                if width == 0 || width > MAX_INTEGER_WIDTH { return Err(TypeParseError { position: start + 1, kind: TypeParseErrorKind::IllegalNumber }); }
+                single_value::IntegerType(width).into()
+            }
+            _ => return Err(TypeParseError { position: start, kind: TypeParseErrorKind::UnknownType(keyword.to_string()) })
+        })
+    }
+}
+
+/// Parses `s` as a type and narrows it with `narrow`, which yields `None` for the wrong kind of type.
+fn parse_as<T>(s: &str, expected: &'static str, narrow: impl FnOnce(Type) -> Option<T>) -> Result<T, TypeParseError> {
+    let position = s.len() - s.trim_start().len();
+    narrow(TypeParser::new(s).parse()?).ok_or(TypeParseError { position, kind: TypeParseErrorKind::WrongType(expected) })
+}
+
+impl FromStr for Type {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TypeParser::new(s).parse()
+    }
+}
+
+impl FromStr for FunctionType {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_as(s, "a function type", |it| match it { Type::Function(it) => Some(it), _ => None })
+    }
+}
+
+impl FromStr for first_class::Type {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let position = s.len() - s.trim_start().len();
+        match TypeParser::new(s).parse()? {
+            Type::FirstClass(it) => Ok(it),
+            _ => Err(TypeParseError { position, kind: TypeParseErrorKind::NotFirstClass })
+        }
+    }
+}
+
+impl FromStr for single_value::Type {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_as(s, "a single value type", |it| match it { Type::FirstClass(first_class::Type::SingleValue(it)) => Some(it), _ => None })
+    }
+}
+
+impl FromStr for single_value::IntegerType {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_as(s, "an integer type", |it| match it { Type::FirstClass(first_class::Type::SingleValue(single_value::Type::Integer(it))) => Some(it), _ => None })
+    }
+}
+
+impl FromStr for single_value::FloatType {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_as(s, "a floating point type", |it| match it { Type::FirstClass(first_class::Type::SingleValue(single_value::Type::Float(it))) => Some(it), _ => None })
+    }
+}
+
+impl FromStr for single_value::PointerType {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_as(s, "a pointer type", |it| match it { Type::FirstClass(first_class::Type::SingleValue(single_value::Type::Pointer(it))) => Some(it), _ => None })
+    }
+}
+
+impl FromStr for single_value::VectorType {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_as(s, "a vector type", |it| match it { Type::FirstClass(first_class::Type::SingleValue(single_value::Type::Vector(it))) => Some(it), _ => None })
+    }
+}
+
+impl FromStr for aggregate::Type {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_as(s, "an aggregate type", |it| match it { Type::FirstClass(first_class::Type::Aggregate(it)) => Some(it), _ => None })
+    }
+}
+
+impl FromStr for aggregate::ArrayType {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_as(s, "an array type", |it| match it { Type::FirstClass(first_class::Type::Aggregate(aggregate::Type::Array(it))) => Some(it), _ => None })
+    }
+}
+
+impl FromStr for aggregate::StructType {
+    type Err = TypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_as(s, "a literal struct type", |it| match it { Type::FirstClass(first_class::Type::Aggregate(aggregate::Type::Struct(it))) => Some(it), _ => None })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(s: &str) -> String {
+        s.parse::<Type>().unwrap().to_string()
+    }
+
+    fn error(s: &str) -> TypeParseError {
+        s.parse::<Type>().unwrap_err()
+    }
+
+    #[test]
+    fn types_print_as_parsed() {
+        for s in ["void", "i1", "i8388608", "x86_fp80", "i32*", "ptr", "ptr addrspace(3)", "%Node*", "i8 addrspace(1)*", "void (i32, ...)*",
+            "{ i32, [ 4 x <2 x float> ] }", "<{ i8, i32 }>", "<vscale x 4 x i32>", "i32 (i8*)"] {
+            assert_eq!(parsed(s), s);
+        }
+        assert_eq!(parsed("  [4x i8 ]"), "[ 4 x i8 ]");
+    }
+
+    #[test]
+    fn quoted_names_are_unescaped() {
+        let names = |s: &str| match s.parse::<Type>().unwrap() {
+            Type::FirstClass(first_class::Type::Aggregate(aggregate::Type::NamedStruct(it))) => it.name().to_string(),
+            _ => panic!("`{}` is not a named struct", s)
+        };
+        assert_eq!(names(r#"%"a b""#), "a b");
+        assert_eq!(names(r#"%"a\22b\\c""#), "a\"b\\c");
+        assert_eq!(error(r#"%"a\x""#), TypeParseError { position: 3, kind: TypeParseErrorKind::InvalidName(crate::identifier::ParseErrorKind::InvalidEscape) });
+        assert_eq!(error(r#"%"a"#).kind, TypeParseErrorKind::UnexpectedEnd);
+        assert_eq!(error(r#"%"""#).kind, TypeParseErrorKind::InvalidName(crate::identifier::ParseErrorKind::Empty));
+    }
+
+    #[test]
+    fn names_can_be_any_bytes() {
+        let Type::FirstClass(first_class::Type::Aggregate(aggregate::Type::NamedStruct(named))) = r#"%"\FFx""#.parse::<Type>().unwrap() else { panic!("not a named struct") };
+        assert_eq!(named.name_bytes(), b"\xFFx");
+        assert_eq!(named.to_string(), r#"%"\FFx""#);
+        assert_eq!(parsed("%a.b-c$_0"), "%a.b-c$_0");
+        assert_eq!(parsed("%0"), "%0");
+        // Plain names are ASCII only, so anything else has to be quoted.
+        assert_eq!(error("%é").kind, TypeParseErrorKind::Expected("a type name"));
+        assert_eq!(error("%aé"), TypeParseError { position: 2, kind: TypeParseErrorKind::UnexpectedCharacter('é') });
+        assert_eq!(error("%1a"), TypeParseError { position: 2, kind: TypeParseErrorKind::InvalidName(crate::identifier::ParseErrorKind::IllegalCharacter('a')) });
+    }
+
+    #[test]
+    fn named_structs_are_resolved_once() {
+        let pair = aggregate::NamedStructType::new_opaque("pair").unwrap();
+        let resolver = |name: &[u8]| (name == b"pair").then(|| pair.clone());
+        let _type = TypeParser::new("{ %pair*, %other*, %other }").with_resolver(&resolver).parse().unwrap();
+        let Type::FirstClass(first_class::Type::Aggregate(aggregate::Type::Struct(_type))) = _type else { panic!("not a struct") };
+        assert_eq!(_type.fields()[0].pointee().unwrap(), &Type::from(pair));
+        assert_eq!(_type.fields()[1].pointee().unwrap(), &Type::from(_type.fields()[2].get().clone()));
+    }
+
+    #[test]
+    fn invalid_pointers_are_rejected() {
+        for (s, position) in [("void*", 4), ("label *", 6), ("token*", 5), ("metadata*", 8), ("ptr*", 3), ("ptr addrspace(1) addrspace(2)*", 17)] {
+            let error = error(s);
+            assert!(matches!(error.kind, TypeParseErrorKind::InvalidPointee(_)), "{} was accepted", s);
+            assert_eq!(error.position, position, "{}", s);
+        }
+        assert_eq!(error("void*").to_string(), "`void` can't be pointed to at position 4");
+    }
+
+    #[test]
+    fn integer_widths_are_bounded() {
+        assert_eq!(error("i0"), TypeParseError { position: 1, kind: TypeParseErrorKind::IllegalNumber });
+        assert_eq!(error("i8388609"), TypeParseError { position: 1, kind: TypeParseErrorKind::IllegalNumber });
+        assert_eq!(error("i99999999999999999999999").kind, TypeParseErrorKind::IllegalNumber);
+    }
+
+    #[test]
+    fn malformed_types_are_rejected() {
+        assert_eq!(error("[4 x void]").kind, TypeParseErrorKind::NotFirstClass);
+        assert!(matches!(error("[4 x label]").kind, TypeParseErrorKind::InvalidType(first_class::TypeCreationError::InvalidArrayElement(_))));
+        assert_eq!(error("<0 x i32>").kind, TypeParseErrorKind::InvalidType(first_class::TypeCreationError::EmptyVector));
+        assert_eq!(error("{ i32, }"), TypeParseError { position: 7, kind: TypeParseErrorKind::UnexpectedCharacter('}') });
+        assert_eq!(error("i32 i32"), TypeParseError { position: 4, kind: TypeParseErrorKind::UnexpectedCharacter('i') });
+        assert_eq!(error("float32").kind, TypeParseErrorKind::UnknownType("float32".to_string()));
+        assert_eq!("i32".parse::<single_value::FloatType>().unwrap_err().kind, TypeParseErrorKind::WrongType("a floating point type"));
+    }
+}
@@ -0,0 +1,167 @@
+//! Mapping of Rust types to the LLVM types with the same memory layout.
+//!
+//! `#[repr(C)]` structs can implement these traits with `#[derive(LlvmType, LlvmConstant)]`
+//! from the `derive` feature. Pointer-sized integers map to the width of the host.
+
+#[cfg(feature = "derive")]
+pub use llvm_ir_generator_derive::{LlvmType, LlvmConstant};
+
+use std::cell::RefCell;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use crate::constant::{Constant, simple::{IntegerConstant, FloatConstant}, complex::ArrayConstant};
+
+use super::first_class::{Type, TypeCreationError, single_value::{IntegerType, FloatType, PointerType}, aggregate::{ArrayType, NamedStructType, StructType}};
+
+/// A Rust type with a matching LLVM type.
+pub trait LlvmType {
+    fn llvm_type() -> Type;
+}
+
+/// A Rust type whose values can be written as LLVM constants of its `LlvmType`.
+pub trait LlvmConstant: LlvmType {
+    fn llvm_constant(&self) -> Constant<'static>;
+}
+
+macro_rules! integer {
+    ($($rust:ty => $bits:expr),*) => {$(
+        impl LlvmType for $rust {
+            fn llvm_type() -> Type {
+                return IntegerType($bits).into();
+            }
+        }
+        impl LlvmConstant for $rust {
+            fn llvm_constant(&self) -> Constant<'static> {
+                // `u128` wraps around to the same bits.
+                return IntegerConstant::from_i128(IntegerType($bits), *self as i128).expect("Rust integers fit their own width").into();
+            }
+        }
+    )*};
+}
+
+integer!(u8 => 8, i8 => 8, u16 => 16, i16 => 16, u32 => 32, i32 => 32, u64 => 64, i64 => 64, u128 => 128, i128 => 128,
+    usize => usize::BITS as usize, isize => isize::BITS as usize);
+
+/// `bool` is one byte in memory, like clang's `_Bool` in structs.
+impl LlvmType for bool {
+    fn llvm_type() -> Type {
+        return IntegerType(8).into();
+    }
+}
+
+impl LlvmConstant for bool {
+    fn llvm_constant(&self) -> Constant<'static> {
+        return IntegerConstant::from_u64(IntegerType(8), *self as u64).expect("booleans fit in a byte").into();
+    }
+}
+
+impl LlvmType for f32 {
+    fn llvm_type() -> Type {
+        return FloatType::Float.into();
+    }
+}
+
+impl LlvmConstant for f32 {
+    fn llvm_constant(&self) -> Constant<'static> {
+        return FloatConstant::from_f32(*self).into();
+    }
+}
+
+impl LlvmType for f64 {
+    fn llvm_type() -> Type {
+        return FloatType::Double.into();
+    }
+}
+
+impl LlvmConstant for f64 {
+    fn llvm_constant(&self) -> Constant<'static> {
+        return FloatConstant::from_f64(*self).into();
+    }
+}
+
+/// `void*` is written as `i8*`.
+impl LlvmType for std::ffi::c_void {
+    fn llvm_type() -> Type {
+        return IntegerType(8).into();
+    }
+}
+
+impl<T: LlvmType + ?Sized> LlvmType for *const T {
+    fn llvm_type() -> Type {
+        return PointerType::new(T::llvm_type()).into();
+    }
+}
+
+impl<T: LlvmType + ?Sized> LlvmType for *mut T {
+    fn llvm_type() -> Type {
+        return PointerType::new(T::llvm_type()).into();
+    }
+}
+
+impl<T: LlvmType + ?Sized> LlvmType for &T {
+    fn llvm_type() -> Type {
+        return PointerType::new(T::llvm_type()).into();
+    }
+}
+
+impl<T: LlvmType + ?Sized> LlvmType for &mut T {
+    fn llvm_type() -> Type {
+        return PointerType::new(T::llvm_type()).into();
+    }
+}
+
+impl<T: LlvmType + ?Sized> LlvmType for Option<&T> {
+    fn llvm_type() -> Type {
+        return PointerType::new(T::llvm_type()).into();
+    }
+}
+
+impl<T: LlvmType + ?Sized> LlvmType for std::ptr::NonNull<T> {
+    fn llvm_type() -> Type {
+        return PointerType::new(T::llvm_type()).into();
+    }
+}
+
+impl<T: LlvmType, const N: usize> LlvmType for [T; N] {
+    fn llvm_type() -> Type {
+        return ArrayType::new(N, T::llvm_type()).expect("Rust types map to valid element types").into();
+    }
+}
+
+impl<T: LlvmConstant, const N: usize> LlvmConstant for [T; N] {
+    fn llvm_constant(&self) -> Constant<'static> {
+        let elements = self.iter().map(LlvmConstant::llvm_constant).collect();
+        return ArrayConstant::new(T::llvm_type(), elements).expect("elements have the array's element type").into();
+    }
+}
+
+thread_local! {
+    /// Named structs declared by this thread whose declaration hasn't finished yet.
+    static DECLARING: RefCell<Vec<(&'static OnceLock<NamedStructType>, NamedStructType)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The named struct of a derived `LlvmType`, declared once and kept in `named_struct`.
+///
+/// Other threads only get to see the struct once its body is set. While the body is built,
+/// fields that point back to the struct get the declaration in progress. One lock is held
+/// for declaring all derived structs, so structs that point to each other can't deadlock.
+#[doc(hidden)]
+pub fn derived_named_struct(named_struct: &'static OnceLock<NamedStructType>, name: &str, body: fn() -> Result<StructType, TypeCreationError>) -> NamedStructType {
+    static LOCK: Mutex<()> = Mutex::new(());
+    if let Some(declared) = named_struct.get() { return declared.clone(); }
+    let declaring = DECLARING.with(|it| it.borrow().iter().find(|(cell, _)| std::ptr::eq(*cell, named_struct)).map(|(_, it)| it.clone()));
+    if let Some(declaring) = declaring { return declaring; }
+    let outermost = DECLARING.with(|it| it.borrow().is_empty());
+    let _guard = outermost.then(|| LOCK.lock().unwrap_or_else(PoisonError::into_inner));
+    if let Some(declared) = named_struct.get() { return declared.clone(); }
+    let declared = NamedStructType::new_opaque(name).expect("Rust type names are valid struct names");
+    DECLARING.with(|it| it.borrow_mut().push((named_struct, declared.clone())));
+    let body = body();
+    // Structs declared on the way are published together with the outermost one, when all bodies are set.
+    let finished = if outermost { DECLARING.with(|it| it.take()) } else { Vec::new() };
+    let _ = declared.set_body(body.expect("Rust types map to valid field types"));
+    for (cell, declared) in finished {
+        let _ = cell.set(declared);
+    }
+    return declared;
+}